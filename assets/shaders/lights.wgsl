@@ -2,6 +2,7 @@ struct PointLight {
     position: vec3f,
     radius: f32,
     color: vec3f,
+    layer_mask: u32,
 };
 
 struct PointLightStorage {
@@ -14,6 +15,7 @@ var<storage, read> point_lights: PointLightStorage;
 
 struct DirectionalLight {
     direction: vec3f,
+    layer_mask: u32,
     color: vec3f,
 };
 
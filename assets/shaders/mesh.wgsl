@@ -3,6 +3,7 @@ const MAX_BONE_INFLUENCE = 4u;
 
 struct Instance {
     bone_transform_index: i32,
+    layers: u32,
     transform: mat4x4f,
 };
 
@@ -10,6 +10,7 @@ struct VertexOut {
     @location(0) world_position: vec3f,
     @location(1) normal: vec3f,
     @location(2) uv: vec2f,
+    @location(3) @interpolate(flat) layers: u32,
 };
 
 struct MaterialProperties {
@@ -79,6 +80,7 @@ fn vs_main(vertex: VertexIn) -> VertexOut {
         camera.view *
         vec4f(out.world_position, 1.0);
     out.uv = vertex.uv0;
+    out.layers = instance.layers;
     return out;
 }
 
@@ -99,6 +101,9 @@ fn fs_main(in: VertexOut) -> @location(0) vec4f {
     // Point lights
     for (var i = 0u; i < point_lights.size; i++) {
         let light = point_lights.lights[i];
+        if (light.layer_mask & in.layers) == 0u {
+            continue;
+        }
         let to_light = light.position - in.world_position;
         let dist = length(to_light);
         if dist > light.radius {
@@ -116,6 +121,9 @@ fn fs_main(in: VertexOut) -> @location(0) vec4f {
     // Directional lights
     for (var i = 0u; i < directional_lights.size; i++) {
         let light = directional_lights.lights[i];
+        if (light.layer_mask & in.layers) == 0u {
+            continue;
+        }
         let l = normalize(-light.direction);
         color += pbr(n, v, l, albedo.rgb, light.color, material);
     }
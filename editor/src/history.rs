@@ -0,0 +1,211 @@
+use engine::scene::{GameObject, GameObjectSnapshot, Scene, SceneId, SceneManager};
+use uuid::Uuid;
+
+/// Undo history of the changes made to the scene from the editor.
+///
+/// Changes are kept as snapshots of the game objects they touch, taken before the change and
+/// once it's done. Changes recorded while an interaction goes on, like dragging a gizmo or a
+/// value, are merged into one when they have the same name and touch the same game objects.
+/// Changes are made to the active scene, and the history is cleared when another scene becomes
+/// active.
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+    pending: Option<Change>,
+    scene: Option<SceneId>,
+}
+
+struct Change {
+    name: String,
+    game_objects: Vec<Uuid>,
+    /// State of the game objects before and after the change, none for game objects that
+    /// don't exist.
+    before: Vec<Option<GameObjectSnapshot>>,
+    after: Vec<Option<GameObjectSnapshot>>,
+}
+
+impl Change {
+    fn restore(scene: &mut Scene, game_objects: &[Uuid], states: &[Option<GameObjectSnapshot>]) {
+        for (id, state) in game_objects.iter().zip(states) {
+            match state {
+                Some(snapshot) => {
+                    scene.restore_snapshot(snapshot);
+                }
+                None => {
+                    if let Some(game_object) = scene.get_game_object_by_uuid(*id) {
+                        scene.delete_game_object(game_object);
+                        scene.delete_game_objects();
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl History {
+    /// Records a change to game objects, before they're changed.
+    pub fn record(
+        &mut self,
+        scene: &Scene,
+        name: impl Into<String>,
+        game_objects: impl IntoIterator<Item = GameObject>,
+    ) {
+        let mut ids = Vec::new();
+        for game_object in game_objects {
+            let id = scene.get_game_object_uuid(scene.snapshot_root(game_object));
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        let name = name.into();
+        if self
+            .pending
+            .as_ref()
+            .is_some_and(|change| change.name == name && change.game_objects == ids)
+        {
+            return;
+        }
+        let before = ids
+            .iter()
+            .map(|id| {
+                scene
+                    .get_game_object_by_uuid(*id)
+                    .map(|go| scene.snapshot(go))
+            })
+            .collect();
+        self.start(scene, name, ids, before);
+    }
+
+    /// Records a change to a game object from a snapshot taken before it changed, for changes
+    /// found once they're made.
+    pub fn record_snapshot(
+        &mut self,
+        scene: &Scene,
+        name: impl Into<String>,
+        snapshot: GameObjectSnapshot,
+    ) {
+        let name = name.into();
+        let ids = vec![snapshot.id()];
+        if self
+            .pending
+            .as_ref()
+            .is_some_and(|change| change.name == name && change.game_objects == ids)
+        {
+            return;
+        }
+        self.start(scene, name, ids, vec![Some(snapshot)]);
+    }
+
+    /// Records the creation of a game object, once it's created.
+    pub fn record_created(
+        &mut self,
+        scene: &Scene,
+        name: impl Into<String>,
+        game_object: GameObject,
+    ) {
+        let id = scene.get_game_object_uuid(game_object);
+        self.start(scene, name.into(), vec![id], vec![None]);
+    }
+
+    fn start(
+        &mut self,
+        scene: &Scene,
+        name: String,
+        game_objects: Vec<Uuid>,
+        before: Vec<Option<GameObjectSnapshot>>,
+    ) {
+        // Game objects the recorded change touches are as they were before the new change
+        self.finish_with(scene, |id| {
+            let index = game_objects.iter().position(|other| *other == id)?;
+            Some(before[index].clone())
+        });
+        self.redo.clear();
+        self.pending = Some(Change {
+            name,
+            game_objects,
+            before,
+            after: Vec::new(),
+        });
+    }
+
+    /// Ends the recorded change once the interaction that made it is over. Called every frame,
+    /// after the game objects to delete are deleted.
+    pub fn update(&mut self, ctx: &egui::Context, scenes: &SceneManager) {
+        let active = scenes.active_scene();
+        if self.scene != Some(active) {
+            self.clear();
+            self.scene = Some(active);
+        }
+        let interacting = ctx.input(|input| input.pointer.any_down()) || ctx.wants_keyboard_input();
+        if !interacting {
+            self.finish(scenes.simulation_scene());
+        }
+    }
+
+    fn finish(&mut self, scene: &Scene) {
+        self.finish_with(scene, |_| None);
+    }
+
+    /// Ends the recorded change, with the state of its game objects taken from `known` when
+    /// it has them and from the scene otherwise.
+    fn finish_with(
+        &mut self,
+        scene: &Scene,
+        known: impl Fn(Uuid) -> Option<Option<GameObjectSnapshot>>,
+    ) {
+        let Some(mut change) = self.pending.take() else {
+            return;
+        };
+        change.after = change
+            .game_objects
+            .iter()
+            .map(|id| {
+                known(*id).unwrap_or_else(|| {
+                    scene
+                        .get_game_object_by_uuid(*id)
+                        .map(|go| scene.snapshot(go))
+                })
+            })
+            .collect();
+        self.undo.push(change);
+    }
+
+    pub fn undo(&mut self, scene: &mut Scene) {
+        self.finish(scene);
+        if let Some(change) = self.undo.pop() {
+            Change::restore(scene, &change.game_objects, &change.before);
+            self.redo.push(change);
+        }
+    }
+
+    pub fn redo(&mut self, scene: &mut Scene) {
+        self.finish(scene);
+        if let Some(change) = self.redo.pop() {
+            Change::restore(scene, &change.game_objects, &change.after);
+            self.undo.push(change);
+        }
+    }
+
+    /// Name of the change to undo, if any.
+    pub fn undo_name(&self) -> Option<&str> {
+        self.pending
+            .as_ref()
+            .or(self.undo.last())
+            .map(|change| change.name.as_str())
+    }
+
+    /// Name of the change to redo, if any.
+    pub fn redo_name(&self) -> Option<&str> {
+        if self.pending.is_some() {
+            return None;
+        }
+        self.redo.last().map(|change| change.name.as_str())
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.pending = None;
+    }
+}
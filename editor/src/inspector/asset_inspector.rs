@@ -3,6 +3,8 @@ use engine::context::GameContext;
 use engine::reflect_trait;
 use uuid::Uuid;
 
+use crate::history::History;
+
 #[reflect_trait]
 pub trait AssetInspector: Send + Sync {
     fn target_type_uuid(&self) -> Uuid;
@@ -12,7 +14,13 @@ pub trait AssetInspector: Send + Sync {
     fn has_context_menu(&self) -> bool {
         false
     }
-    fn show_context_menu(&self, ui: &mut Ui, game: &mut GameContext, asset_id: Uuid) {
-        let _ = (ui, game, asset_id);
+    fn show_context_menu(
+        &self,
+        ui: &mut Ui,
+        game: &mut GameContext,
+        history: &mut History,
+        asset_id: Uuid,
+    ) {
+        let _ = (ui, game, history, asset_id);
     }
 }
@@ -5,6 +5,7 @@ use engine::scene::Prefab;
 use engine::utils::TypeUuid;
 use uuid::Uuid;
 
+use crate::history::History;
 use crate::inspector::asset_inspector::{AssetInspector, ReflectAssetInspector};
 
 #[derive(Default, Clone, TypeUuid, Reflect)]
@@ -20,7 +21,13 @@ impl AssetInspector for PrefabInspector {
         true
     }
 
-    fn show_context_menu(&self, ui: &mut Ui, game: &mut GameContext, asset_id: Uuid) {
+    fn show_context_menu(
+        &self,
+        ui: &mut Ui,
+        game: &mut GameContext,
+        history: &mut History,
+        asset_id: Uuid,
+    ) {
         if ui.button("Import").clicked() {
             let Ok(asset) = game.assets.asset_registry.read().load_dyn_by_id(asset_id) else {
                 ui.close_menu();
@@ -30,10 +37,10 @@ impl AssetInspector for PrefabInspector {
                 ui.close_menu();
                 return;
             };
-            let prefab = prefab_ref.read();
-            game.scenes
-                .simulation_scene_mut()
-                .instantiate_prefab(&prefab, None);
+            let scene = game.scenes.simulation_scene_mut();
+            if let Some(root) = scene.instantiate_prefab(&prefab_ref, None) {
+                history.record_created(scene, "Instantiate Prefab", root);
+            }
             ui.close_menu();
         }
     }
@@ -1,3 +1,4 @@
+use crate::history::History;
 use crate::inspector::asset_inspector::{AssetInspector, ReflectAssetInspector};
 use egui::Ui;
 use engine::assets::material::Material;
@@ -23,7 +24,13 @@ impl AssetInspector for ShaderInspector {
         true
     }
 
-    fn show_context_menu(&self, ui: &mut Ui, game: &mut GameContext, asset_id: Uuid) {
+    fn show_context_menu(
+        &self,
+        ui: &mut Ui,
+        game: &mut GameContext,
+        _history: &mut History,
+        asset_id: Uuid,
+    ) {
         if ui.button("Create Material").clicked() {
             'cleanup: {
                 let Ok(asset) = game.assets.asset_registry.read().load_dyn_by_id(asset_id) else {
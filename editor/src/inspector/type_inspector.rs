@@ -2,7 +2,7 @@ use egui::Ui;
 use engine::context::ReadOnlyAssetContext;
 use engine::reflect::{Reflect, StructInfo};
 use engine::reflect_trait;
-use engine::scene::{GameObject, Scene};
+use engine::scene::{GameObject, LayerNames, Scene};
 use uuid::Uuid;
 
 #[reflect_trait]
@@ -22,6 +22,7 @@ pub trait TypeInspector: Send + Sync {
 pub struct InspectorContext<'a> {
     pub assets: &'a ReadOnlyAssetContext,
     pub scene: &'a Scene,
+    pub layer_names: &'a LayerNames,
     pub game_object: GameObject,
     pub parent: Option<GameObject>,
     pub type_info: &'a StructInfo,
@@ -1,10 +1,12 @@
 use crate::inspector::type_inspector::{InspectorContext, ReflectTypeInspector, TypeInspector};
 use crate::inspector::widgets::Widgets;
 use egui::Ui;
+use engine::assets::mesh::Mesh;
 use engine::component::{ColliderShape, ComponentCollider, Orientation};
 use engine::core::Ref;
 use engine::reflect::{Reflect, ReflectDefault};
-use engine::{type_uuids, TypeUuid};
+use engine::type_uuids;
+use engine::utils::TypeUuid;
 use nalgebra_glm as glm;
 use std::ops::DerefMut;
 use uuid::Uuid;
@@ -37,6 +39,9 @@ impl ColliderInspector {
                         ColliderShape::Capsule { .. } => "Capsule",
                         ColliderShape::Cuboid { .. } => "Cuboid",
                         ColliderShape::Cone { .. } => "Cone",
+                        ColliderShape::ConvexHull(_) => "Convex Hull",
+                        ColliderShape::TriMesh(_) => "Triangle Mesh",
+                        ColliderShape::Heightfield { .. } => "Heightfield",
                     })
                     .show_ui(ui, |ui| {
                         state.changed |= ui
@@ -76,6 +81,31 @@ impl ColliderInspector {
                                 "Cuboid",
                             )
                             .changed();
+                        state.changed |= ui
+                            .selectable_value(
+                                state.value,
+                                ColliderShape::ConvexHull(Default::default()),
+                                "Convex Hull",
+                            )
+                            .changed();
+                        state.changed |= ui
+                            .selectable_value(
+                                state.value,
+                                ColliderShape::TriMesh(Default::default()),
+                                "Triangle Mesh",
+                            )
+                            .changed();
+                        state.changed |= ui
+                            .selectable_value(
+                                state.value,
+                                ColliderShape::Heightfield {
+                                    mesh: Default::default(),
+                                    rows: 32,
+                                    columns: 32,
+                                },
+                                "Heightfield",
+                            )
+                            .changed();
                     });
             },
             |ui| {
@@ -136,6 +166,44 @@ impl ColliderInspector {
                                 ui.add(egui::DragValue::new(radius).speed(0.1)).changed();
                         });
                     }
+                    ColliderShape::ConvexHull(mesh) | ColliderShape::TriMesh(mesh) => {
+                        Widgets::inspector_prop_value(ui, "Mesh", |ui, _| {
+                            state.changed |= Widgets::asset_select_t(
+                                ui,
+                                &ctx.assets.asset_registry.read(),
+                                "collider_mesh",
+                                Some(Mesh::type_uuid()),
+                                mesh,
+                            )
+                            .changed();
+                        });
+                    }
+                    ColliderShape::Heightfield {
+                        mesh,
+                        rows,
+                        columns,
+                    } => {
+                        Widgets::inspector_prop_value(ui, "Mesh", |ui, _| {
+                            state.changed |= Widgets::asset_select_t(
+                                ui,
+                                &ctx.assets.asset_registry.read(),
+                                "collider_mesh",
+                                Some(Mesh::type_uuid()),
+                                mesh,
+                            )
+                            .changed();
+                        });
+                        Widgets::inspector_prop_value(ui, "Rows", |ui, _| {
+                            state.changed |= ui
+                                .add(egui::DragValue::new(rows).range(2..=u32::MAX))
+                                .changed();
+                        });
+                        Widgets::inspector_prop_value(ui, "Columns", |ui, _| {
+                            state.changed |= ui
+                                .add(egui::DragValue::new(columns).range(2..=u32::MAX))
+                                .changed();
+                        });
+                    }
                 };
             },
         );
@@ -164,6 +232,29 @@ impl TypeInspector for ColliderInspector {
             Widgets::inspector_prop_value(ui, "Density", |ui, _| {
                 changed |= ui.add(egui::DragValue::new(&mut value.density)).changed();
             });
+            Widgets::inspector_prop_value(ui, "Sensor", |ui, _| {
+                changed |= ui
+                    .add(egui::Checkbox::without_text(&mut value.sensor))
+                    .changed();
+            });
+            Widgets::inspector_prop_value(ui, "Collision Layers", |ui, _| {
+                changed |= Widgets::layer_mask(
+                    ui,
+                    (ctx.game_object.node, "collision_layers"),
+                    ctx.layer_names,
+                    &mut value.collision_layers,
+                )
+                .changed();
+            });
+            Widgets::inspector_prop_value(ui, "Collision Mask", |ui, _| {
+                changed |= Widgets::layer_mask(
+                    ui,
+                    (ctx.game_object.node, "collision_mask"),
+                    ctx.layer_names,
+                    &mut value.collision_mask,
+                )
+                .changed();
+            });
             if changed {
                 value.dirty = true;
             }
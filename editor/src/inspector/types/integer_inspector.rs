@@ -1,4 +1,5 @@
 use crate::inspector::type_inspector::{InspectorContext, ReflectTypeInspector, TypeInspector};
+use crate::inspector::widgets::Widgets;
 use egui::Ui;
 use engine::reflect::{Reflect, ReflectDefault, ReflectGenericInt};
 use engine::type_uuids;
@@ -15,6 +16,23 @@ impl TypeInspector for IntegerInspector {
     }
 
     fn show_inspector(&self, ui: &mut Ui, ctx: &InspectorContext, instance: &mut dyn Reflect) {
+        let layers_attr = match ctx.field_name {
+            Some(field) => ctx
+                .type_info
+                .fields
+                .get(field)
+                .and_then(|f| f.attrs.get("layers")),
+            None => ctx.type_info.attrs.get("layers"),
+        };
+        if let (Some(_), Some(mask)) = (layers_attr, instance.downcast_mut::<u32>()) {
+            Widgets::layer_mask(
+                ui,
+                (ctx.game_object.node, ctx.field_name),
+                ctx.layer_names,
+                mask,
+            );
+            return;
+        }
         let type_registry = ctx.assets.type_registry.read();
         let Some(meta) = type_registry.trait_meta::<ReflectGenericInt>(instance.uuid()) else {
             return;
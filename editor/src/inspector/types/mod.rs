@@ -7,6 +7,7 @@ mod game_object_ref_inspector;
 mod integer_inspector;
 mod rigid_body_inspector;
 mod string_inspector;
+mod tags_inspector;
 mod transform_inspector;
 mod uuid_inspector;
 mod vec_inspector;
@@ -76,6 +76,11 @@ impl TypeInspector for RigidBodyInspector {
                     .add(egui::Checkbox::without_text(&mut value.can_sleep))
                     .changed();
             });
+            Widgets::inspector_prop_value(ui, "CCD Enabled", |ui, _| {
+                changed |= ui
+                    .add(egui::Checkbox::without_text(&mut value.ccd_enabled))
+                    .changed();
+            });
             if changed {
                 value.dirty = true;
             }
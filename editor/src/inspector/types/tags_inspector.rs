@@ -0,0 +1,53 @@
+use std::collections::BTreeSet;
+
+use crate::inspector::type_inspector::{InspectorContext, ReflectTypeInspector, TypeInspector};
+use egui::{Key, TextEdit, Ui};
+use engine::reflect::{Reflect, ReflectDefault};
+use engine::type_uuids;
+use engine::utils::TypeUuid;
+use uuid::Uuid;
+
+#[derive(Default, Clone, TypeUuid, Reflect)]
+#[reflect(Default, TypeInspector)]
+pub struct TagsInspector;
+
+impl TypeInspector for TagsInspector {
+    fn target_type_uuids(&self) -> Vec<Uuid> {
+        type_uuids!(BTreeSet<String>)
+    }
+
+    fn show_inspector(&self, ui: &mut Ui, ctx: &InspectorContext, instance: &mut dyn Reflect) {
+        let Some(tags) = instance.downcast_mut::<BTreeSet<String>>() else {
+            return;
+        };
+        ui.vertical(|ui| {
+            let mut removed = None;
+            ui.horizontal_wrapped(|ui| {
+                for tag in tags.iter() {
+                    if ui.small_button(format!("{tag} ×")).clicked() {
+                        removed = Some(tag.clone());
+                    }
+                }
+            });
+            if let Some(tag) = removed {
+                tags.remove(&tag);
+            }
+
+            let id = ui.make_persistent_id((ctx.game_object.node, ctx.field_name, "new_tag"));
+            let mut new_tag = ui.data_mut(|d| d.get_temp::<String>(id).unwrap_or_default());
+            let response = TextEdit::singleline(&mut new_tag)
+                .hint_text("Add tag")
+                .desired_width(130.0)
+                .show(ui)
+                .response;
+            if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                let tag = new_tag.trim();
+                if !tag.is_empty() {
+                    tags.insert(tag.to_string());
+                }
+                new_tag.clear();
+            }
+            ui.data_mut(|d| d.insert_temp(id, new_tag));
+        });
+    }
+}
@@ -2,6 +2,7 @@ use crate::inspector::type_inspector::{InspectorContext, ReflectTypeInspector, T
 use crate::inspector::widgets::Widgets;
 use egui::Ui;
 use engine::component::ComponentTransform;
+use engine::math::Transform;
 use engine::reflect::{Reflect, ReflectDefault};
 use engine::type_uuids;
 use engine::utils::TypeUuid;
@@ -21,11 +22,12 @@ impl TypeInspector for TransformInspector {
     fn show_inspector(&self, ui: &mut Ui, ctx: &InspectorContext, instance: &mut dyn Reflect) {
         if let Some(t_comp) = instance.downcast_mut::<ComponentTransform>() {
             let mut changed = false;
-            let mut transform = ctx.scene.get_world_transform(ctx.game_object);
             let parent_transform = ctx
                 .parent
                 .map(|parent| ctx.scene.get_world_transform(parent))
                 .unwrap_or_default();
+            let mut transform: Transform =
+                (parent_transform.matrix * t_comp.transform.matrix).into();
             Widgets::inspector_prop_value(ui, "Position", |ui, _| {
                 changed |= Widgets::drag_float3(ui, 0.1, &mut transform.position);
             });
@@ -2,7 +2,7 @@ use egui;
 use egui::{Color32, DragValue, Frame, Id, Response, Shape, Stroke, StrokeKind, Ui, WidgetText};
 use engine::assets::{Asset, AssetAccess, AssetRef, AssetRegistry};
 use engine::component::ComponentID;
-use engine::scene::{GameObjectRef, Scene};
+use engine::scene::{GameObjectRef, LayerNames, Scene};
 use engine::utils::TypeUuid;
 use lazy_static::lazy_static;
 use nalgebra_glm::{Vec2, Vec3, Vec4};
@@ -178,6 +178,55 @@ impl Widgets {
         res.inner
     }
 
+    /// Picks the named layers of a layer mask. Bits of unnamed layers are left untouched.
+    pub fn layer_mask(
+        ui: &mut Ui,
+        id: impl std::hash::Hash,
+        layer_names: &LayerNames,
+        value: &mut u32,
+    ) -> Response {
+        let named_mask = layer_names
+            .iter()
+            .fold(0u32, |mask, (layer, _)| mask | 1 << layer);
+        let selected_text = match *value & named_mask {
+            0 => String::from("Nothing"),
+            mask if mask == named_mask => String::from("Everything"),
+            mask if mask.is_power_of_two() => layer_names
+                .name(mask.trailing_zeros() as usize)
+                .unwrap_or_default()
+                .to_string(),
+            _ => String::from("Mixed"),
+        };
+        let mut changed = false;
+        let mut res = egui::ComboBox::from_id_salt(id)
+            .truncate()
+            .width(100.0)
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                if ui.selectable_label(false, "Nothing").clicked() {
+                    *value &= !named_mask;
+                    changed = true;
+                }
+                if ui.selectable_label(false, "Everything").clicked() {
+                    *value |= named_mask;
+                    changed = true;
+                }
+                ui.separator();
+                for (layer, name) in layer_names.iter() {
+                    let mut on_layer = *value & 1 << layer != 0;
+                    if ui.checkbox(&mut on_layer, name).changed() {
+                        *value ^= 1 << layer;
+                        changed = true;
+                    }
+                }
+            })
+            .response;
+        if changed {
+            res.mark_changed();
+        }
+        res
+    }
+
     pub fn drag_float4(ui: &mut Ui, speed: f32, value: &mut Vec4) -> bool {
         Self::drag_floatn(ui, speed, &mut value.data.as_mut_slice()[0..4])
     }
@@ -1,6 +1,5 @@
 use std::env;
-use std::io::BufWriter;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use eframe::egui;
@@ -11,20 +10,21 @@ use transform_gizmo_egui::{GizmoMode, GizmoOrientation};
 use self::panel::*;
 pub use self::project_manager::*;
 use crate::camera::EditorCamera;
+use crate::history::History;
 use crate::task_id::TaskId;
 use eframe::{wgpu, NativeOptions};
-use egui::{include_image, Button, CornerRadius, Sense, Vec2};
+use egui::{include_image, Button, CornerRadius, Key, Modifiers, Sense, Vec2};
 use egui::{Align, Layout};
 use egui::{Color32, Frame, Margin, Shadow};
 use egui_tiles::{Container, Linear, LinearDir, Tiles, Tree};
 use egui_wgpu::wgpu::PowerPreference;
 use egui_wgpu::{SurfaceErrorAction, WgpuSetup, WgpuSetupCreateNew};
-use engine::context::{AssetContext, GameContext};
+use engine::context::{default_engine_assets_path, AssetContext, GameContext};
 use engine::core::Ref;
 use engine::error::BoxedError;
 use engine::input::{Input, InputState};
 use engine::render::{Camera, SceneRenderer, SceneRendererOptions};
-use engine::scene::Scene;
+use engine::scene::{self, LayerNames, LoadSceneMode, SaveGame, Scene, SceneFormat, LAYER_COUNT};
 use engine::*;
 use rapier3d::prelude::DebugRenderPipeline;
 use selection::{Selection, SelectionType};
@@ -39,6 +39,7 @@ use winit::platform::windows::EventLoopBuilderExtWindows;
 use winit::platform::x11::EventLoopBuilderExtX11;
 
 mod camera;
+mod history;
 mod icons;
 mod inspector;
 mod panel;
@@ -56,6 +57,7 @@ pub struct EditorApp {
     panels: Panels,
     physics_debug_pipeline: DebugRenderPipeline,
     project_manager: Ref<ProjectManager>,
+    show_layers: bool,
     state: EditorAppState,
 }
 
@@ -67,6 +69,7 @@ pub struct EditorAppState {
     pub camera: EditorCamera,
     pub game_aspect: Option<(u32, u32)>,
     pub selection: Selection,
+    pub history: History,
     pub viewport_size: (f32, f32),
     pub game_response: Option<egui::Response>,
     pub game_size: (f32, f32),
@@ -83,6 +86,7 @@ impl EditorAppState {
             camera: Default::default(),
             game_aspect: None,
             selection: Default::default(),
+            history: Default::default(),
             viewport_size: Default::default(),
             game_size: Default::default(),
             game_response: Default::default(),
@@ -116,13 +120,25 @@ impl EditorApp {
     ) -> Result<Self, BoxedError> {
         let tree = Self::create_tree();
         let project_path = project_path.into();
-        let asset_context = AssetContext::new(cc, project_path.join("assets"))?;
-        let game = GameContext::new(asset_context.clone());
+        let asset_context = AssetContext::new(
+            cc,
+            project_path.join("assets"),
+            default_engine_assets_path()?,
+        )?;
+        let mut game = GameContext::new(asset_context.clone());
         let project_manager = ProjectManager::new(
             asset_context,
             project_path,
             game.resources.background().clone(),
         )?;
+        game.resources.insert(LayerNames::new(
+            project_manager
+                .read()
+                .current_project()
+                .layers()
+                .iter()
+                .cloned(),
+        ));
         let panels = Panels::new(
             project_manager
                 .read()
@@ -141,6 +157,7 @@ impl EditorApp {
                 Default::default(),
             ),
             project_manager,
+            show_layers: false,
             state: EditorAppState::new(game),
         })
     }
@@ -218,28 +235,32 @@ impl eframe::App for EditorApp {
             camera.update_projection();
 
             {
-                let scene = scenes.simulation_scene();
-                scene_renderer.render_scene(
+                let loaded = scenes.scenes_active_first();
+                scene_renderer.render_scenes(
                     render_state,
                     camera,
                     transform,
-                    scene,
+                    &loaded,
                     Some(physics_debug_pipeline),
                 );
-                if let Some((node, c)) = scene.get_main_camera() {
+                let main_camera = loaded
+                    .iter()
+                    .find_map(|scene| Some((*scene, scene.get_main_camera()?)));
+                if let Some((scene, (node, c))) = main_camera {
                     game_renderer.options_mut().clear_color = c.clear_color;
                     let (width, height) = EditorApp::get_physical_size(ctx, *game_size);
                     if width != 0 && height != 0 {
                         game_renderer.resize_textures(width, height);
                     }
                     let transform = scene.get_world_transform(node);
-                    let camera = Camera::new(
+                    let mut camera = Camera::new(
                         width as f32 / height as f32,
                         c.fov,
                         c.near_plane,
                         c.far_plane,
                     );
-                    game_renderer.render_scene(render_state, &camera, &transform, scene, None)
+                    camera.culling_mask = c.culling_mask;
+                    game_renderer.render_scenes(render_state, &camera, &transform, &loaded, None)
                 } else {
                     let device = &render_state.device;
                     let queue = &render_state.queue;
@@ -253,6 +274,8 @@ impl eframe::App for EditorApp {
         }
 
         self.menu_bar(ctx);
+        self.layers_window(ctx);
+        self.history_shortcuts(ctx);
 
         egui::CentralPanel::default()
             .frame(Frame {
@@ -302,8 +325,9 @@ impl eframe::App for EditorApp {
         self.state
             .game
             .scenes
-            .current_scene_mut()
+            .simulation_scene_mut()
             .delete_game_objects();
+        self.state.history.update(ctx, &self.state.game.scenes);
         self.state
             .game
             .assets
@@ -334,44 +358,101 @@ impl EditorApp {
                 ui.menu_button("File", |ui| {
                     if ui.button("New").clicked() {
                         self.state.game.scenes.load_default_scene();
+                        self.state.history.clear();
                         ui.close_menu();
                     }
-                    if ui.button("Open").clicked() {
-                        if let Ok(scene) = self
-                            .state
-                            .game
-                            .assets
-                            .asset_registry
-                            .read()
-                            .load::<Scene>("scene")
-                        {
+                    if ui.button("Open...").clicked() {
+                        if let Some(scene) = self.pick_scene() {
                             self.state.game.scenes.load_scene(scene.readonly());
+                            self.state.history.clear();
                         }
                         ui.close_menu();
                     }
-                    if ui.button("Save").clicked() {
-                        let res = std::fs::OpenOptions::new()
-                            .create(true)
-                            .write(true)
-                            .truncate(true)
-                            .open(
-                                self.project_manager
+                    if ui.button("Open Additive...").clicked() {
+                        if let Some(scene) = self.pick_scene() {
+                            self.state
+                                .game
+                                .scenes
+                                .load_scene_with_mode(scene.readonly(), LoadSceneMode::Additive);
+                        }
+                        ui.close_menu();
+                    }
+                    for (label, format) in [
+                        ("Save", SceneFormat::Json),
+                        ("Save Binary", SceneFormat::Binary),
+                    ] {
+                        if ui.button(label).clicked() {
+                            let scenes = &self.state.game.scenes;
+                            let path = scenes.scene_asset(scenes.active_scene()).and_then(|id| {
+                                self.state
+                                    .game
+                                    .assets
+                                    .asset_registry
                                     .read()
-                                    .current_project()
-                                    .assets_directory()
-                                    .join("scene.cxscene"),
-                            );
-                        if let Ok(file) = res {
-                            let writer = BufWriter::new(file);
-                            serde_json::to_writer_pretty(
-                                writer,
-                                self.state.game.scenes.simulation_scene(),
-                            )
-                            .unwrap();
+                                    .asset_path(id, &["cxscene"])
+                            });
+                            match path {
+                                Some(path) => self.save_scene(&path, format),
+                                None => self.save_scene_as(format),
+                            }
+                            ui.close_menu();
                         }
+                    }
+                    if ui.button("Save As...").clicked() {
+                        self.save_scene_as(SceneFormat::Json);
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui
+                        .add_enabled(
+                            self.state.game.scenes.has_simulation_scene(),
+                            Button::new("Save Game..."),
+                        )
+                        .clicked()
+                    {
+                        self.save_game();
+                        ui.close_menu();
+                    }
+                    if ui.button("Load Game...").clicked() {
+                        self.load_game();
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("Edit", |ui| {
+                    let history = &mut self.state.history;
+                    let scene = self.state.game.scenes.simulation_scene_mut();
+                    let undo = history.undo_name().map(|name| format!("Undo {}", name));
+                    if ui
+                        .add_enabled(
+                            undo.is_some(),
+                            Button::new(undo.unwrap_or_else(|| "Undo".into()))
+                                .shortcut_text("Ctrl+Z"),
+                        )
+                        .clicked()
+                    {
+                        history.undo(scene);
+                        ui.close_menu();
+                    }
+                    let redo = history.redo_name().map(|name| format!("Redo {}", name));
+                    if ui
+                        .add_enabled(
+                            redo.is_some(),
+                            Button::new(redo.unwrap_or_else(|| "Redo".into()))
+                                .shortcut_text("Ctrl+Shift+Z"),
+                        )
+                        .clicked()
+                    {
+                        history.redo(scene);
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("Project", |ui| {
+                    if ui.button("Layers").clicked() {
+                        self.show_layers = true;
                         ui.close_menu();
                     }
-                    if ui.button("Save As").clicked() {
+                    if ui.button("Export").clicked() {
+                        self.export_assets();
                         ui.close_menu();
                     }
                 });
@@ -414,6 +495,9 @@ impl EditorApp {
                         if self.is_simulating() {
                             self.state.game.scenes.pause_simulation();
                         } else {
+                            if !self.state.game.scenes.has_simulation_scene() {
+                                self.state.history.clear();
+                            }
                             self.state.game.scenes.start_simulation();
                         }
                     }
@@ -433,12 +517,191 @@ impl EditorApp {
                         .clicked()
                     {
                         self.state.game.scenes.stop_simulation();
+                        self.state.history.clear();
                     }
                 }
             });
         });
     }
 
+    /// Asks for a scene file in the project's assets and loads it.
+    fn pick_scene(&self) -> Option<Ref<Scene>> {
+        let path = rfd::FileDialog::new()
+            .set_directory(
+                self.project_manager
+                    .read()
+                    .current_project()
+                    .assets_directory(),
+            )
+            .add_filter("cxscene", &["cxscene"])
+            .pick_file()?;
+        match self
+            .state
+            .game
+            .assets
+            .asset_registry
+            .read()
+            .load_by_path::<Scene>(&path)
+        {
+            Ok(scene) => {
+                for error in &scene.read().load_report().errors {
+                    eprintln!("{}", error);
+                }
+                Some(scene)
+            }
+            Err(err) => {
+                eprintln!("Failed to load scene {}: {:?}", path.display(), err);
+                None
+            }
+        }
+    }
+
+    /// Saves the active scene to a file, which becomes the asset it's saved to from then on.
+    fn save_scene(&mut self, path: &Path, format: SceneFormat) {
+        let scenes = &mut self.state.game.scenes;
+        if let Err(err) = scenes.simulation_scene().save(path, format) {
+            eprintln!("{}", err);
+            return;
+        }
+        let asset = self
+            .state
+            .game
+            .assets
+            .asset_registry
+            .read()
+            .asset_id_from_path(path);
+        if asset.is_some() {
+            scenes.set_scene_asset(scenes.active_scene(), asset);
+        }
+    }
+
+    fn save_scene_as(&mut self, format: SceneFormat) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_directory(
+                self.project_manager
+                    .read()
+                    .current_project()
+                    .assets_directory(),
+            )
+            .set_file_name("scene.cxscene")
+            .add_filter("cxscene", &["cxscene"])
+            .save_file()
+        else {
+            return;
+        };
+        self.save_scene(&path, format);
+    }
+
+    /// Saves the running simulation with its runtime state, see [`SaveGame`].
+    fn save_game(&self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name("save.cxsave")
+            .add_filter("cxsave", &["cxsave"])
+            .save_file()
+        else {
+            return;
+        };
+        let GameContext {
+            scenes, resources, ..
+        } = &self.state.game;
+        if let Err(err) = scenes.save_game(resources).save(&path) {
+            eprintln!("{}", err);
+        }
+    }
+
+    /// Replaces the simulation with a saved one, left paused.
+    fn load_game(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("cxsave", &["cxsave"])
+            .pick_file()
+        else {
+            return;
+        };
+        let save = match std::fs::read(&path)
+            .map_err(BoxedError::from)
+            .and_then(|bytes| SaveGame::from_bytes(&bytes))
+        {
+            Ok(save) => save,
+            Err(err) => {
+                eprintln!("Failed to load game {}: {}", path.display(), err);
+                return;
+            }
+        };
+        let GameContext {
+            scenes, resources, ..
+        } = &mut self.state.game;
+        scenes.load_game(&save, resources);
+        self.state.history.clear();
+    }
+
+    /// Copies the project's assets to its export directory, with scenes and prefabs converted
+    /// to the binary format.
+    fn export_assets(&self) {
+        let (source, destination) = {
+            let project_manager = self.project_manager.read();
+            let project = project_manager.current_project();
+            (
+                project.assets_directory(),
+                project.root_directory().join("export").join("assets"),
+            )
+        };
+        let assets = self.state.game.assets.lock_read();
+        match scene::export_assets(&assets, &source, &destination) {
+            Ok(converted) => println!(
+                "Exported assets to {}, {} scenes and prefabs converted",
+                destination.display(),
+                converted
+            ),
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+
+    /// Undoes and redoes changes to the scene with Ctrl+Z and Ctrl+Shift+Z, unless a text field
+    /// has focus and handles them itself.
+    fn history_shortcuts(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        let scene = self.state.game.scenes.simulation_scene_mut();
+        if ctx.input_mut(|input| input.consume_key(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z)) {
+            self.state.history.redo(scene);
+        } else if ctx.input_mut(|input| input.consume_key(Modifiers::COMMAND, Key::Z)) {
+            self.state.history.undo(scene);
+        }
+    }
+
+    fn layers_window(&mut self, ctx: &egui::Context) {
+        let mut changed = false;
+        egui::Window::new("Layers")
+            .open(&mut self.show_layers)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let layer_names = self.state.game.resources.layer_names_mut();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("layers").num_columns(2).show(ui, |ui| {
+                        for layer in 0..LAYER_COUNT {
+                            ui.label(format!("Layer {}", layer));
+                            let mut name = layer_names.name(layer).unwrap_or_default().to_string();
+                            if ui.text_edit_singleline(&mut name).changed() {
+                                layer_names.set_name(layer, name);
+                                changed = true;
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        if changed {
+            let layers = self.state.game.resources.layer_names().to_vec();
+            let mut project_manager = self.project_manager.write();
+            let project = project_manager.current_project_mut();
+            project.set_layers(layers);
+            if let Err(err) = project.save() {
+                eprintln!("{}", err);
+            }
+        }
+    }
+
     fn status_bar(&mut self, ctx: &egui::Context) {
         egui::TopBottomPanel::bottom("bottom_panel")
             .exact_height(24.0)
@@ -449,6 +712,9 @@ impl EditorApp {
                             ui.label(format!("{}", self.fps));
                         }
                         ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            if let Some(load) = self.state.game.scenes.loads().first() {
+                                ui.label(format!("Loading scene {:.0}%", load.progress() * 100.0));
+                            }
                             let background_ref = self.state.game.resources.background().clone();
                             let background = background_ref.read();
                             if !background.task_list().is_empty() {
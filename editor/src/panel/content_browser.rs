@@ -168,7 +168,12 @@ impl Panel for PanelContentBrowser {
                                 };
                                 if inspector.has_context_menu() {
                                     res.context_menu(|ui| {
-                                        inspector.show_context_menu(ui, &mut state.game, asset_id);
+                                        inspector.show_context_menu(
+                                            ui,
+                                            &mut state.game,
+                                            &mut state.history,
+                                            asset_id,
+                                        );
                                     });
                                 }
                             }
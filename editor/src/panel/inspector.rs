@@ -1,6 +1,7 @@
 use std::any::Any;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
+use crate::history::History;
 use crate::inspector::assets::animation_graph_inspector::AnimationGraphInspector;
 use crate::inspector::inspector_registry::InspectorRegistry;
 use crate::inspector::type_inspector::InspectorContext;
@@ -10,20 +11,34 @@ use crate::selection::SelectionType;
 use crate::EditorAppState;
 use convert_case::{Case, Casing};
 use egui::scroll_area::ScrollBarVisibility;
-use egui::{Id, PopupCloseBehavior, Response, Ui};
+use egui::{Event, Id, PopupCloseBehavior, Response, RichText, Ui};
 use engine::assets::animation_graph::AnimationGraph;
+use engine::assets::AssetRegistry;
 use engine::component::{ComponentID, ComponentTransform};
 use engine::context::ReadOnlyAssetContext;
+use engine::physics::PhysicsConfiguration;
 use engine::reflect::type_registry::TypeRegistry;
 use engine::reflect::{AttributeValue, NamedField, Reflect, TypeInfo};
-use engine::scene::{GameObject, SceneManager};
+use engine::scene::{GameObject, GameObjectSnapshot, OverrideValue, Scene, SceneManager};
 use engine::utils::TypeUuid;
 use re_ui::list_item::{LabelContent, ListItem};
 use re_ui::{DesignTokens, UiExt};
 use uuid::Uuid;
 
 #[derive(Default)]
-pub struct PanelInspector;
+pub struct PanelInspector {
+    /// Snapshot of the inspected game object, taken when an interaction may start, to record
+    /// the edits made from the inspector.
+    snapshot: Option<GameObjectSnapshot>,
+}
+
+enum ComponentAction {
+    Remove,
+    SetEnabled(bool),
+    ApplyToPrefab,
+    /// Reverts an overridden field, or the whole component without a path.
+    RevertOverride(Option<String>),
+}
 
 impl Panel for PanelInspector {
     fn name() -> &'static str {
@@ -58,11 +73,25 @@ impl Panel for PanelInspector {
                         {
                             let mut entity_components = HashSet::new();
                             let mut components_to_remove = HashSet::new();
+                            let mut components_to_enable = Vec::new();
+                            let mut prefab_actions = Vec::new();
+                            let mut edited = None;
+                            let overrides = state
+                                .game
+                                .scenes
+                                .simulation_scene()
+                                .game_object_overrides(game_object);
+                            self.update_snapshot(
+                                ui,
+                                state.game.scenes.simulation_scene(),
+                                game_object,
+                            );
 
                             Self::add_component_button_ui(
                                 ui,
                                 &state.game.assets.lock_read(),
                                 &mut state.game.scenes,
+                                &mut state.history,
                                 &entity_components,
                                 game_object,
                             );
@@ -71,51 +100,126 @@ impl Panel for PanelInspector {
                                 state.game.assets.component_registry.clone();
                             let component_registry = component_registry_ref.read();
                             for (type_id, component) in component_registry.components() {
-                                let Some(instance) = (unsafe {
-                                    state
-                                        .game
-                                        .scenes
-                                        .simulation_scene_mut()
-                                        .get_component_ptr(game_object, &**component)
-                                        .map(|ptr| &mut *ptr)
-                                }) else {
-                                    continue;
-                                };
-
-                                entity_components.insert(*type_id);
-                                let Some(TypeInfo::Struct(type_info)) =
-                                    type_registry.type_info_by_id(*type_id)
-                                else {
-                                    continue;
-                                };
-                                let simulation_scene = state.game.scenes.simulation_scene();
-                                let ctx = InspectorContext {
-                                    assets: &state.game.assets.lock_read(),
-                                    scene: simulation_scene,
-                                    game_object,
-                                    parent: simulation_scene.get_parent_game_object(game_object),
-                                    type_info,
-                                    field_name: None,
-                                };
-                                if self.show_inspector(
-                                    ui,
-                                    &state.inspector_registry,
-                                    &ctx,
-                                    instance.as_reflect_mut(),
-                                ) {
-                                    components_to_remove.insert(*type_id);
+                                let action = state
+                                    .game
+                                    .scenes
+                                    .simulation_scene_mut()
+                                    .with_component_mut(
+                                        game_object,
+                                        &**component,
+                                        |instance, simulation_scene| {
+                                            entity_components.insert(*type_id);
+                                            let Some(TypeInfo::Struct(type_info)) =
+                                                type_registry.type_info_by_id(*type_id)
+                                            else {
+                                                return None;
+                                            };
+                                            let ctx = InspectorContext {
+                                                assets: &state.game.assets.lock_read(),
+                                                scene: simulation_scene,
+                                                layer_names: state.game.resources.layer_names(),
+                                                game_object,
+                                                parent: simulation_scene
+                                                    .get_parent_game_object(game_object),
+                                                type_info,
+                                                field_name: None,
+                                            };
+                                            let enabled = simulation_scene
+                                                .is_component_enabled_dyn(game_object, *type_id);
+                                            let value = instance.serialize();
+                                            let action = self.show_inspector(
+                                                ui,
+                                                &state.inspector_registry,
+                                                &ctx,
+                                                instance.as_reflect_mut(),
+                                                enabled,
+                                                overrides.get(type_id),
+                                            );
+                                            if instance.serialize() != value {
+                                                edited = Some(format!(
+                                                    "Edit {}",
+                                                    Self::display_name(
+                                                        &type_registry,
+                                                        instance.as_reflect()
+                                                    )
+                                                ));
+                                            }
+                                            action
+                                        },
+                                    )
+                                    .flatten();
+                                match action {
+                                    Some(ComponentAction::Remove) => {
+                                        components_to_remove.insert(*type_id);
+                                    }
+                                    Some(ComponentAction::SetEnabled(enabled)) => {
+                                        components_to_enable.push((*type_id, enabled));
+                                    }
+                                    Some(action) => prefab_actions.push((*type_id, action)),
+                                    None => {}
+                                }
+                            }
+                            let scene = state.game.scenes.simulation_scene_mut();
+                            if let (Some(name), Some(snapshot)) = (edited, self.snapshot.clone()) {
+                                state.history.record_snapshot(scene, name, snapshot);
+                            }
+                            for (type_id, action) in prefab_actions {
+                                let scene = state.game.scenes.simulation_scene_mut();
+                                match action {
+                                    ComponentAction::ApplyToPrefab => {
+                                        match scene.apply_prefab_override(
+                                            game_object,
+                                            type_id,
+                                            None,
+                                        ) {
+                                            Ok(prefab) => {
+                                                state
+                                                    .game
+                                                    .assets
+                                                    .asset_registry
+                                                    .read()
+                                                    .persist(prefab.id());
+                                            }
+                                            Err(err) => eprintln!("{}", err),
+                                        }
+                                    }
+                                    ComponentAction::RevertOverride(path) => {
+                                        state.history.record(
+                                            scene,
+                                            "Revert Override",
+                                            [game_object],
+                                        );
+                                        scene.revert_prefab_override(
+                                            game_object,
+                                            type_id,
+                                            path.as_deref(),
+                                        );
+                                    }
+                                    _ => {}
                                 }
                             }
+                            for (type_id, enabled) in components_to_enable {
+                                let scene = state.game.scenes.simulation_scene_mut();
+                                state.history.record(
+                                    scene,
+                                    if enabled {
+                                        "Enable Component"
+                                    } else {
+                                        "Disable Component"
+                                    },
+                                    [game_object],
+                                );
+                                scene.set_component_enabled_dyn(game_object, type_id, enabled);
+                            }
                             for (type_id, component) in component_registry.components() {
                                 if !components_to_remove.contains(type_id) {
                                     continue;
                                 }
-                                if let Some(mut entry) = state
-                                    .game
-                                    .scenes
-                                    .simulation_scene_mut()
-                                    .entry_mut(game_object)
-                                {
+                                let scene = state.game.scenes.simulation_scene_mut();
+                                state
+                                    .history
+                                    .record(scene, "Remove Component", [game_object]);
+                                if let Some(mut entry) = scene.entry_mut(game_object) {
                                     component.remove_instance(&mut entry);
                                 }
                             }
@@ -126,12 +230,9 @@ impl Panel for PanelInspector {
                             else {
                                 return;
                             };
-                            let Some(inspector) = state
+                            let inspector = state
                                 .inspector_registry
-                                .asset_inspector_lookup(asset_meta.type_uuid)
-                            else {
-                                return;
-                            };
+                                .asset_inspector_lookup(asset_meta.type_uuid);
 
                             let header_id = Id::new(asset_id);
                             ListItem::new()
@@ -160,13 +261,26 @@ impl Panel for PanelInspector {
                                                 {
                                                     asset_registry.persist(asset_id);
                                                 }
+                                                if ui
+                                                    .list_item()
+                                                    .show_flat(
+                                                        ui,
+                                                        LabelContent::new("Find References"),
+                                                    )
+                                                    .clicked()
+                                                {
+                                                    asset_registry.scan_dependencies();
+                                                }
                                             });
                                             res
                                         }),
                                     |ui| {
-                                        inspector.show_inspector(ui, &mut state.game, asset_id);
+                                        if let Some(inspector) = inspector {
+                                            inspector.show_inspector(ui, &mut state.game, asset_id);
+                                        }
                                     },
                                 );
+                            Self::show_asset_references(ui, &asset_registry, asset_id);
                         } else if let SelectionType::AnimationNode(asset_id) = state.selection.ty()
                         {
                             if let Some(id) = state.selection.iter().next() {
@@ -202,6 +316,8 @@ impl Panel for PanelInspector {
                                     AnimationGraphInspector::transition(ui, &mut graph, id);
                                 }
                             }
+                        } else if state.selection.is(SelectionType::None) {
+                            self.show_scene_settings(ui, state, &type_registry);
                         }
                     });
                     ui.allocate_space(ui.available_size());
@@ -219,6 +335,29 @@ impl Panel for PanelInspector {
 }
 
 impl PanelInspector {
+    /// Takes a new snapshot of the inspected game object when there's none for it yet, or on
+    /// frames where a click or a key press may start editing it.
+    fn update_snapshot(&mut self, ui: &Ui, scene: &Scene, game_object: GameObject) {
+        let id = scene.get_game_object_uuid(scene.snapshot_root(game_object));
+        let input = ui.input(|input| {
+            input.pointer.any_pressed()
+                || input.events.iter().any(|event| {
+                    matches!(
+                        event,
+                        Event::Key { pressed: true, .. } | Event::Text(_) | Event::Paste(_)
+                    )
+                })
+        });
+        if input
+            || self
+                .snapshot
+                .as_ref()
+                .is_none_or(|snapshot| snapshot.id() != id)
+        {
+            self.snapshot = Some(scene.snapshot(game_object));
+        }
+    }
+
     fn display_name(type_registry: &TypeRegistry, instance: &dyn Reflect) -> &'static str {
         type_registry
             .type_info_by_id(instance.uuid())
@@ -247,7 +386,9 @@ impl PanelInspector {
         registry: &InspectorRegistry,
         ctx: &InspectorContext,
         instance: &mut dyn Reflect,
-    ) -> bool {
+        enabled: bool,
+        overrides: Option<&BTreeMap<String, OverrideValue>>,
+    ) -> Option<ComponentAction> {
         let name = Self::display_name(&ctx.assets.type_registry.read(), instance);
         let id = ui.make_persistent_id(name);
         let type_uuid = instance.uuid();
@@ -258,12 +399,17 @@ impl PanelInspector {
                 ui,
                 id,
                 true,
-                LabelContent::new(name).truncate(true),
+                LabelContent::new(if enabled {
+                    name.to_string()
+                } else {
+                    format!("{name} (Disabled)")
+                })
+                .truncate(true),
                 |ui| {
                     if let Some(inspector) = registry.type_inspector_lookup(type_uuid) {
                         inspector.show_inspector(ui, ctx, instance);
                     } else {
-                        self.show_default_inspector(ui, registry, ctx, instance);
+                        self.show_default_inspector(ui, registry, ctx, instance, overrides);
                     }
                 },
             )
@@ -274,19 +420,124 @@ impl PanelInspector {
                 state.store(ui.ctx());
             }
         }
-        let mut remove = false;
-        if type_uuid != ComponentID::type_uuid() {
+        let mut action = None;
+        if type_uuid != ComponentID::type_uuid() || overrides.is_some() {
             res.context_menu(|ui| {
-                if type_uuid != ComponentTransform::type_uuid() && ui.button("Remove").clicked() {
-                    remove = true;
-                    ui.close_menu();
+                if let Some(overrides) = overrides {
+                    if ui.button("Apply to Prefab").clicked() {
+                        action = Some(ComponentAction::ApplyToPrefab);
+                        ui.close_menu();
+                    }
+                    if ui.button("Revert Overrides").clicked() {
+                        action = Some(ComponentAction::RevertOverride(None));
+                        ui.close_menu();
+                    }
+                    ui.menu_button("Revert Override", |ui| {
+                        for path in overrides.keys().filter(|path| !path.is_empty()) {
+                            if ui.button(path).clicked() {
+                                action = Some(ComponentAction::RevertOverride(Some(path.clone())));
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.separator();
+                }
+                if type_uuid == ComponentID::type_uuid() {
+                    return;
+                }
+                if type_uuid != ComponentTransform::type_uuid() {
+                    if ui.button("Remove").clicked() {
+                        action = Some(ComponentAction::Remove);
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button(if enabled { "Disable" } else { "Enable" })
+                        .clicked()
+                    {
+                        action = Some(ComponentAction::SetEnabled(!enabled));
+                        ui.close_menu();
+                    }
                 }
                 if let Some(inspector) = registry.type_inspector_lookup(type_uuid) {
                     inspector.show_inspector_context(ui, ctx, instance);
                 }
             });
         }
-        remove
+        action
+    }
+
+    /// Lists the assets the asset uses and the loaded assets using it.
+    fn show_asset_references(ui: &mut Ui, asset_registry: &AssetRegistry, asset_id: Uuid) {
+        let sections = [
+            ("Uses", asset_registry.dependencies(asset_id)),
+            ("Used By", asset_registry.dependents(asset_id)),
+        ];
+        for (name, ids) in sections {
+            ListItem::new()
+                .interactive(true)
+                .force_background(re_ui::design_tokens().section_collapsing_header_color())
+                .show_hierarchical_with_children_unindented(
+                    ui,
+                    Id::new(asset_id).with(name),
+                    false,
+                    LabelContent::new(format!("{name} ({})", ids.len())).truncate(true),
+                    |ui| {
+                        for id in ids {
+                            let label = asset_registry
+                                .asset_meta_from_id(id)
+                                .map(|meta| meta.name)
+                                .unwrap_or_else(|| format!("Missing asset {id}"));
+                            ui.list_item()
+                                .show_flat(ui, LabelContent::new(label).truncate(true));
+                        }
+                    },
+                );
+        }
+    }
+
+    fn show_scene_settings(
+        &self,
+        ui: &mut Ui,
+        state: &mut EditorAppState,
+        type_registry: &TypeRegistry,
+    ) {
+        let Some(TypeInfo::Struct(type_info)) =
+            type_registry.type_info_by_id(PhysicsConfiguration::type_uuid())
+        else {
+            return;
+        };
+        let assets = state.game.assets.lock_read();
+        let scene = state.game.scenes.simulation_scene();
+        let mut config = scene.physics.config.clone();
+        let ctx = InspectorContext {
+            assets: &assets,
+            scene,
+            layer_names: state.game.resources.layer_names(),
+            game_object: scene.root(),
+            parent: None,
+            type_info,
+            field_name: None,
+        };
+        let name = Self::display_name(type_registry, &config);
+        ListItem::new()
+            .interactive(true)
+            .force_background(re_ui::design_tokens().section_collapsing_header_color())
+            .show_hierarchical_with_children_unindented(
+                ui,
+                ui.make_persistent_id("scene_physics_settings"),
+                true,
+                LabelContent::new(name).truncate(true),
+                |ui| {
+                    self.show_default_inspector(
+                        ui,
+                        &state.inspector_registry,
+                        &ctx,
+                        &mut config,
+                        None,
+                    );
+                },
+            );
+        state.game.scenes.simulation_scene_mut().physics.config = config;
     }
 
     fn show_default_inspector(
@@ -295,6 +546,7 @@ impl PanelInspector {
         registry: &InspectorRegistry,
         ctx: &InspectorContext,
         instance: &mut dyn Reflect,
+        overrides: Option<&BTreeMap<String, OverrideValue>>,
     ) {
         if let Some(TypeInfo::Struct(info)) = ctx
             .assets
@@ -305,8 +557,13 @@ impl PanelInspector {
             for (_, field) in info.fields.iter() {
                 let mut ctx = *ctx;
                 ctx.field_name = Some(field.name);
+                let overridden = overrides.is_some_and(|overrides| {
+                    overrides
+                        .keys()
+                        .any(|path| path.split('.').next() == Some(field.name) || path.is_empty())
+                });
                 if let Some(value) = field.get_reflect_mut(instance.as_reflect_mut()) {
-                    self.show_default_inspector_field(ui, registry, &ctx, field, value);
+                    self.show_default_inspector_field(ui, registry, &ctx, field, value, overridden);
                 }
             }
         }
@@ -319,9 +576,16 @@ impl PanelInspector {
         ctx: &InspectorContext,
         field: &NamedField,
         instance: &mut dyn Reflect,
+        overridden: bool,
     ) {
         let mut name = Self::field_display_name(field);
         name.push(' ');
+        // Fields overriding the prefab the game object comes from are shown in bold
+        let name = if overridden {
+            RichText::new(name).strong()
+        } else {
+            RichText::new(name)
+        };
         if let Some(inspector) = registry.type_inspector_lookup(instance.uuid()) {
             Widgets::inspector_prop_value(ui, name, |ui, _| {
                 inspector.show_inspector(ui, ctx, instance);
@@ -333,6 +597,7 @@ impl PanelInspector {
         ui: &mut Ui,
         assets: &ReadOnlyAssetContext,
         scenes: &mut SceneManager,
+        history: &mut History,
         entity_components: &HashSet<Uuid>,
         game_object: GameObject,
     ) -> Response {
@@ -357,9 +622,9 @@ impl PanelInspector {
                 }
                 let name = Self::display_name(&assets.type_registry.read(), component.as_reflect());
                 if ui.selectable_label(false, name).clicked() {
-                    scenes
-                        .simulation_scene_mut()
-                        .bind_component_dyn(game_object, *type_uuid);
+                    let scene = scenes.simulation_scene_mut();
+                    history.record(scene, "Add Component", [game_object]);
+                    scene.bind_component_dyn(game_object, *type_uuid);
                 }
             }
         });
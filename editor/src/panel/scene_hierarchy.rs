@@ -1,15 +1,16 @@
+use crate::history::History;
 use crate::panel::Panel;
 use crate::{icons, EditorAppState, Selection, SelectionType};
 use egui::Ui;
 use egui::{Color32, Response};
+use engine::assets::AssetRegistry;
 use engine::component::ComponentID;
 use engine::context::GameContext;
-use engine::scene::Scene;
-use engine::scene::{GameObject, SiblingDir};
+use engine::scene::{GameObject, SceneFormat, SiblingDir};
+use engine::scene::{Scene, SceneId, SceneManager};
 use re_ui::drag_and_drop::{DropTarget, ItemKind};
 use re_ui::{DesignTokens, UiExt};
 use std::any::Any;
-use std::io::BufWriter;
 use std::sync::mpsc::{Receiver, Sender};
 use uuid::Uuid;
 
@@ -17,6 +18,11 @@ enum Command {
     SetTargetContainer(GameObject),
 }
 
+enum SceneAction {
+    SetActive(SceneId),
+    Unload(SceneId),
+}
+
 pub struct PanelSceneHierarchy {
     target_container: Option<GameObject>,
     sender: Sender<Command>,
@@ -51,11 +57,28 @@ impl Panel for PanelSceneHierarchy {
                 }
                 .show(ui, |ui| {
                     let EditorAppState {
-                        game: GameContext { scenes, .. },
+                        game: GameContext { scenes, assets, .. },
                         selection,
+                        history,
                         ..
                     } = state;
 
+                    let asset_registry = assets.asset_registry.read();
+                    let active_scene = scenes.active_scene();
+                    let active_name = Self::scene_name(&asset_registry, scenes, active_scene);
+                    // The persistent scene is only listed when it has game objects
+                    let other_scenes = scenes
+                        .scenes()
+                        .filter(|(id, scene)| {
+                            *id != active_scene
+                                && (*id != SceneId::PERSISTENT
+                                    || scene.root_objects().next().is_some())
+                        })
+                        .map(|(id, _)| (id, Self::scene_name(&asset_registry, scenes, id)))
+                        .collect::<Vec<_>>();
+                    drop(asset_registry);
+                    let mut scene_action = None;
+
                     let scene = scenes.simulation_scene_mut();
                     re_ui::list_item::list_item_scope(ui, "scene", |ui| {
                         let mut list_item = ui.list_item().draggable(false);
@@ -65,18 +88,42 @@ impl Panel for PanelSceneHierarchy {
                         }
                         let response = list_item.show_flat(
                             ui,
-                            re_ui::list_item::LabelContent::new("Scene")
+                            re_ui::list_item::LabelContent::new(active_name)
                                 .truncate(true)
                                 .always_show_buttons(true)
                                 .with_icon(&icons::OBJECT_TREE)
                                 .with_buttons(|ui| {
-                                    self.add_game_object_button(ui, scene, selection)
+                                    self.add_game_object_button(ui, scene, selection, history)
                                 }),
                         );
                         for root_object in scene.root_objects().collect::<Vec<_>>() {
-                            self.render_scene_node(scene, selection, ui, root_object, true);
+                            self.render_scene_node(
+                                scene,
+                                selection,
+                                history,
+                                ui,
+                                root_object,
+                                true,
+                            );
+                        }
+                        self.handle_root_dnd_interaction(ui, scene, history, &response);
+                        response.context_menu(|ui| {
+                            if ui
+                                .add_enabled(
+                                    active_scene != SceneId::PERSISTENT,
+                                    egui::Button::new("Unload Scene"),
+                                )
+                                .clicked()
+                            {
+                                scene_action = Some(SceneAction::Unload(active_scene));
+                                ui.close_menu();
+                            }
+                        });
+                        for (id, name) in other_scenes {
+                            if let Some(action) = Self::scene_ui(ui, id, name) {
+                                scene_action = Some(action);
+                            }
                         }
-                        self.handle_root_dnd_interaction(ui, scene, &response);
 
                         let empty_space_response =
                             ui.allocate_response(ui.available_size(), egui::Sense::click());
@@ -88,9 +135,24 @@ impl Panel for PanelSceneHierarchy {
                         self.handle_empty_space_dnd_interaction(
                             ui,
                             scene,
+                            history,
                             empty_space_response.rect,
                         );
                     });
+
+                    match scene_action {
+                        Some(SceneAction::SetActive(id)) => {
+                            scenes.set_active_scene(id);
+                            *selection = Selection::none();
+                        }
+                        Some(SceneAction::Unload(id)) => {
+                            if scenes.active_scene() == id {
+                                *selection = Selection::none();
+                            }
+                            scenes.unload_scene(id);
+                        }
+                        None => {}
+                    }
                 });
             });
 
@@ -114,10 +176,51 @@ impl Panel for PanelSceneHierarchy {
 }
 
 impl PanelSceneHierarchy {
+    fn scene_name(asset_registry: &AssetRegistry, scenes: &SceneManager, id: SceneId) -> String {
+        if id == SceneId::PERSISTENT {
+            return "Persistent".to_string();
+        }
+        scenes
+            .scene_asset(id)
+            .map(|asset| asset_registry.asset_name(asset))
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "Untitled".to_string())
+    }
+
+    /// Shows a loaded scene other than the active one, which a double click makes active.
+    fn scene_ui(ui: &mut Ui, id: SceneId, name: String) -> Option<SceneAction> {
+        let mut action = None;
+        let response = ui.list_item().draggable(false).show_flat(
+            ui,
+            re_ui::list_item::LabelContent::new(name)
+                .truncate(true)
+                .subdued(true)
+                .with_icon(&icons::OBJECT_TREE),
+        );
+        if response.double_clicked() {
+            action = Some(SceneAction::SetActive(id));
+        }
+        response.context_menu(|ui| {
+            if ui.button("Set Active Scene").clicked() {
+                action = Some(SceneAction::SetActive(id));
+                ui.close_menu();
+            }
+            if ui
+                .add_enabled(id != SceneId::PERSISTENT, egui::Button::new("Unload Scene"))
+                .clicked()
+            {
+                action = Some(SceneAction::Unload(id));
+                ui.close_menu();
+            }
+        });
+        action
+    }
+
     fn render_scene_node(
         &mut self,
         scene: &mut Scene,
         selection: &mut Selection,
+        history: &mut History,
         ui: &mut Ui,
         game_object: GameObject,
         parent_visible: bool,
@@ -131,6 +234,7 @@ impl PanelSceneHierarchy {
             .read_component::<ComponentID, _, _>(game_object, |c| c.visible)
             .unwrap_or(false);
         let container_visible = visible && parent_visible;
+        let active = scene.is_active_in_hierarchy(game_object);
         let mut item = re_ui::list_item::ListItem::new()
             .selected(is_selected)
             .draggable(true);
@@ -142,7 +246,7 @@ impl PanelSceneHierarchy {
         let mut visibility_response = None;
         let content = re_ui::list_item::LabelContent::new(name)
             .truncate(true)
-            .subdued(!container_visible)
+            .subdued(!container_visible || !active)
             .with_icon(&icons::GAME_OBJECT)
             .with_buttons(|ui| {
                 let res = Self::visibility_button_ui(ui, parent_visible, &mut visible);
@@ -152,7 +256,14 @@ impl PanelSceneHierarchy {
         if !children.is_empty() {
             let res = item.show_hierarchical_with_children(ui, id, true, content, |ui| {
                 for child_node in children {
-                    self.render_scene_node(scene, selection, ui, child_node, container_visible)
+                    self.render_scene_node(
+                        scene,
+                        selection,
+                        history,
+                        ui,
+                        child_node,
+                        container_visible,
+                    )
                 }
             });
             response = res.item_response;
@@ -169,6 +280,7 @@ impl PanelSceneHierarchy {
             is_selected,
             visible,
             selection,
+            history,
             &response,
             body_response.as_ref(),
             visibility_response.as_ref(),
@@ -184,6 +296,7 @@ impl PanelSceneHierarchy {
         is_selected: bool,
         is_visible: bool,
         selection: &mut Selection,
+        history: &mut History,
         response: &Response,
         body_response: Option<&Response>,
         visibility_response: Option<&Response>,
@@ -212,11 +325,12 @@ impl PanelSceneHierarchy {
             );
         }
         if visibility_response.map(|r| r.changed()).unwrap_or(false) {
+            history.record(scene, "Toggle Visibility", [game_object]);
             scene.write_component::<ComponentID, _>(game_object, |c| {
                 c.visible = is_visible;
             });
         }
-        self.handle_dnd_interaction(ui, scene, game_object, response, body_response);
+        self.handle_dnd_interaction(ui, scene, history, game_object, response, body_response);
         response.context_menu(|ui| {
             if ui.button("Save as prefab").clicked() {
                 if let Some(path) = rfd::FileDialog::new()
@@ -224,31 +338,65 @@ impl PanelSceneHierarchy {
                     .add_filter("cxprefab", &["cxprefab"])
                     .save_file()
                 {
-                    if let Ok(file) = std::fs::OpenOptions::new()
-                        .create(true)
-                        .write(true)
-                        .truncate(true)
-                        .open(path)
-                    {
-                        let prefab = scene.create_prefab(game_object);
-                        let writer = BufWriter::new(file);
-                        serde_json::to_writer_pretty(writer, &prefab).unwrap();
+                    let replaced = scene
+                        .assets()
+                        .asset_registry
+                        .read()
+                        .asset_id_from_path(&path);
+                    match scene.create_prefab(game_object, replaced) {
+                        Ok(prefab) => {
+                            if let Err(err) = prefab.save(&path, SceneFormat::Json) {
+                                eprintln!("{}", err);
+                            }
+                        }
+                        Err(err) => eprintln!("{}", err),
                     }
                 }
                 ui.close_menu();
             }
+            let active = scene.is_active(game_object);
+            if ui
+                .button(if active { "Deactivate" } else { "Activate" })
+                .clicked()
+            {
+                history.record(
+                    scene,
+                    if active { "Deactivate" } else { "Activate" },
+                    [game_object],
+                );
+                scene.set_active(game_object, !active);
+                ui.close_menu();
+            }
+            if ui.button("Duplicate").clicked() {
+                if let Some(copy) = scene.duplicate_game_object(game_object) {
+                    history.record_created(scene, "Duplicate", copy);
+                    *selection = Selection::from_id(
+                        SelectionType::GameObject,
+                        scene.get_game_object_uuid(copy),
+                    );
+                }
+                ui.close_menu();
+            }
             if ui.button("Delete").clicked() {
+                history.record(scene, "Delete", [game_object]);
                 scene.delete_game_object(game_object);
                 ui.close_menu();
             }
             if ui.button("New Game Object").clicked() {
-                scene.create_game_object(None, Some(game_object));
+                let child = scene.create_game_object(None, Some(game_object));
+                history.record_created(scene, "New Game Object", child);
                 ui.close_menu();
             }
         });
     }
 
-    fn handle_root_dnd_interaction(&mut self, ui: &mut Ui, scene: &mut Scene, response: &Response) {
+    fn handle_root_dnd_interaction(
+        &mut self,
+        ui: &mut Ui,
+        scene: &mut Scene,
+        history: &mut History,
+        response: &Response,
+    ) {
         let Some(dragged_game_object) = self.dragged_game_object(ui, scene) else {
             return;
         };
@@ -268,7 +416,7 @@ impl PanelSceneHierarchy {
         );
 
         if let Some(drop_target) = drop_target {
-            self.handle_drop_target(ui, scene, drop_target, dragged_game_object);
+            self.handle_drop_target(ui, scene, history, drop_target, dragged_game_object);
         }
     }
 
@@ -276,6 +424,7 @@ impl PanelSceneHierarchy {
         &mut self,
         ui: &mut Ui,
         scene: &mut Scene,
+        history: &mut History,
         empty_space: egui::Rect,
     ) {
         let Some(dragged_game_object) = self.dragged_game_object(ui, scene) else {
@@ -290,7 +439,7 @@ impl PanelSceneHierarchy {
                 usize::MAX,
             );
 
-            self.handle_drop_target(ui, scene, drop_target, dragged_game_object);
+            self.handle_drop_target(ui, scene, history, drop_target, dragged_game_object);
         }
     }
 
@@ -298,6 +447,7 @@ impl PanelSceneHierarchy {
         &mut self,
         ui: &mut Ui,
         scene: &mut Scene,
+        history: &mut History,
         game_object: GameObject,
         response: &Response,
         body_response: Option<&Response>,
@@ -342,7 +492,7 @@ impl PanelSceneHierarchy {
         );
 
         if let Some(drop_target) = drop_target {
-            self.handle_drop_target(ui, scene, drop_target, dragged_game_object);
+            self.handle_drop_target(ui, scene, history, drop_target, dragged_game_object);
         }
     }
 
@@ -350,6 +500,7 @@ impl PanelSceneHierarchy {
         &mut self,
         ui: &mut Ui,
         scene: &mut Scene,
+        history: &mut History,
         drop_target: DropTarget<Uuid>,
         dragged_game_object: GameObject,
     ) {
@@ -372,6 +523,16 @@ impl PanelSceneHierarchy {
         );
 
         if ui.input(|i| i.pointer.any_released()) {
+            // Moving next to a sibling in the same parent swaps the two
+            let swapped = target_sibling.filter(|sibling| {
+                scene.get_parent_game_object(dragged_game_object) == Some(target_parent)
+                    && *sibling != dragged_game_object
+            });
+            history.record(
+                scene,
+                "Move",
+                std::iter::once(dragged_game_object).chain(swapped),
+            );
             scene.set_parent_with_sibling(
                 dragged_game_object,
                 Some(target_parent),
@@ -399,6 +560,7 @@ impl PanelSceneHierarchy {
         ui: &mut Ui,
         scene: &mut Scene,
         selection: &Selection,
+        history: &mut History,
     ) -> Response {
         let res = ui
             .small_icon_button(&re_ui::icons::ADD)
@@ -407,7 +569,8 @@ impl PanelSceneHierarchy {
             let parent = selection
                 .last(SelectionType::GameObject)
                 .and_then(|id| scene.get_game_object_by_uuid(id));
-            scene.create_game_object(None, parent);
+            let game_object = scene.create_game_object(None, parent);
+            history.record_created(scene, "New Game Object", game_object);
         }
         res
     }
@@ -139,7 +139,10 @@ impl PanelViewport {
         };
 
         let EditorAppState {
-            selection, game, ..
+            selection,
+            history,
+            game,
+            ..
         } = app_state;
 
         if let Some(game_object) = selection
@@ -173,9 +176,9 @@ impl PanelViewport {
                 .get_world_transform(game_object);
             if let Some((result, transforms)) = self.gizmo.interact(ui, &[transform.into()]) {
                 let res: Transform = transforms[0].into();
-                game.scenes
-                    .simulation_scene_mut()
-                    .set_world_transform(game_object, res.matrix);
+                let scene = game.scenes.simulation_scene_mut();
+                history.record(scene, "Transform", [game_object]);
+                scene.set_world_transform(game_object, res.matrix);
                 self.gizmo_status(ui, &result);
             }
         }
@@ -48,6 +48,10 @@ impl ProjectManager {
         &self.current_project
     }
 
+    pub fn current_project_mut(&mut self) -> &mut Project {
+        &mut self.current_project
+    }
+
     fn root_project_dir(&self) -> PathBuf {
         self.current_project.root_directory().clone()
     }
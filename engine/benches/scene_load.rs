@@ -0,0 +1,54 @@
+//! Compares loading the same scene from JSON and from the binary format.
+//! Run with `cargo bench -p engine --bench scene_load`.
+#![feature(test)]
+
+extern crate test;
+
+use engine::component::{ComponentMesh, ComponentPointLight};
+use engine::context::AssetContext;
+use engine::render::RenderContext;
+use engine::scene::{BinarySceneData, Scene};
+use std::path::Path;
+use test::Bencher;
+
+const GAME_OBJECT_COUNT: usize = 2000;
+
+fn scene_bytes() -> (AssetContext, Vec<u8>, Vec<u8>) {
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let assets = AssetContext::with_render_context(
+        RenderContext::without_gpu(),
+        workspace.join("assets"),
+        workspace.join("assets"),
+    )
+    .unwrap();
+    let mut scene = assets.scene();
+    let mut parent = None;
+    for i in 0..GAME_OBJECT_COUNT {
+        let game_object = scene.create_game_object(None, parent);
+        scene.add_component(game_object, ComponentMesh::default());
+        if i % 10 == 0 {
+            scene.add_component(game_object, ComponentPointLight::default());
+            parent = Some(game_object);
+        }
+    }
+    let json = serde_json::to_vec_pretty(&scene).unwrap();
+    let mut binary = Vec::new();
+    BinarySceneData::from(&scene)
+        .write_binary(&mut binary)
+        .unwrap();
+    (assets, json, binary)
+}
+
+#[bench]
+fn load_json(b: &mut Bencher) {
+    let (assets, json, _) = scene_bytes();
+    let assets = assets.lock_read();
+    b.iter(|| Scene::from_bytes(&assets, &json).unwrap());
+}
+
+#[bench]
+fn load_binary(b: &mut Bencher) {
+    let (assets, _, binary) = scene_bytes();
+    let assets = assets.lock_read();
+    b.iter(|| Scene::from_bytes(&assets, &binary).unwrap());
+}
@@ -1,17 +1,71 @@
-use crate::fq::{FQBox, FQOption, FQReflect};
+use crate::fq::{FQBox, FQOption, FQReflect, FQResult};
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
 
 pub fn derive_component(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = input.ident;
+    // Fields marked `#[save_state]` are kept in save games, apart from the serialized fields
+    let state_fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields
+                .named
+                .iter()
+                .filter(|field| {
+                    field
+                        .attrs
+                        .iter()
+                        .any(|attr| attr.path().is_ident("save_state"))
+                })
+                .filter_map(|field| field.ident.clone())
+                .collect(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+    let state_methods = if state_fields.is_empty() {
+        quote! {}
+    } else {
+        let state_names = state_fields.iter().map(|field| field.to_string());
+        let load_names = state_names.clone();
+        let load_fields = state_fields.iter();
+        quote! {
+            fn save_state(&self) -> #FQOption<serde_json::Value> {
+                let mut state = serde_json::Map::new();
+                // Fields that fail to serialize are left out, keeping their value on load
+                #(
+                    if let Ok(value) = serde_json::to_value(&self.#state_fields) {
+                        state.insert(#state_names.to_string(), value);
+                    }
+                )*
+                Some(serde_json::Value::Object(state))
+            }
+            fn load_state(&mut self, state: &serde_json::Value) {
+                #(
+                    if let Some(value) = state.get(#load_names) {
+                        if let Ok(value) = serde_json::from_value(value.clone()) {
+                            self.#load_fields = value;
+                        }
+                    }
+                )*
+            }
+        }
+    };
     let expanded = quote! {
         #[automatically_derived]
         impl engine::component::ComponentInstance for #name {
             fn component_type_id(&self) -> legion::storage::ComponentTypeId {
                 legion::storage::ComponentTypeId::of::<#name>()
             }
+            fn query_entities(&self, world: &legion::World) -> Vec<legion::Entity> {
+                use legion::IntoQuery;
+                <legion::Entity>::query()
+                    .filter(legion::query::component::<#name>())
+                    .iter(world)
+                    .copied()
+                    .collect()
+            }
             fn get_instance<'a>(
                 &self, entry: &'a legion::world::EntryRef
             ) -> #FQOption<&'a dyn engine::component::Component> {
@@ -24,6 +78,22 @@ pub fn derive_component(input: TokenStream) -> TokenStream {
                 let instance = entry.get_component_mut::<#name>().ok()?;
                 Some(instance)
             }
+            fn swap_instance(
+                &self,
+                entry: &mut legion::world::Entry,
+                instance: &mut dyn engine::component::Component
+            ) -> bool {
+                match (
+                    entry.get_component_mut::<#name>(),
+                    instance.as_any_mut().downcast_mut::<#name>(),
+                ) {
+                    (Ok(slot), Some(instance)) => {
+                        std::mem::swap(slot, instance);
+                        true
+                    }
+                    _ => false,
+                }
+            }
             fn bind_instance(
                 &self,
                 entry: &mut legion::world::Entry,
@@ -44,12 +114,28 @@ pub fn derive_component(input: TokenStream) -> TokenStream {
             fn serialize(&self) -> #FQOption<serde_json::Value> {
                 serde_json::to_value(self).ok()
             }
-            fn deserialize(&self, value: serde_json::Value) -> #FQOption<#FQBox<dyn #FQReflect>> {
-                serde_json::from_value::<#name>(value).ok().map(|v| {
+            fn deserialize(
+                &self,
+                value: serde_json::Value
+            ) -> #FQResult<#FQBox<dyn #FQReflect>, serde_json::Error> {
+                serde_json::from_value::<#name>(value).map(|v| {
+                    let value: Box<dyn Reflect> = Box::new(v);
+                    value
+                })
+            }
+            fn serialize_binary(&self) -> #FQOption<Vec<u8>> {
+                engine::scene::encode_component_binary(self)
+            }
+            fn deserialize_binary(
+                &self,
+                data: &[u8]
+            ) -> #FQResult<#FQBox<dyn #FQReflect>, engine::scene::BinaryError> {
+                engine::scene::decode_binary::<#name>(data).map(|v| {
                     let value: Box<dyn Reflect> = Box::new(v);
                     value
                 })
             }
+            #state_methods
         }
     };
     TokenStream::from(expanded)
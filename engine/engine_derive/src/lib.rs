@@ -11,7 +11,7 @@ extern crate proc_macro;
 
 use proc_macro::TokenStream;
 
-#[proc_macro_derive(Component)]
+#[proc_macro_derive(Component, attributes(save_state))]
 pub fn derive_component(input: TokenStream) -> TokenStream {
     component::derive_component(input)
 }
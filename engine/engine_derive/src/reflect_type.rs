@@ -12,6 +12,7 @@ use crate::fq::{FQAny, FQAttributeValue, FQBox, FQReflect, FQReflectedType, FQTy
 #[derive(Debug)]
 struct ReflectAttribute {
     name: Ident,
+    negative: bool,
     value: Option<Lit>,
 }
 
@@ -19,8 +20,13 @@ impl Parse for ReflectAttribute {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let name = input.parse()?;
         let _equals: Option<Token![=]> = input.parse()?;
+        let minus: Option<Token![-]> = input.parse()?;
         let value = input.parse()?;
-        Ok(ReflectAttribute { name, value })
+        Ok(ReflectAttribute {
+            name,
+            negative: minus.is_some(),
+            value,
+        })
     }
 }
 
@@ -29,11 +35,16 @@ fn attribute_map(attrs: &[ReflectAttribute]) -> proc_macro2::TokenStream {
     for attr in attrs {
         let name = attr.name.to_string();
         let lit_name = LitStr::new(name.as_str(), Span::call_site());
+        let sign = if attr.negative {
+            quote! { - }
+        } else {
+            quote! {}
+        };
         let value = match attr.value.as_ref() {
             Some(lit) => match lit {
                 Lit::Str(str) => quote! { #FQAttributeValue::String(#str) },
-                Lit::Float(float) => quote! { #FQAttributeValue::Float(#float) },
-                Lit::Int(int) => quote! { #FQAttributeValue::Integer(#int) },
+                Lit::Float(float) => quote! { #FQAttributeValue::Float(#sign #float) },
+                Lit::Int(int) => quote! { #FQAttributeValue::Integer(#sign #int) },
                 _ => quote! { #FQAttributeValue::None },
             },
             None => quote! { #FQAttributeValue::None },
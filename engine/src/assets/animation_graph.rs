@@ -1,14 +1,14 @@
 use crate as engine;
 use crate::assets::animation::Animation;
 use crate::assets::error::AssetError;
-use crate::assets::{Asset, AssetRef, AssetRegistry, LoadedAsset};
+use crate::assets::{Asset, AssetAccess, AssetRef, AssetRegistry, LoadedAsset};
 use crate::context::ReadOnlyAssetContext;
 use crate::math::Distance;
 use eframe::emath::Pos2;
 use engine_derive::TypeUuid;
 use petgraph::prelude::StableGraph;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::io::Error;
 use std::ops::{Deref, DerefMut};
 use std::path::Path;
@@ -119,6 +119,27 @@ where
     }
 }
 
+impl AnimationMotion {
+    /// Adds the ids of the animations the motion plays to `animations`.
+    fn collect_animations(&self, animations: &mut BTreeSet<Uuid>) {
+        match self {
+            Self::AnimationClip(clip) => {
+                animations.insert(clip.animation.id());
+            }
+            Self::BlendTree1D(tree) => {
+                for motion in &tree.motions {
+                    motion.motion.collect_animations(animations);
+                }
+            }
+            Self::BlendTree2D(tree) => {
+                for motion in &tree.motions {
+                    motion.motion.collect_animations(animations);
+                }
+            }
+        }
+    }
+}
+
 impl Default for AnimationMotion {
     fn default() -> Self {
         Self::AnimationClip(Default::default())
@@ -272,4 +293,20 @@ impl Asset for AnimationGraph {
     fn to_file(&self, path: &Path) -> Result<(), Error> {
         AssetRegistry::write_to_file(self, path)
     }
+
+    fn has_dependencies() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn dependencies(&self) -> BTreeSet<Uuid> {
+        let mut animations = BTreeSet::new();
+        for node in self.graph.node_weights() {
+            node.motion.collect_animations(&mut animations);
+        }
+        animations.remove(&Uuid::nil());
+        animations
+    }
 }
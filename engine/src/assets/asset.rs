@@ -1,4 +1,5 @@
 use std::any::{Any, TypeId};
+use std::collections::{BTreeSet, HashMap};
 use std::ops::Deref;
 use std::path::Path;
 use std::sync::{Arc, RwLock};
@@ -19,7 +20,9 @@ use crate::assets::texture::Texture;
 use crate::assets::LoadedAsset;
 use crate::context::ReadOnlyAssetContext;
 use crate::core::Ref;
+use crate::reflect::{ReflectContainer, ReflectDefault};
 use crate::render::Shader;
+use crate::scene::{Prefab, Scene};
 use crate::utils::{ContextSeed, TypeUuid};
 
 use super::animation::Animation;
@@ -40,6 +43,12 @@ pub trait Asset: Any + Send + Sync {
     {
         &[]
     }
+    fn requires_gpu() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
     fn from_file(
         assets: &ReadOnlyAssetContext,
         path: &Path,
@@ -49,6 +58,29 @@ pub trait Asset: Any + Send + Sync {
     fn to_file(&self, _path: &Path) -> Result<(), std::io::Error> {
         Ok(())
     }
+    /// Whether assets of this type can reference other assets, for
+    /// [`AssetRegistry::scan_dependencies`](crate::assets::AssetRegistry::scan_dependencies)
+    /// to load them.
+    fn has_dependencies() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+    /// Whether the loaded asset is built from the contents of the assets it references, so that
+    /// it has to be reloaded along with them. Other assets using a reloaded asset only get their
+    /// [`AssetRegistry::asset_version`](crate::assets::AssetRegistry::asset_version) bumped.
+    fn reload_with_dependencies() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+    /// Ids of the assets this asset references, which the asset registry keeps track of to
+    /// tell what uses an asset and to reload its dependents along with it.
+    fn dependencies(&self) -> BTreeSet<Uuid> {
+        BTreeSet::new()
+    }
 }
 
 impl<T: Asset + TypeUuid> Ref<T> {
@@ -91,6 +123,12 @@ impl<T: Asset + TypeUuid> Clone for AssetRef<T> {
     }
 }
 
+impl<T: Asset + TypeUuid> PartialEq for AssetRef<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
 impl<T: Asset + TypeUuid> Serialize for AssetRef<T> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -233,6 +271,8 @@ impl_extern_type_uuid!(
     AssetRef<AnimationGraph>,
     "3c20b700-e0a1-4a01-bd53-246c1d1a292c"
 );
+impl_extern_type_uuid!(AssetRef<Prefab>, "b5e2f0c8-3d7a-4e19-8c64-0a9f1b2d7e53");
+impl_extern_type_uuid!(AssetRef<Scene>, "e71c4a96-2b8d-4f35-9a07-c6d3e15b8f42");
 
 impl_reflect_value!(AssetRef<Mesh>(AssetAccess));
 impl_reflect_value!(AssetRef<Shader>(AssetAccess));
@@ -241,3 +281,73 @@ impl_reflect_value!(AssetRef<Material>(AssetAccess));
 impl_reflect_value!(AssetRef<Skybox>(AssetAccess));
 impl_reflect_value!(AssetRef<Animation>(AssetAccess));
 impl_reflect_value!(AssetRef<AnimationGraph>(AssetAccess));
+impl_reflect_value!(AssetRef<Prefab>(AssetAccess));
+impl_reflect_value!(AssetRef<Scene>(AssetAccess));
+
+/// Makes `Vec`, `Option` and `HashMap<String, _>` fields of [`AssetRef`]s reflectable, so that
+/// the assets they reference can be found.
+macro_rules! impl_asset_ref_containers {
+    ($asset:ident, $vec_uuid:tt, $option_uuid:tt, $map_uuid:tt) => {
+        impl_extern_type_uuid!(Vec<AssetRef<$asset>>, $vec_uuid);
+        impl_extern_type_uuid!(Option<AssetRef<$asset>>, $option_uuid);
+        impl_extern_type_uuid!(HashMap<String, AssetRef<$asset>>, $map_uuid);
+        impl_reflect_value!(Vec<AssetRef<$asset>>(Default, Container));
+        impl_reflect_value!(Option<AssetRef<$asset>>(Default, Container));
+        impl_reflect_value!(HashMap<String, AssetRef<$asset>>(Default, Container));
+    };
+}
+
+impl_asset_ref_containers!(
+    Mesh,
+    "5bb31b1b-4f92-41f5-84e7-f106c37b0210",
+    "046b31e2-b6ff-46f3-a429-dc621837217a",
+    "b7972900-9a7a-44a8-a14f-2e5280e939fc"
+);
+impl_asset_ref_containers!(
+    Shader,
+    "f2204b9b-b1c5-4c69-8758-9168ec923027",
+    "faa8a5d4-3360-47fb-827a-08868c19f8ce",
+    "3e13d5d1-19d0-4962-acbb-75bc59724823"
+);
+impl_asset_ref_containers!(
+    Texture,
+    "4ec7853b-f686-43f3-8820-bdafd2739784",
+    "a3c49fb1-3ddb-4009-8876-dcb1a8e9f195",
+    "76a83b19-fd3d-471c-b786-85ff955a4c96"
+);
+impl_asset_ref_containers!(
+    Material,
+    "a7e3b148-9f04-4ac5-80b3-4d99bde9bd48",
+    "25e867bd-5857-41c6-aefb-fb5dd1b7fb71",
+    "302e1551-cfc9-4cfc-a762-51bc4f56fb07"
+);
+impl_asset_ref_containers!(
+    Skybox,
+    "e342e53f-1455-41da-896d-3297df8e3a3c",
+    "c5da0c60-96ca-4c15-9ca2-b55bc3a101a4",
+    "bbe5012f-d2bd-429e-9755-c0e8338b6563"
+);
+impl_asset_ref_containers!(
+    Animation,
+    "35a2da60-3e6f-450e-8b5b-c7c8a838e355",
+    "4335f7d1-61f1-4a27-972d-daaf8dfea5d3",
+    "2ea0f705-7da2-49f0-80c6-45500a122cb1"
+);
+impl_asset_ref_containers!(
+    AnimationGraph,
+    "1b36c2c2-0a59-47fa-b2ec-de1d823b2a0d",
+    "435efc9e-1dc6-4ea1-9352-292bed2cdd58",
+    "4b8bf931-9489-437e-bae1-0756f1aada4b"
+);
+impl_asset_ref_containers!(
+    Prefab,
+    "41807a3d-903b-43d7-80a6-e0c44da3edcc",
+    "105d339c-e24a-4ec5-a803-3ae96510c953",
+    "fe057254-2c1e-43e5-888f-e268e360943f"
+);
+impl_asset_ref_containers!(
+    Scene,
+    "a06a5bc4-cc8b-4fed-bad4-62f5ce63a2a2",
+    "4b654ccd-ee36-4aea-9fee-2ac5d7dec469",
+    "638cb0cd-ca57-41b0-ab2d-892fb05793c5"
+);
@@ -7,7 +7,7 @@ use relative_path::{PathExt, RelativePathBuf};
 use serde::{Deserialize, Serialize};
 use std::any::TypeId;
 use std::borrow::Cow;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs::{File, OpenOptions};
 use std::io::{BufReader, BufWriter};
 use std::path::{Path, PathBuf};
@@ -21,6 +21,7 @@ use crate::assets::material::Material;
 use crate::assets::mesh::Mesh;
 use crate::assets::texture::Texture;
 use crate::assets::Asset;
+use crate::assets::AssetDependencies;
 use crate::assets::LoadedAssetRef;
 use crate::class_registry::ComponentRegistry;
 use crate::component::ComponentMesh;
@@ -79,11 +80,16 @@ struct AssetData {
     names: HashMap<RelativePathBuf, Uuid>,
     extensions: HashMap<String, (TypeId, Uuid, &'static str)>,
     dirty: HashSet<Uuid>,
+    /// Number of times each asset was hot-reloaded or changed in memory.
+    versions: HashMap<Uuid, u32>,
+    dependencies: AssetDependencies,
 }
 
 struct AssetConstructors {
     create: AssetConstructor,
     reload: AssetReload,
+    has_dependencies: bool,
+    reload_with_dependencies: bool,
 }
 
 pub struct AssetRegistry {
@@ -103,14 +109,14 @@ pub struct AssetRegistry {
 impl AssetRegistry {
     pub fn new(
         root_path: impl Into<PathBuf>,
+        engine_assets_path: impl Into<PathBuf>,
         render_context: Arc<RenderContext>,
         type_registry: Ref<TypeRegistry>,
         component_registry: Ref<ComponentRegistry>,
     ) -> Result<Ref<Self>, BoxedError> {
         let (tx, watcher_rx) = std::sync::mpsc::channel();
         let path = dunce::canonicalize(root_path.into()).map_err(Box::new)?;
-        let assets_path = std::env::current_dir().map_err(Box::new)?.join("assets");
-        let assets_path = dunce::canonicalize(assets_path).map_err(Box::new)?;
+        let assets_path = dunce::canonicalize(engine_assets_path.into()).map_err(Box::new)?;
         let asset_paths = [path.clone(), assets_path];
         let mut watcher = RecommendedWatcher::new(tx, Config::default()).map_err(Box::new)?;
         for path in asset_paths.iter() {
@@ -196,7 +202,11 @@ impl AssetRegistry {
         let Some(asset_ref) = asset_cache.get(&id).cloned() else {
             return false;
         };
+        drop(asset_cache);
         let result = asset_ref.read().to_file(&asset_path);
+        if result.is_ok() {
+            self.update_dependencies(id, &asset_ref);
+        }
         result.is_ok()
     }
 
@@ -236,6 +246,7 @@ impl AssetRegistry {
             .asset_path(id, A::file_extensions())
             .ok_or(AssetError::NotFound)?;
         let asset = self.load_asset_file(id, &path)?;
+        self.update_dependencies(id, &asset.as_asset());
 
         // Create ref
         self.asset_cache_mut().insert(id, asset.as_asset());
@@ -262,6 +273,8 @@ impl AssetRegistry {
 
         // Load from file
         self.load_sub_asset_meta(id, sub_assets);
+        drop(ctors);
+        self.update_dependencies(id, &asset);
         self.asset_cache_mut().insert(id, asset.clone());
         Ok(asset)
     }
@@ -300,6 +313,9 @@ impl AssetRegistry {
                 path: None,
             },
         );
+        drop(data);
+        drop(registry);
+        self.update_dependencies(id, &asset.as_asset());
         self.asset_cache_mut().insert(id, asset.as_asset());
         Ok(asset)
     }
@@ -329,7 +345,7 @@ impl AssetRegistry {
             type_uuid,
             AssetConstructors {
                 create: Box::new(|game, id, path| {
-                    let loaded_asset = A::from_file(&game, path)?;
+                    let loaded_asset = Self::asset_from_file::<A>(&game, path)?;
                     let LoadedAssetRef { asset, sub_assets } =
                         LoadedAssetRef::new(id, loaded_asset);
                     Ok(LoadedAssetRef {
@@ -342,7 +358,7 @@ impl AssetRegistry {
                         let LoadedAsset {
                             asset: loaded_asset,
                             sub_assets,
-                        } = A::from_file(&game, path)?;
+                        } = Self::asset_from_file::<A>(&game, path)?;
                         {
                             let mut asset = asset_ref.write();
                             *asset = loaded_asset;
@@ -355,6 +371,8 @@ impl AssetRegistry {
                         Err(AssetError::LoadError)
                     }
                 }),
+                has_dependencies: A::has_dependencies(),
+                reload_with_dependencies: A::reload_with_dependencies(),
             },
         );
     }
@@ -385,15 +403,30 @@ impl AssetRegistry {
     }
 
     fn load_asset_file<A: Asset>(&self, id: Uuid, path: &Path) -> Result<Ref<A>, AssetError> {
-        let loaded_asset = A::from_file(&self.game_context(), path)?;
+        let loaded_asset = Self::asset_from_file::<A>(&self.game_context(), path)?;
         let LoadedAssetRef { asset, sub_assets } = LoadedAssetRef::new(id, loaded_asset);
         self.load_sub_asset_meta(id, sub_assets);
         Ok(asset)
     }
 
+    fn asset_from_file<A: Asset>(
+        game: &ReadOnlyAssetContext,
+        path: &Path,
+    ) -> Result<LoadedAsset<A>, AssetError> {
+        if A::requires_gpu() && !game.render_context.has_gpu() {
+            return Err(AssetError::GpuUnavailable);
+        }
+        A::from_file(game, path)
+    }
+
     fn mark_asset_dirty(&self, id: Uuid) {
         self.asset_data_mut().dirty.insert(id);
     }
+
+    fn update_dependencies(&self, id: Uuid, asset: &Ref<dyn Asset>) {
+        let dependencies = asset.read().dependencies();
+        self.asset_data_mut().dependencies.set(id, dependencies);
+    }
 }
 
 impl AssetRegistry {
@@ -450,6 +483,9 @@ impl AssetRegistry {
             }
             EventKind::Remove(_) => {
                 for file in paths_iter {
+                    if let Some(id) = self.asset_id_from_path(file) {
+                        self.warn_if_referenced(id);
+                    }
                     let _ = std::fs::remove_file(file.with_extension("meta"));
                 }
             }
@@ -457,6 +493,25 @@ impl AssetRegistry {
         }
     }
 
+    /// Warns about a deleted asset still used by other assets and returns them. Only the
+    /// dependencies of assets loaded or saved so far are known, the watcher doesn't load the
+    /// rest of the project to find them, see [`AssetRegistry::scan_dependencies`].
+    pub(crate) fn warn_if_referenced(&self, id: Uuid) -> BTreeSet<Uuid> {
+        let dependents = self.dependents(id);
+        if !dependents.is_empty() {
+            let names = dependents
+                .iter()
+                .map(|id| self.asset_name(*id))
+                .collect::<Vec<_>>();
+            eprintln!(
+                "Deleted asset {} is still used by {}",
+                self.asset_name(id),
+                names.join(", ")
+            );
+        }
+        dependents
+    }
+
     fn notify_event_paths(event: &Event) -> impl Iterator<Item = &PathBuf> {
         event
             .paths
@@ -476,6 +531,70 @@ impl AssetRegistry {
             .filter_map(|(ext, f)| if ext != "meta" { Some(f) } else { None })
     }
 
+    /// Returns how many times an asset was reloaded, so that data derived from it
+    /// can be rebuilt when it changes.
+    pub fn asset_version(&self, id: Uuid) -> u32 {
+        self.asset_data()
+            .versions
+            .get(&id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Bumps the version of an asset changed in memory, so that data derived from it gets
+    /// rebuilt as if it was reloaded.
+    pub fn asset_changed(&self, id: Uuid) {
+        *self.asset_data_mut().versions.entry(id).or_default() += 1;
+        let asset = self.asset_cache().get(&id).cloned();
+        if let Some(asset) = asset {
+            self.update_dependencies(id, &asset);
+        }
+    }
+
+    /// Assets the asset references, see [`Asset::dependencies`]. Only loaded assets are
+    /// tracked, see [`AssetRegistry::scan_dependencies`].
+    pub fn dependencies(&self, id: Uuid) -> BTreeSet<Uuid> {
+        self.asset_data().dependencies.dependencies(id)
+    }
+
+    /// Loaded assets referencing the asset.
+    pub fn dependents(&self, id: Uuid) -> BTreeSet<Uuid> {
+        self.asset_data().dependencies.dependents(id)
+    }
+
+    /// Assets the asset needs, referenced by it or by the assets it needs, the closest first.
+    pub fn all_dependencies(&self, id: Uuid) -> Vec<Uuid> {
+        self.asset_data().dependencies.all_dependencies(id)
+    }
+
+    /// Loaded assets using the asset, directly or through other assets, the closest first.
+    pub fn all_dependents(&self, id: Uuid) -> Vec<Uuid> {
+        self.asset_data().dependencies.all_dependents(id)
+    }
+
+    /// Loads the assets of the types that can reference other assets, so that the dependencies
+    /// of all assets are known, including the ones nothing loaded yet.
+    pub fn scan_dependencies(&self) {
+        let ids = {
+            let ctors = self.asset_constructors();
+            let cache = self.asset_cache();
+            self.asset_data()
+                .meta
+                .values()
+                .filter(|meta| meta.path.is_some() && !cache.contains_key(&meta.id))
+                .filter(|meta| {
+                    ctors
+                        .get(&meta.type_uuid)
+                        .is_some_and(|ctor| ctor.has_dependencies)
+                })
+                .map(|meta| meta.id)
+                .collect::<Vec<_>>()
+        };
+        for id in ids {
+            let _ = self.load_dyn_by_id(id);
+        }
+    }
+
     pub fn asset_id(&self, name: &str) -> Option<Uuid> {
         let path = RelativePathBuf::from(name).normalize();
         self.asset_data().names.get(&path).copied()
@@ -666,26 +785,66 @@ impl AssetRegistry {
         }
     }
 
+    /// Reloads the assets changed on disk. The loaded assets using them are reloaded as well
+    /// when their data is built from the contents of the reloaded assets, see
+    /// [`Asset::reload_with_dependencies`], and only get their version bumped otherwise.
     pub fn reload_assets(&self) {
-        let cache = self.asset_cache();
-        let reload_ids = self.asset_data_mut().dirty.drain().collect::<Vec<_>>();
-        for id in reload_ids {
-            let Some(path) = self.asset_meta_from_id(id).and_then(|meta| meta.path) else {
-                continue;
-            };
-            let Some(asset_ref) = cache.get(&id) else {
-                continue;
-            };
-            let Some(meta) = self.asset_meta_from_id(id) else {
-                continue;
-            };
-
-            let ctors = self.asset_constructors();
-            if let Some(ctor) = ctors.get(&meta.type_uuid) {
-                let _ = (ctor.reload)(self.game_context(), asset_ref, &path);
+        let dirty = self.asset_data_mut().dirty.drain().collect::<Vec<_>>();
+        let dirty = dirty
+            .into_iter()
+            .filter(|id| self.reload_asset(*id))
+            .collect::<Vec<_>>();
+        let mut reloaded = dirty.iter().copied().collect::<HashSet<_>>();
+        let mut notified = HashSet::new();
+        for id in dirty {
+            for dependent in self.all_dependents(id) {
+                if reloaded.contains(&dependent) || notified.contains(&dependent) {
+                    continue;
+                }
+                let reload = self.reloads_with_dependencies(dependent)
+                    && self
+                        .dependencies(dependent)
+                        .iter()
+                        .any(|dependency| reloaded.contains(dependency));
+                if reload && self.reload_asset(dependent) {
+                    reloaded.insert(dependent);
+                } else {
+                    *self.asset_data_mut().versions.entry(dependent).or_default() += 1;
+                    notified.insert(dependent);
+                }
             }
         }
     }
+
+    fn reloads_with_dependencies(&self, id: Uuid) -> bool {
+        self.asset_meta_from_id(id).is_some_and(|meta| {
+            self.asset_constructors()
+                .get(&meta.type_uuid)
+                .is_some_and(|ctor| ctor.reload_with_dependencies)
+        })
+    }
+
+    /// Reloads a loaded asset from its file, returning whether it was reloaded.
+    fn reload_asset(&self, id: Uuid) -> bool {
+        let Some(meta) = self.asset_meta_from_id(id) else {
+            return false;
+        };
+        let Some(path) = meta.path else {
+            return false;
+        };
+        let Some(asset_ref) = self.asset_cache().get(&id).cloned() else {
+            return false;
+        };
+        let reloaded = self
+            .asset_constructors()
+            .get(&meta.type_uuid)
+            .is_some_and(|ctor| (ctor.reload)(self.game_context(), &asset_ref, &path).is_ok());
+        if reloaded {
+            *self.asset_data_mut().versions.entry(id).or_default() += 1;
+            self.update_dependencies(id, &asset_ref);
+        }
+        reloaded
+    }
 }
 
 impl AssetRegistry {
@@ -790,7 +949,7 @@ impl AssetRegistry {
                     vec![],
                     vec![],
                 ],
-                ..Mesh::new(&self.render_context)
+                ..Default::default()
             };
             quad.mark_dirty();
             quad
@@ -799,7 +958,7 @@ impl AssetRegistry {
 
     pub fn wire_circle(&self) -> Mesh {
         const RESOLUTION: usize = 72;
-        let mut circle = Mesh::new(&self.render_context);
+        let mut circle = Mesh::default();
         circle.vertices.resize(RESOLUTION + 1, vec3(0.0, 0.0, 0.0));
         circle.normals.resize(RESOLUTION + 1, vec3(0.0, 0.0, 0.0));
         for i in 0..RESOLUTION {
@@ -839,7 +998,7 @@ impl AssetRegistry {
                 vec3(0.0, 0.0, 0.0),
                 vec3(0.0, 0.0, 0.0),
             ],
-            ..Mesh::new(&self.render_context)
+            ..Default::default()
         };
         cube.mark_dirty();
         cube
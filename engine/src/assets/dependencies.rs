@@ -0,0 +1,202 @@
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+use uuid::Uuid;
+
+use crate::assets::ReflectAssetAccess;
+use crate::reflect::type_registry::TypeRegistry;
+use crate::reflect::{Reflect, ReflectContainer, TypeInfo};
+
+/// Which loaded assets reference which, built from [`Asset::dependencies`](crate::assets::Asset::dependencies).
+#[derive(Default)]
+pub(crate) struct AssetDependencies {
+    dependencies: HashMap<Uuid, BTreeSet<Uuid>>,
+    /// The reverse of `dependencies`.
+    dependents: HashMap<Uuid, BTreeSet<Uuid>>,
+}
+
+impl AssetDependencies {
+    /// Replaces the assets an asset references.
+    pub fn set(&mut self, id: Uuid, dependencies: BTreeSet<Uuid>) {
+        self.remove(id);
+        for dependency in &dependencies {
+            self.dependents.entry(*dependency).or_default().insert(id);
+        }
+        if !dependencies.is_empty() {
+            self.dependencies.insert(id, dependencies);
+        }
+    }
+
+    pub fn remove(&mut self, id: Uuid) {
+        for dependency in self.dependencies.remove(&id).unwrap_or_default() {
+            if let Some(dependents) = self.dependents.get_mut(&dependency) {
+                dependents.remove(&id);
+                if dependents.is_empty() {
+                    self.dependents.remove(&dependency);
+                }
+            }
+        }
+    }
+
+    pub fn dependencies(&self, id: Uuid) -> BTreeSet<Uuid> {
+        self.dependencies.get(&id).cloned().unwrap_or_default()
+    }
+
+    pub fn dependents(&self, id: Uuid) -> BTreeSet<Uuid> {
+        self.dependents.get(&id).cloned().unwrap_or_default()
+    }
+
+    pub fn all_dependencies(&self, id: Uuid) -> Vec<Uuid> {
+        Self::walk(&self.dependencies, id)
+    }
+
+    pub fn all_dependents(&self, id: Uuid) -> Vec<Uuid> {
+        Self::walk(&self.dependents, id)
+    }
+
+    /// Assets reachable from `id`, the closest first.
+    fn walk(edges: &HashMap<Uuid, BTreeSet<Uuid>>, id: Uuid) -> Vec<Uuid> {
+        let mut visited = HashSet::from([id]);
+        let mut queue = VecDeque::from([id]);
+        let mut result = Vec::new();
+        while let Some(current) = queue.pop_front() {
+            for next in edges.get(&current).into_iter().flatten() {
+                if visited.insert(*next) {
+                    result.push(*next);
+                    queue.push_back(*next);
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Adds the ids of the assets referenced by the [`AssetRef`](crate::assets::AssetRef) fields of
+/// a reflected value to `dependencies`, looking into its reflected struct fields and the
+/// elements of its `Vec`, `Option` and map fields.
+pub fn reflect_dependencies(
+    type_registry: &TypeRegistry,
+    value: &dyn Reflect,
+    dependencies: &mut BTreeSet<Uuid>,
+) {
+    if let Some(asset_ref) = type_registry
+        .trait_meta::<ReflectAssetAccess>(value.uuid())
+        .and_then(|meta| meta.get(value))
+    {
+        if !asset_ref.id().is_nil() {
+            dependencies.insert(asset_ref.id());
+        }
+        return;
+    }
+    if let Some(container) = type_registry
+        .trait_meta::<ReflectContainer>(value.uuid())
+        .and_then(|meta| meta.get(value))
+    {
+        for element in container.elements() {
+            reflect_dependencies(type_registry, element, dependencies);
+        }
+        return;
+    }
+    if let Some(TypeInfo::Struct(info)) = type_registry.type_info_by_id(value.uuid()) {
+        for field in info.fields.values() {
+            if let Some(field_value) = field.get_reflect(value) {
+                reflect_dependencies(type_registry, field_value, dependencies);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate as engine;
+    use crate::assets::material::Material;
+    use crate::assets::mesh::Mesh;
+    use crate::assets::skybox::Skybox;
+    use crate::assets::texture::Texture;
+    use crate::assets::{AssetAccess, AssetRef};
+    use crate::component::{Component, ComponentMesh, ReflectComponent};
+    use crate::reflect::{Reflect, ReflectDefault};
+    use crate::render::Shader;
+    use crate::scene::Scene;
+    use crate::test_utils;
+    use crate::utils::{ReflectTypeUuidDynamic, TypeUuid};
+    use uuid::Uuid;
+
+    #[derive(Default, TypeUuid, Serialize, Deserialize, Component, Reflect)]
+    #[uuid = "9d2f6b1e-58a3-4c07-b4e9-1f7a3c6d82e5"]
+    #[reflect(Default, TypeUuidDynamic, Component)]
+    struct Gallery {
+        pictures: Vec<AssetRef<Texture>>,
+        sky: Option<AssetRef<Skybox>>,
+        props: HashMap<String, AssetRef<Mesh>>,
+    }
+
+    impl Component for Gallery {}
+
+    fn asset_ref<T: crate::assets::Asset + TypeUuid>(id: Uuid) -> AssetRef<T> {
+        let mut asset_ref = AssetRef::default();
+        *asset_ref.id_mut() = id;
+        asset_ref
+    }
+
+    #[test]
+    fn dependency_graph() {
+        let assets = test_utils::assets();
+        let (mesh, shader) = (Uuid::new_v4(), Uuid::new_v4());
+        let registry = assets.asset_registry.read();
+        let material = registry
+            .create(
+                "tests/dependency_graph/material".into(),
+                Material {
+                    shader: asset_ref::<Shader>(shader),
+                    variables: Vec::new(),
+                    bind_group_entries: Default::default(),
+                    buffers: Default::default(),
+                },
+            )
+            .unwrap();
+        let mut scene = assets.scene();
+        let game_object = scene.create_game_object(None, None);
+        scene.add_component(
+            game_object,
+            ComponentMesh {
+                mesh: asset_ref::<Mesh>(mesh),
+                material: asset_ref::<Material>(material.id()),
+            },
+        );
+        let (picture, sky, prop) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let gallery = scene.create_game_object(None, None);
+        scene.add_component(
+            gallery,
+            Gallery {
+                pictures: vec![asset_ref(picture), AssetRef::default()],
+                sky: Some(asset_ref(sky)),
+                props: [("vase".to_string(), asset_ref(prop))].into(),
+            },
+        );
+        let scene = registry
+            .create::<Scene>("tests/dependency_graph/scene".into(), scene)
+            .unwrap();
+
+        assert_eq!(
+            registry.dependencies(scene.id()),
+            [mesh, material.id(), picture, sky, prop].into()
+        );
+        assert_eq!(registry.dependents(material.id()), [scene.id()].into());
+        assert!(registry.all_dependencies(scene.id()).contains(&shader));
+        assert_eq!(
+            registry.all_dependents(shader),
+            vec![material.id(), scene.id()]
+        );
+
+        // Deleting an asset warns about the loaded assets still using it.
+        assert_eq!(
+            registry.warn_if_referenced(material.id()),
+            [scene.id()].into()
+        );
+        assert!(registry.warn_if_referenced(scene.id()).is_empty());
+    }
+}
@@ -1,11 +1,12 @@
 use russimp::RussimpError;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum AssetError {
     NotFound,
     LoadError,
     AlreadyExists,
     TypeMismatch,
+    GpuUnavailable,
 }
 
 impl From<RussimpError> for AssetError {
@@ -1,5 +1,5 @@
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::io::BufReader;
 use std::path::Path;
 
@@ -16,6 +16,7 @@ use egui::Color32;
 use egui_wgpu::{wgpu, RenderState};
 use naga::{ImageDimension, Scalar, ScalarKind, TypeInner, VectorSize};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 pub enum BindingType {
     Buffer,
@@ -135,6 +136,13 @@ impl Asset for Material {
         &["cxmat"]
     }
 
+    fn requires_gpu() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
     fn from_file(
         assets: &ReadOnlyAssetContext,
         path: &Path,
@@ -152,6 +160,30 @@ impl Asset for Material {
         let material: Material = (assets, data).into();
         Ok(LoadedAsset::new(material))
     }
+
+    fn has_dependencies() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn reload_with_dependencies() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn dependencies(&self) -> BTreeSet<Uuid> {
+        std::iter::once(self.shader.id())
+            .chain(self.variables.iter().filter_map(|var| match &var.value {
+                ShaderVariableValue::Texture2D(texture) => Some(texture.id()),
+                _ => None,
+            }))
+            .filter(|id| !id.is_nil())
+            .collect()
+    }
 }
 
 impl Material {
@@ -12,7 +12,6 @@ use crate::assets::error::AssetError;
 use crate::assets::Asset;
 use crate::context::ReadOnlyAssetContext;
 use crate::render::buffer::{wgpu_buffer_init_desc, BufferLayout, ResizableBuffer};
-use crate::render::RenderContext;
 use crate::utils::TypeUuid;
 use crate::{self as engine, math};
 
@@ -60,7 +59,9 @@ pub struct MeshUniforms {
 #[derive(Default, Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Instance {
     pub bone_transform_index: i32,
-    pub _padding: [u32; 3],
+    /// Mask of the layers of the game object, lights only affect the layers of their mask.
+    pub layers: u32,
+    pub _padding: [u32; 2],
     pub transform: [[f32; 4]; 4],
 }
 
@@ -92,10 +93,10 @@ pub struct Mesh {
     pub(crate) bone_transforms: Vec<BoneTransform>,
     pub(crate) index_buffer: Option<wgpu::Buffer>,
     pub(crate) vertex_buffer: Option<wgpu::Buffer>,
-    pub(crate) instance_buffer: wgpu::Buffer,
+    pub(crate) instance_buffer: Option<wgpu::Buffer>,
     pub(crate) bone_buffer: ResizableBuffer,
 
-    pub(crate) instance_bind_group_layout: wgpu::BindGroupLayout,
+    pub(crate) instance_bind_group_layout: Option<wgpu::BindGroupLayout>,
 }
 
 impl Mesh {
@@ -115,44 +116,8 @@ impl Mesh {
     // const ATTRIBUTE_MODEL3: u32 = 9;
 }
 
-impl Mesh {
-    pub fn new(render_context: &RenderContext) -> Self {
-        let device = render_context.device();
-
-        let instance_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("instance_buffer"),
-            size: (std::mem::size_of::<MeshUniforms>()) as u64,
-            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
-            mapped_at_creation: false,
-        });
-
-        let instance_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("instance_bind_group_layout"),
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
-                        ty: wgpu::BindingType::Buffer {
-                            ty: wgpu::BufferBindingType::Uniform,
-                            has_dynamic_offset: false,
-                            min_binding_size: None,
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
-                        ty: wgpu::BindingType::Buffer {
-                            ty: wgpu::BufferBindingType::Storage { read_only: true },
-                            has_dynamic_offset: false,
-                            min_binding_size: None,
-                        },
-                        count: None,
-                    },
-                ],
-            });
-
+impl Default for Mesh {
+    fn default() -> Self {
         Self {
             indices: Default::default(),
             vertices: Default::default(),
@@ -171,11 +136,11 @@ impl Mesh {
             bone_transforms: Default::default(),
             index_buffer: None,
             vertex_buffer: None,
-            instance_buffer,
+            instance_buffer: None,
             bone_buffer: ResizableBuffer::new(
                 wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::STORAGE,
             ),
-            instance_bind_group_layout,
+            instance_bind_group_layout: None,
         }
     }
 }
@@ -193,7 +158,7 @@ impl Asset for Mesh {
     }
 
     fn from_file(
-        game: &ReadOnlyAssetContext,
+        _game: &ReadOnlyAssetContext,
         path: &Path,
     ) -> Result<LoadedAsset<Self>, AssetError> {
         let scene = Scene::from_file(
@@ -209,10 +174,7 @@ impl Asset for Mesh {
 
         // Assuming you want to load the first mesh in the scene
         let mesh = scene.meshes.first().ok_or(AssetError::NotFound)?;
-        Ok(LoadedAsset::new(Mesh::from_russimp_mesh(
-            &game.render_context,
-            mesh,
-        )))
+        Ok(LoadedAsset::new(Mesh::from_russimp_mesh(mesh)))
     }
 }
 
@@ -238,7 +200,7 @@ impl Mesh {
         }
     }
 
-    pub fn from_russimp_mesh(render_context: &RenderContext, mesh: &russimp::mesh::Mesh) -> Self {
+    pub fn from_russimp_mesh(mesh: &russimp::mesh::Mesh) -> Self {
         let indices = mesh
             .faces
             .iter()
@@ -317,7 +279,7 @@ impl Mesh {
             bone_indices,
             bone_weights,
             dirty: true,
-            ..Mesh::new(render_context)
+            ..Default::default()
         }
     }
 }
@@ -360,7 +322,51 @@ impl Mesh {
         )));
     }
 
+    fn create_instance_resources(&mut self, device: &wgpu::Device) {
+        if self.instance_buffer.is_some() {
+            return;
+        }
+
+        self.instance_buffer = Some(device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("instance_buffer"),
+            size: (std::mem::size_of::<MeshUniforms>()) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        }));
+
+        self.instance_bind_group_layout = Some(device.create_bind_group_layout(
+            &wgpu::BindGroupLayoutDescriptor {
+                label: Some("instance_bind_group_layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            },
+        ));
+    }
+
     fn rebuild_instance_buffer(&self, queue: &wgpu::Queue) {
+        let Some(instance_buffer) = &self.instance_buffer else {
+            return;
+        };
         let mut instances: [Instance; Self::MAX_INSTANCES] = Default::default();
         for (i, instance) in self.instances.iter().take(Self::MAX_INSTANCES).enumerate() {
             instances[i] = *instance;
@@ -371,7 +377,7 @@ impl Mesh {
             instances,
         };
         queue.write_buffer(
-            &self.instance_buffer,
+            instance_buffer,
             0 as wgpu::BufferAddress,
             bytemuck::cast_slice([uniforms].as_slice()),
         );
@@ -407,25 +413,32 @@ impl Mesh {
     }
 
     pub(crate) fn rebuild_instance_data(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.create_instance_resources(device);
         self.rebuild_instance_buffer(queue);
         self.rebuild_bone_buffer(device, queue);
     }
 
-    pub(crate) fn instance_bind_group(&self, device: &wgpu::Device) -> wgpu::BindGroup {
-        device.create_bind_group(&wgpu::BindGroupDescriptor {
+    /// Bind group of the instance data, once it was created by `rebuild_instance_data`.
+    pub(crate) fn instance_bind_group(&self, device: &wgpu::Device) -> Option<wgpu::BindGroup> {
+        let (Some(layout), Some(instance_buffer)) =
+            (&self.instance_bind_group_layout, &self.instance_buffer)
+        else {
+            return None;
+        };
+        Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("instance_bind_group"),
-            layout: &self.instance_bind_group_layout,
+            layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: self.instance_buffer.as_entire_binding(),
+                    resource: instance_buffer.as_entire_binding(),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
                     resource: self.bone_buffer.get_wgpu_buffer().as_entire_binding(),
                 },
             ],
-        })
+        }))
     }
 
     pub fn mark_dirty(&mut self) {
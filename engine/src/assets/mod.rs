@@ -1,11 +1,13 @@
 pub use asset::*;
 pub use asset_registry::*;
+pub use dependencies::*;
 pub use loaded_asset::*;
 
 pub mod animation;
 pub mod animation_graph;
 mod asset;
 mod asset_registry;
+mod dependencies;
 pub mod error;
 mod loaded_asset;
 pub mod material;
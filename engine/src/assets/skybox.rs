@@ -39,6 +39,13 @@ impl Asset for Skybox {
         &["exr", "hdr"]
     }
 
+    fn requires_gpu() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
     fn from_file(
         game: &ReadOnlyAssetContext,
         path: &Path,
@@ -36,6 +36,13 @@ impl Asset for Texture {
         &["png", "jpg", "jpeg", "webp"]
     }
 
+    fn requires_gpu() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
     fn from_file(
         game: &ReadOnlyAssetContext,
         path: &Path,
@@ -1,24 +1,49 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::ops::Deref;
 
 use uuid::Uuid;
 
 use crate::component::{Component, ReflectComponent};
 use crate::reflect::type_registry::TypeRegistry;
-use crate::reflect::{ReflectDefault, TypeInfo};
+use crate::reflect::{AttributeValue, ReflectDefault, TypeInfo};
+use crate::scene::{ReflectSystem, System, SystemStage};
 use crate::type_uuids;
 use crate::utils::ReflectTypeUuidDynamic;
 
+/// Keeps an instance of every registered component and system, along with the component
+/// types receiving each update hook.
+///
+/// Components, update lists and systems are sorted by the `priority` reflect attribute, lowest first
+/// (types without one have a priority of 0), then by type uuid, so that the update order is
+/// the same on every run:
+/// ```ignore
+/// #[reflect_attr(name = "Player Input", update, priority = -10)]
+/// ```
+///
+/// The `version` reflect attribute gives the version of a component's serialized data, see
+/// [`Component::migrate`].
 pub struct ComponentRegistry {
     components: HashMap<Uuid, Box<dyn Component>>,
-    components_update: HashSet<Uuid>,
+    versions: HashMap<Uuid, u32>,
+    components_ordered: Vec<Uuid>,
+    components_update: Vec<Uuid>,
+    components_fixed_update: Vec<Uuid>,
+    components_late_update: Vec<Uuid>,
+    systems: Vec<(Uuid, Box<dyn System>)>,
+    generation: u64,
 }
 
 impl ComponentRegistry {
     pub fn new(type_registry: &TypeRegistry) -> Self {
         let mut registry = Self {
             components: Default::default(),
+            versions: Default::default(),
+            components_ordered: Default::default(),
             components_update: Default::default(),
+            components_fixed_update: Default::default(),
+            components_late_update: Default::default(),
+            systems: Default::default(),
+            generation: 0,
         };
         registry.refresh_class_lists(type_registry);
         registry
@@ -30,9 +55,33 @@ impl ComponentRegistry {
         self.components.get(&id).map(|b| b.deref())
     }
 
+    /// Current version of a component's serialized data.
+    pub fn component_version(&self, id: Uuid) -> u32 {
+        self.versions.get(&id).copied().unwrap_or_default()
+    }
+
+    /// Every component, in update order.
+    pub fn components_ordered(&self) -> impl Iterator<Item = (Uuid, &Box<dyn Component>)> {
+        self.components_in(&self.components_ordered)
+    }
+
     pub fn components_update(&self) -> impl Iterator<Item = (Uuid, &Box<(dyn Component)>)> {
-        self.components_update
-            .iter()
+        self.components_in(&self.components_update)
+    }
+
+    pub fn components_fixed_update(&self) -> impl Iterator<Item = (Uuid, &Box<dyn Component>)> {
+        self.components_in(&self.components_fixed_update)
+    }
+
+    pub fn components_late_update(&self) -> impl Iterator<Item = (Uuid, &Box<dyn Component>)> {
+        self.components_in(&self.components_late_update)
+    }
+
+    fn components_in<'a>(
+        &'a self,
+        ids: &'a [Uuid],
+    ) -> impl Iterator<Item = (Uuid, &'a Box<dyn Component>)> {
+        ids.iter()
             .filter_map(|id| self.components.get(id).map(|component| (*id, component)))
     }
 
@@ -40,10 +89,29 @@ impl ComponentRegistry {
         self.components.iter()
     }
 
+    /// Changes every time the class lists are refreshed, so that what was built from them
+    /// can be rebuilt.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn systems(&self, stage: SystemStage) -> impl Iterator<Item = &dyn System> {
+        self.systems
+            .iter()
+            .map(|(_, system)| system.as_ref())
+            .filter(move |system| system.stage() == stage)
+    }
+
     pub fn refresh_class_lists(&mut self, type_registry: &TypeRegistry) {
         use crate as engine;
         self.components.clear();
+        self.versions.clear();
+        self.components_ordered.clear();
         self.components_update.clear();
+        self.components_fixed_update.clear();
+        self.components_late_update.clear();
+        self.systems.clear();
+        self.generation += 1;
         for type_id in type_registry.all_of(type_uuids!(
             ReflectDefault,
             ReflectComponent,
@@ -58,10 +126,47 @@ impl ComponentRegistry {
             let type_info = type_registry.type_info_by_id(type_id).unwrap();
             if let TypeInfo::Struct(struct_info) = type_info {
                 if struct_info.attr("update").is_some() {
-                    self.components_update.insert(type_id);
+                    self.components_update.push(type_id);
+                }
+                if struct_info.attr("fixed_update").is_some() {
+                    self.components_fixed_update.push(type_id);
+                }
+                if struct_info.attr("late_update").is_some() {
+                    self.components_late_update.push(type_id);
+                }
+                if let Some(AttributeValue::Integer(version)) = struct_info.attr("version") {
+                    self.versions.insert(type_id, version.max(0) as u32);
                 }
             }
             self.components.insert(type_id, component);
+            self.components_ordered.push(type_id);
+        }
+
+        for type_id in type_registry.all_of(type_uuids!(
+            ReflectDefault,
+            ReflectSystem,
+            ReflectTypeUuidDynamic
+        )) {
+            let meta_default = type_registry.trait_meta::<ReflectDefault>(type_id).unwrap();
+            let meta_system = type_registry.trait_meta::<ReflectSystem>(type_id).unwrap();
+            let system = meta_system.get_boxed(meta_default.default()).unwrap();
+            self.systems.push((type_id, system));
         }
+
+        let update_order = |id: &Uuid| {
+            let priority = match type_registry.type_info_by_id(*id) {
+                Some(TypeInfo::Struct(struct_info)) => match struct_info.attr("priority") {
+                    Some(AttributeValue::Integer(priority)) => priority,
+                    _ => 0,
+                },
+                _ => 0,
+            };
+            (priority, *id)
+        };
+        self.components_ordered.sort_by_key(update_order);
+        self.components_update.sort_by_key(update_order);
+        self.components_fixed_update.sort_by_key(update_order);
+        self.components_late_update.sort_by_key(update_order);
+        self.systems.sort_by_key(|(id, _)| update_order(id));
     }
 }
@@ -1,5 +1,6 @@
 use super::{
-    Component, ComponentBone, ComponentEventContext, ComponentSkinnedMesh, ReflectComponent,
+    Component, ComponentBone, ComponentEventContext, ComponentID, ComponentSkinnedMesh,
+    ReflectComponent,
 };
 use crate as engine;
 use crate::assets::animation::{AnimationKeyFrames, QuatKeyFrame, VectorKeyFrame};
@@ -14,12 +15,14 @@ use crate::core::{Time, TimeType};
 use crate::input::Input;
 use crate::render::Gizmos;
 use crate::resource::ResourceMap;
-use crate::scene::{GameObject, Scene};
+use crate::scene::{GameObject, ReflectSystem, Scene, System, SystemStage};
 use crate::{
     math,
     reflect::{Reflect, ReflectDefault},
     utils::{ReflectTypeUuidDynamic, TypeUuid},
 };
+use legion::systems::Builder;
+use legion::{IntoQuery, SystemBuilder};
 use nalgebra::Unit;
 use nalgebra_glm::{Mat4, Quat, Vec3, Vec4};
 use petgraph::prelude::{EdgeIndex, EdgeRef, NodeIndex};
@@ -28,7 +31,7 @@ use std::cmp::Ordering;
 use std::collections::HashMap;
 use uuid::Uuid;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 #[repr(C)]
 struct AnimatorTransition {
     transition: EdgeIndex,
@@ -117,15 +120,18 @@ pub struct ComponentAnimator {
     pub animation_graph: AssetRef<AnimationGraph>,
     #[reflect_skip]
     #[serde(skip)]
+    #[save_state]
     current_state: Option<NodeIndex>,
     #[reflect_skip]
     #[serde(skip)]
+    #[save_state]
     current_transition: Option<AnimatorTransition>,
     #[reflect_skip]
     #[serde(skip)]
     current_pose: AnimatorPose,
     #[reflect_skip]
     #[serde(skip)]
+    #[save_state]
     parameters: HashMap<Uuid, AnimationParameterValue>,
 }
 
@@ -152,8 +158,7 @@ impl Component for ComponentAnimator {
         resources: &mut ResourceMap,
         _input: &Input,
     ) {
-        self.init(assets);
-        self.step_fsm(assets);
+        // The state machine was stepped by the animator system, the pose needs the scene
         self.apply_animation_pose(assets, scene, game_object);
         self.update_time(resources.time());
     }
@@ -175,6 +180,40 @@ impl Component for ComponentAnimator {
     }
 }
 
+/// Steps the state machines of the enabled animators of active game objects in parallel,
+/// before they update and pose their skeletons.
+#[derive(Default, TypeUuid, Reflect)]
+#[uuid = "2b9e64d0-7c1f-4a85-9e37-d58a0c6f1b42"]
+#[reflect(Default, TypeUuidDynamic, System)]
+pub struct AnimatorSystem;
+
+impl System for AnimatorSystem {
+    fn stage(&self) -> SystemStage {
+        SystemStage::Update
+    }
+
+    fn add_to_schedule(&self, schedule: &mut Builder) {
+        schedule.add_system(
+            SystemBuilder::new("animator")
+                .read_resource::<ReadOnlyAssetContext>()
+                .with_query(<(&ComponentID, &mut ComponentAnimator)>::query())
+                .build(|_, world, assets, query| {
+                    query.par_for_each_mut(world, |(c_id, animator)| {
+                        if !c_id.is_active_in_hierarchy()
+                            || c_id
+                                .disabled_components
+                                .contains(&ComponentAnimator::type_uuid())
+                        {
+                            return;
+                        }
+                        animator.init(assets);
+                        animator.step_fsm(assets);
+                    });
+                }),
+        );
+    }
+}
+
 impl ComponentAnimator {
     pub fn parameters(&self) -> &HashMap<Uuid, AnimationParameterValue> {
         &self.parameters
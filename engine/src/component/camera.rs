@@ -23,6 +23,9 @@ pub struct ComponentCamera {
     pub far_plane: f32,
     pub clear_color: Color32,
     pub enabled: bool,
+    /// Mask of the layers of the game objects this camera renders.
+    #[reflect_attr(layers)]
+    pub culling_mask: u32,
 }
 
 impl Default for ComponentCamera {
@@ -33,6 +36,7 @@ impl Default for ComponentCamera {
             far_plane: 100.0,
             clear_color: Color32::BLACK,
             enabled: true,
+            culling_mask: u32::MAX,
         }
     }
 }
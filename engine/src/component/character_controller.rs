@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+use crate as engine;
+use crate::component::{Component, ReflectComponent};
+use crate::reflect::{Reflect, ReflectDefault};
+use crate::utils::{ReflectTypeUuidDynamic, TypeUuid};
+
+/// Moves a game object through the world without simulating it, sliding along walls,
+/// climbing steps and following the ground. The character's shape is taken from the
+/// collider of the same game object, and it is moved with [`Scene::move_and_slide`].
+///
+/// [`Scene::move_and_slide`]: crate::scene::Scene::move_and_slide
+#[derive(TypeUuid, Serialize, Deserialize, Component, Reflect)]
+#[uuid = "b23a57d8-4368-4e96-9756-e8e23a74a733"]
+#[reflect(Default, TypeUuidDynamic, Component)]
+#[reflect_attr(name = "Character Controller")]
+#[serde(default)]
+#[repr(C)]
+pub struct ComponentCharacterController {
+    /// Gap kept between the character and the obstacles around it.
+    #[reflect_attr(min = 0.0, speed = 0.001)]
+    pub offset: f32,
+    /// Slide along obstacles instead of stopping when hitting them.
+    pub slide: bool,
+    /// Steepest slope the character can walk up.
+    #[reflect_attr(angle)]
+    pub max_slope_climb_angle: f32,
+    /// Slopes steeper than this make the character slide down.
+    #[reflect_attr(angle)]
+    pub min_slope_slide_angle: f32,
+    /// Highest step the character climbs automatically, 0 to disable stepping.
+    #[reflect_attr(min = 0.0, speed = 0.01)]
+    pub step_height: f32,
+    /// Free space needed on top of a step to climb it.
+    #[reflect_attr(min = 0.0, speed = 0.01)]
+    pub step_min_width: f32,
+    /// Maximum distance the character is pulled down to stay on the ground, 0 to disable.
+    #[reflect_attr(min = 0.0, speed = 0.01)]
+    pub snap_to_ground: f32,
+    #[serde(skip)]
+    #[reflect_skip]
+    #[save_state]
+    pub(crate) grounded: bool,
+    #[serde(skip)]
+    #[reflect_skip]
+    #[save_state]
+    pub(crate) sliding_down_slope: bool,
+}
+
+impl Default for ComponentCharacterController {
+    fn default() -> Self {
+        Self {
+            offset: 0.01,
+            slide: true,
+            max_slope_climb_angle: 45f32.to_radians(),
+            min_slope_slide_angle: 30f32.to_radians(),
+            step_height: 0.3,
+            step_min_width: 0.2,
+            snap_to_ground: 0.2,
+            grounded: false,
+            sliding_down_slope: false,
+        }
+    }
+}
+
+impl ComponentCharacterController {
+    /// Whether the character was touching the ground at the end of its last move.
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+
+    /// Whether the character was sliding down a slope steeper than `min_slope_slide_angle`
+    /// during its last move.
+    pub fn is_sliding_down_slope(&self) -> bool {
+        self.sliding_down_slope
+    }
+}
+
+impl Component for ComponentCharacterController {}
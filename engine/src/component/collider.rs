@@ -3,6 +3,8 @@ use nalgebra_glm::Vec3;
 use serde::{Deserialize, Serialize};
 
 use crate as engine;
+use crate::assets::mesh::Mesh;
+use crate::assets::AssetRef;
 use crate::component::{Component, ReflectComponent};
 use crate::reflect::{Reflect, ReflectDefault};
 use crate::utils::{ReflectTypeUuidDynamic, TypeUuid};
@@ -14,7 +16,7 @@ pub enum Orientation {
     Z,
 }
 
-#[derive(Clone, Copy, PartialEq, TypeUuid, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, TypeUuid, Serialize, Deserialize)]
 #[uuid = "9b1a794d-df37-4abe-a7a0-c4423bb9edd3"]
 pub enum ColliderShape {
     Sphere {
@@ -32,11 +34,34 @@ pub enum ColliderShape {
         height: f32,
         radius: f32,
     },
+    /// Convex hull of the mesh's vertices.
+    ConvexHull(AssetRef<Mesh>),
+    /// Triangle mesh built from the mesh's vertices and indices. Only suited to static geometry.
+    TriMesh(AssetRef<Mesh>),
+    /// Grid of `rows` by `columns` heights sampled from the top of the mesh,
+    /// over the mesh's extents centered on the collider's origin.
+    Heightfield {
+        mesh: AssetRef<Mesh>,
+        rows: u32,
+        columns: u32,
+    },
+}
+
+impl ColliderShape {
+    /// The mesh this shape is built from, if any.
+    pub fn mesh(&self) -> Option<&AssetRef<Mesh>> {
+        match self {
+            ColliderShape::ConvexHull(mesh)
+            | ColliderShape::TriMesh(mesh)
+            | ColliderShape::Heightfield { mesh, .. } => Some(mesh),
+            _ => None,
+        }
+    }
 }
 
 impl_reflect_value!(ColliderShape());
 
-#[derive(TypeUuid, Serialize, Deserialize, Component, Reflect)]
+#[derive(Clone, TypeUuid, Serialize, Deserialize, Component, Reflect)]
 #[uuid = "53a682cb-a207-4c4c-8795-63f38351c7ef"]
 #[reflect(Default, TypeUuidDynamic, Component)]
 #[reflect_attr(name = "Collider")]
@@ -47,6 +72,14 @@ pub struct ComponentCollider {
     pub shape: ColliderShape,
     pub friction: f32,
     pub density: f32,
+    /// Sensors detect overlaps and raise trigger events instead of colliding.
+    pub sensor: bool,
+    /// Bit mask of the layers this collider belongs to.
+    #[reflect_attr(layers)]
+    pub collision_layers: u32,
+    /// Bit mask of the layers this collider collides with.
+    #[reflect_attr(layers)]
+    pub collision_mask: u32,
     #[serde(skip)]
     #[reflect_skip]
     pub dirty: bool,
@@ -59,6 +92,9 @@ impl Default for ComponentCollider {
             shape: ColliderShape::Sphere { radius: 1.0 },
             friction: 0.5,
             density: 100.0,
+            sensor: false,
+            collision_layers: u32::MAX,
+            collision_mask: u32::MAX,
             dirty: true,
         }
     }
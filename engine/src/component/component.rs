@@ -1,5 +1,6 @@
 use legion::storage::ComponentTypeId;
 use legion::world::{Entry, EntryRef};
+use legion::{Entity, World};
 
 use engine_derive::reflect_trait;
 pub use engine_derive::Component;
@@ -7,22 +8,40 @@ pub use engine_derive::Component;
 use crate as engine;
 use crate::context::ReadOnlyAssetContext;
 use crate::input::Input;
+use crate::physics::Collision;
 use crate::reflect::Reflect;
 use crate::render::Gizmos;
 use crate::resource::ResourceMap;
-use crate::scene::{GameObject, Scene};
+use crate::scene::{BinaryError, GameObject, Scene};
 use crate::utils::TypeUuidDynamic;
 
 pub trait ComponentInstance: Reflect {
     fn component_type_id(&self) -> ComponentTypeId;
+    /// Lists the entities with this component, one archetype after the other,
+    /// in the order legion stores them.
+    fn query_entities(&self, world: &World) -> Vec<Entity>;
     fn get_instance<'a>(&self, entry: &'a EntryRef) -> Option<&'a dyn Component>;
     fn get_instance_mut<'a>(&self, entry: &'a mut Entry) -> Option<&'a mut dyn Component>;
+    /// Exchanges the instance of the entity with another instance of the same type, returns
+    /// false if the entity doesn't have the component or the types differ.
+    fn swap_instance(&self, entry: &mut Entry, instance: &mut dyn Component) -> bool;
     fn bind_instance(&self, entry: &mut Entry, instance: Box<dyn Reflect>) -> bool;
     fn remove_instance(&self, entry: &mut Entry);
     fn serialize(&self) -> Option<serde_json::Value>;
-    fn deserialize(&self, value: serde_json::Value) -> Option<Box<dyn Reflect>>;
+    fn deserialize(&self, value: serde_json::Value) -> Result<Box<dyn Reflect>, serde_json::Error>;
+    /// Encodes the component for binary scenes, see [`SceneFormat`](crate::scene::SceneFormat).
+    /// Returns `None` for components that have to be saved as JSON.
+    fn serialize_binary(&self) -> Option<Vec<u8>>;
+    fn deserialize_binary(&self, data: &[u8]) -> Result<Box<dyn Reflect>, BinaryError>;
+    /// Runtime state of the component kept in save games, made of its fields marked
+    /// `#[save_state]`. Those are usually `#[serde(skip)]` so scenes don't save them.
+    fn save_state(&self) -> Option<serde_json::Value> {
+        None
+    }
+    /// Restores the state saved by `save_state`.
+    fn load_state(&mut self, _state: &serde_json::Value) {}
     fn deserialize_in_place(&mut self, value: serde_json::Value) -> bool {
-        if let Some(value) = self.deserialize(value) {
+        if let Ok(value) = self.deserialize(value) {
             self.assign(value)
         } else {
             false
@@ -40,7 +59,52 @@ pub struct ComponentEventContext<'a> {
 #[reflect_trait]
 pub trait Component: TypeUuidDynamic + ComponentInstance {
     fn reset(&mut self, ctx: ComponentEventContext) {}
+    /// Called once, before the first update following the component being added to a scene
+    /// (or the scene being loaded), as soon as the component is enabled.
+    fn start(&mut self, ctx: ComponentEventContext) {}
+    /// Called when the component gets enabled, and right before `start`.
+    fn on_enable(&mut self, ctx: ComponentEventContext) {}
+    /// Called when a started component gets disabled.
+    fn on_disable(&mut self, ctx: ComponentEventContext) {}
+    /// Called once per physics step, before the step, on components with the `fixed_update`
+    /// reflect attribute. Several steps, or none, can run in a single frame.
+    fn fixed_update(
+        &mut self,
+        ctx: ComponentEventContext,
+        resources: &mut ResourceMap,
+        input: &Input,
+    ) {
+    }
+    /// Called once per frame, after physics, on components with the `update` reflect attribute.
+    /// Component types update in the order of their `priority` reflect attribute, lowest first.
     fn update(&mut self, ctx: ComponentEventContext, resources: &mut ResourceMap, input: &Input) {}
+    /// Called once per frame, after every component was updated, on components with the
+    /// `late_update` reflect attribute.
+    fn late_update(
+        &mut self,
+        ctx: ComponentEventContext,
+        resources: &mut ResourceMap,
+        input: &Input,
+    ) {
+    }
     fn destroy(&mut self, ctx: ComponentEventContext) {}
+    fn on_collision_enter(&mut self, ctx: ComponentEventContext, collision: &Collision) {}
+    fn on_collision_stay(&mut self, ctx: ComponentEventContext, collision: &Collision) {}
+    fn on_collision_exit(&mut self, ctx: ComponentEventContext, collision: &Collision) {}
+    fn on_trigger_enter(&mut self, ctx: ComponentEventContext, other: GameObject) {}
+    fn on_trigger_exit(&mut self, ctx: ComponentEventContext, other: GameObject) {}
     fn draw_gizmos(&self, scene: &Scene, game_object: GameObject, gizmos: &mut Gizmos) {}
+    /// Upgrades data saved with an older version of the component, given by the `version`
+    /// reflect attribute (0 when missing), before it gets deserialized. Called once per version
+    /// step, with the version the data is at:
+    /// ```ignore
+    /// fn migrate(&self, version: u32, data: &mut serde_json::Value) {
+    ///     match version {
+    ///         0 => scene::rename_field(data, "speed", "max_speed"),
+    ///         1 => scene::set_field_default(data, "jump_height", 1.5),
+    ///         _ => {}
+    ///     }
+    /// }
+    /// ```
+    fn migrate(&self, version: u32, data: &mut serde_json::Value) {}
 }
@@ -19,6 +19,9 @@ pub struct ComponentDirectionalLight {
     pub color: Color32,
     #[reflect_attr(min = 0.0, max = 1.0, speed = 0.01)]
     pub intensity: f32,
+    /// Mask of the layers of the game objects this light affects.
+    #[reflect_attr(layers)]
+    pub layer_mask: u32,
 }
 
 impl Default for ComponentDirectionalLight {
@@ -27,6 +30,7 @@ impl Default for ComponentDirectionalLight {
             active: true,
             color: Color32::WHITE,
             intensity: 0.75,
+            layer_mask: u32::MAX,
         }
     }
 }
@@ -1,8 +1,10 @@
 use crate as engine;
 use crate::component::{Component, ReflectComponent};
 use crate::reflect::{Reflect, ReflectDefault};
+use crate::scene::DEFAULT_LAYERS;
 use crate::utils::{ReflectTypeUuidDynamic, TypeUuid};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use uuid::Uuid;
 
 #[derive(TypeUuid, Serialize, Deserialize, Component, Reflect)]
@@ -16,6 +18,23 @@ pub struct ComponentID {
     pub id: Uuid,
     pub name: String,
     pub visible: bool,
+    /// Inactive game objects and their descendants don't update, simulate or render.
+    /// Changed through [`Scene::set_active`](crate::scene::Scene::set_active).
+    #[reflect_skip]
+    pub active: bool,
+    /// Whether this game object and all of its ancestors are active, kept up to date by the scene.
+    #[serde(skip)]
+    #[reflect_skip]
+    pub(crate) active_in_hierarchy: bool,
+    /// Mask of the layers this game object is on, see [`LayerNames`](crate::scene::LayerNames).
+    #[reflect_attr(layers)]
+    pub layers: u32,
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+    /// Type UUIDs of the components of this game object that are disabled.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    #[reflect_skip]
+    pub disabled_components: BTreeSet<Uuid>,
 }
 
 impl Default for ComponentID {
@@ -24,8 +43,27 @@ impl Default for ComponentID {
             id: Uuid::new_v4(),
             name: String::from("Game Object"),
             visible: true,
+            active: true,
+            active_in_hierarchy: true,
+            layers: DEFAULT_LAYERS,
+            tags: Default::default(),
+            disabled_components: Default::default(),
         }
     }
 }
 
+impl ComponentID {
+    /// Whether this game object and all of its ancestors are active, for systems going
+    /// through game objects without the scene.
+    pub fn is_active_in_hierarchy(&self) -> bool {
+        self.active_in_hierarchy
+    }
+
+    /// Whether a component of this game object wasn't disabled, see
+    /// [`Scene::set_component_enabled`](crate::scene::Scene::set_component_enabled).
+    pub fn is_component_enabled(&self, component_id: Uuid) -> bool {
+        !self.disabled_components.contains(&component_id)
+    }
+}
+
 impl Component for ComponentID {}
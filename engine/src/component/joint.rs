@@ -0,0 +1,424 @@
+use nalgebra::{Unit, Vector3};
+use nalgebra_glm::{Vec3, Vec4};
+use rapier3d::dynamics::{
+    FixedJointBuilder, GenericJoint, PrismaticJointBuilder, RevoluteJointBuilder, RopeJointBuilder,
+    SphericalJointBuilder, SpringJointBuilder,
+};
+use serde::{Deserialize, Serialize};
+
+use crate as engine;
+use crate::component::{Component, ReflectComponent};
+use crate::reflect::{Reflect, ReflectDefault};
+use crate::render::Gizmos;
+use crate::scene::{GameObject, GameObjectRef, Scene};
+use crate::utils::{ReflectTypeUuidDynamic, TypeUuid};
+
+/// Common interface of the joint components, used to sync them with the physics simulation.
+///
+/// A joint connects the rigid body of its game object (or of its nearest ancestor) to the
+/// rigid body of the connected game object. Anchors and axes are expressed in the local space
+/// of the game object they belong to.
+pub trait Joint {
+    fn connected_body(&self) -> GameObjectRef;
+    fn anchor(&self) -> Vec3;
+    fn connected_anchor(&self) -> Vec3;
+    fn build(&self) -> GenericJoint;
+
+    fn draw_joint_gizmos(&self, scene: &Scene, game_object: GameObject, gizmos: &mut Gizmos) {
+        let anchor = scene
+            .get_world_transform(game_object)
+            .transform_position(&self.anchor());
+        gizmos.set_color(&Vec4::new(1.0, 0.6, 0.0, 1.0));
+        gizmos.point(&anchor);
+        if let Some(connected) = self.connected_body().game_object(scene) {
+            let connected_anchor = scene
+                .get_world_transform(connected)
+                .transform_position(&self.connected_anchor());
+            gizmos.point(&connected_anchor);
+            gizmos.line(&anchor, &connected_anchor);
+        }
+    }
+}
+
+fn joint_axis(axis: Vec3) -> Unit<Vector3<f32>> {
+    Unit::try_new(axis, f32::EPSILON).unwrap_or(Vector3::y_axis())
+}
+
+#[derive(TypeUuid, Serialize, Deserialize, Component, Reflect)]
+#[uuid = "c5268c7a-a28e-40ea-ad4f-bdfa540cb206"]
+#[reflect(Default, TypeUuidDynamic, Component)]
+#[reflect_attr(name = "Fixed Joint")]
+#[serde(default)]
+#[repr(C)]
+pub struct ComponentFixedJoint {
+    pub connected_body: GameObjectRef,
+    pub anchor: Vec3,
+    pub connected_anchor: Vec3,
+}
+
+impl Default for ComponentFixedJoint {
+    fn default() -> Self {
+        Self {
+            connected_body: Default::default(),
+            anchor: Vec3::zeros(),
+            connected_anchor: Vec3::zeros(),
+        }
+    }
+}
+
+impl Joint for ComponentFixedJoint {
+    fn connected_body(&self) -> GameObjectRef {
+        self.connected_body
+    }
+
+    fn anchor(&self) -> Vec3 {
+        self.anchor
+    }
+
+    fn connected_anchor(&self) -> Vec3 {
+        self.connected_anchor
+    }
+
+    fn build(&self) -> GenericJoint {
+        FixedJointBuilder::new()
+            .local_anchor1(self.anchor.into())
+            .local_anchor2(self.connected_anchor.into())
+            .build()
+            .into()
+    }
+}
+
+impl Component for ComponentFixedJoint {
+    fn draw_gizmos(&self, scene: &Scene, game_object: GameObject, gizmos: &mut Gizmos) {
+        self.draw_joint_gizmos(scene, game_object, gizmos);
+    }
+}
+
+#[derive(TypeUuid, Serialize, Deserialize, Component, Reflect)]
+#[uuid = "675c0a2f-01d5-4848-b343-8634114218e1"]
+#[reflect(Default, TypeUuidDynamic, Component)]
+#[reflect_attr(name = "Revolute Joint")]
+#[serde(default)]
+#[repr(C)]
+pub struct ComponentRevoluteJoint {
+    pub connected_body: GameObjectRef,
+    pub anchor: Vec3,
+    pub connected_anchor: Vec3,
+    pub axis: Vec3,
+    pub limits_enabled: bool,
+    #[reflect_attr(angle)]
+    pub min_angle: f32,
+    #[reflect_attr(angle)]
+    pub max_angle: f32,
+    pub motor_enabled: bool,
+    pub motor_target_velocity: f32,
+    #[reflect_attr(min = 0.0)]
+    pub motor_damping: f32,
+    #[reflect_attr(min = 0.0)]
+    pub motor_max_force: f32,
+}
+
+impl Default for ComponentRevoluteJoint {
+    fn default() -> Self {
+        Self {
+            connected_body: Default::default(),
+            anchor: Vec3::zeros(),
+            connected_anchor: Vec3::zeros(),
+            axis: Vec3::y(),
+            limits_enabled: false,
+            min_angle: -std::f32::consts::FRAC_PI_2,
+            max_angle: std::f32::consts::FRAC_PI_2,
+            motor_enabled: false,
+            motor_target_velocity: 0.0,
+            motor_damping: 1.0,
+            motor_max_force: 100.0,
+        }
+    }
+}
+
+impl Joint for ComponentRevoluteJoint {
+    fn connected_body(&self) -> GameObjectRef {
+        self.connected_body
+    }
+
+    fn anchor(&self) -> Vec3 {
+        self.anchor
+    }
+
+    fn connected_anchor(&self) -> Vec3 {
+        self.connected_anchor
+    }
+
+    fn build(&self) -> GenericJoint {
+        let mut builder = RevoluteJointBuilder::new(joint_axis(self.axis))
+            .local_anchor1(self.anchor.into())
+            .local_anchor2(self.connected_anchor.into());
+        if self.limits_enabled {
+            builder = builder.limits([self.min_angle, self.max_angle]);
+        }
+        if self.motor_enabled {
+            builder = builder
+                .motor_velocity(self.motor_target_velocity, self.motor_damping)
+                .motor_max_force(self.motor_max_force);
+        }
+        builder.build().into()
+    }
+}
+
+impl Component for ComponentRevoluteJoint {
+    fn draw_gizmos(&self, scene: &Scene, game_object: GameObject, gizmos: &mut Gizmos) {
+        self.draw_joint_gizmos(scene, game_object, gizmos);
+        let transform = scene.get_world_transform(game_object);
+        let anchor = transform.transform_position(&self.anchor);
+        let axis = transform.transform_direction(&joint_axis(self.axis).into_inner());
+        gizmos.line(&anchor, &(anchor + axis));
+    }
+}
+
+#[derive(TypeUuid, Serialize, Deserialize, Component, Reflect)]
+#[uuid = "42f6be3f-bbc3-4e41-9502-1d37bbaef4cc"]
+#[reflect(Default, TypeUuidDynamic, Component)]
+#[reflect_attr(name = "Prismatic Joint")]
+#[serde(default)]
+#[repr(C)]
+pub struct ComponentPrismaticJoint {
+    pub connected_body: GameObjectRef,
+    pub anchor: Vec3,
+    pub connected_anchor: Vec3,
+    pub axis: Vec3,
+    pub limits_enabled: bool,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    pub motor_enabled: bool,
+    pub motor_target_velocity: f32,
+    #[reflect_attr(min = 0.0)]
+    pub motor_damping: f32,
+    #[reflect_attr(min = 0.0)]
+    pub motor_max_force: f32,
+}
+
+impl Default for ComponentPrismaticJoint {
+    fn default() -> Self {
+        Self {
+            connected_body: Default::default(),
+            anchor: Vec3::zeros(),
+            connected_anchor: Vec3::zeros(),
+            axis: Vec3::x(),
+            limits_enabled: false,
+            min_distance: -1.0,
+            max_distance: 1.0,
+            motor_enabled: false,
+            motor_target_velocity: 0.0,
+            motor_damping: 1.0,
+            motor_max_force: 100.0,
+        }
+    }
+}
+
+impl Joint for ComponentPrismaticJoint {
+    fn connected_body(&self) -> GameObjectRef {
+        self.connected_body
+    }
+
+    fn anchor(&self) -> Vec3 {
+        self.anchor
+    }
+
+    fn connected_anchor(&self) -> Vec3 {
+        self.connected_anchor
+    }
+
+    fn build(&self) -> GenericJoint {
+        let mut builder = PrismaticJointBuilder::new(joint_axis(self.axis))
+            .local_anchor1(self.anchor.into())
+            .local_anchor2(self.connected_anchor.into());
+        if self.limits_enabled {
+            builder = builder.limits([self.min_distance, self.max_distance]);
+        }
+        if self.motor_enabled {
+            builder = builder
+                .motor_velocity(self.motor_target_velocity, self.motor_damping)
+                .motor_max_force(self.motor_max_force);
+        }
+        builder.build().into()
+    }
+}
+
+impl Component for ComponentPrismaticJoint {
+    fn draw_gizmos(&self, scene: &Scene, game_object: GameObject, gizmos: &mut Gizmos) {
+        self.draw_joint_gizmos(scene, game_object, gizmos);
+        let transform = scene.get_world_transform(game_object);
+        let anchor = transform.transform_position(&self.anchor);
+        let axis = transform.transform_direction(&joint_axis(self.axis).into_inner());
+        if self.limits_enabled {
+            gizmos.line(
+                &(anchor + axis * self.min_distance),
+                &(anchor + axis * self.max_distance),
+            );
+        } else {
+            gizmos.line(&(anchor - axis), &(anchor + axis));
+        }
+    }
+}
+
+#[derive(TypeUuid, Serialize, Deserialize, Component, Reflect)]
+#[uuid = "f7de1925-536a-4e33-9fb9-4613a12eac8f"]
+#[reflect(Default, TypeUuidDynamic, Component)]
+#[reflect_attr(name = "Spherical Joint")]
+#[serde(default)]
+#[repr(C)]
+pub struct ComponentSphericalJoint {
+    pub connected_body: GameObjectRef,
+    pub anchor: Vec3,
+    pub connected_anchor: Vec3,
+}
+
+impl Default for ComponentSphericalJoint {
+    fn default() -> Self {
+        Self {
+            connected_body: Default::default(),
+            anchor: Vec3::zeros(),
+            connected_anchor: Vec3::zeros(),
+        }
+    }
+}
+
+impl Joint for ComponentSphericalJoint {
+    fn connected_body(&self) -> GameObjectRef {
+        self.connected_body
+    }
+
+    fn anchor(&self) -> Vec3 {
+        self.anchor
+    }
+
+    fn connected_anchor(&self) -> Vec3 {
+        self.connected_anchor
+    }
+
+    fn build(&self) -> GenericJoint {
+        SphericalJointBuilder::new()
+            .local_anchor1(self.anchor.into())
+            .local_anchor2(self.connected_anchor.into())
+            .build()
+            .into()
+    }
+}
+
+impl Component for ComponentSphericalJoint {
+    fn draw_gizmos(&self, scene: &Scene, game_object: GameObject, gizmos: &mut Gizmos) {
+        self.draw_joint_gizmos(scene, game_object, gizmos);
+    }
+}
+
+#[derive(TypeUuid, Serialize, Deserialize, Component, Reflect)]
+#[uuid = "a42a5bd4-dc6e-4cf3-904b-630992d04954"]
+#[reflect(Default, TypeUuidDynamic, Component)]
+#[reflect_attr(name = "Rope Joint")]
+#[serde(default)]
+#[repr(C)]
+pub struct ComponentRopeJoint {
+    pub connected_body: GameObjectRef,
+    pub anchor: Vec3,
+    pub connected_anchor: Vec3,
+    #[reflect_attr(min = 0.0, speed = 0.1)]
+    pub max_distance: f32,
+}
+
+impl Default for ComponentRopeJoint {
+    fn default() -> Self {
+        Self {
+            connected_body: Default::default(),
+            anchor: Vec3::zeros(),
+            connected_anchor: Vec3::zeros(),
+            max_distance: 1.0,
+        }
+    }
+}
+
+impl Joint for ComponentRopeJoint {
+    fn connected_body(&self) -> GameObjectRef {
+        self.connected_body
+    }
+
+    fn anchor(&self) -> Vec3 {
+        self.anchor
+    }
+
+    fn connected_anchor(&self) -> Vec3 {
+        self.connected_anchor
+    }
+
+    fn build(&self) -> GenericJoint {
+        RopeJointBuilder::new(self.max_distance)
+            .local_anchor1(self.anchor.into())
+            .local_anchor2(self.connected_anchor.into())
+            .build()
+            .into()
+    }
+}
+
+impl Component for ComponentRopeJoint {
+    fn draw_gizmos(&self, scene: &Scene, game_object: GameObject, gizmos: &mut Gizmos) {
+        self.draw_joint_gizmos(scene, game_object, gizmos);
+    }
+}
+
+#[derive(TypeUuid, Serialize, Deserialize, Component, Reflect)]
+#[uuid = "89d35e16-37e1-435a-9a63-eab34a19865b"]
+#[reflect(Default, TypeUuidDynamic, Component)]
+#[reflect_attr(name = "Spring Joint")]
+#[serde(default)]
+#[repr(C)]
+pub struct ComponentSpringJoint {
+    pub connected_body: GameObjectRef,
+    pub anchor: Vec3,
+    pub connected_anchor: Vec3,
+    #[reflect_attr(min = 0.0, speed = 0.1)]
+    pub rest_length: f32,
+    #[reflect_attr(min = 0.0)]
+    pub stiffness: f32,
+    #[reflect_attr(min = 0.0)]
+    pub damping: f32,
+}
+
+impl Default for ComponentSpringJoint {
+    fn default() -> Self {
+        Self {
+            connected_body: Default::default(),
+            anchor: Vec3::zeros(),
+            connected_anchor: Vec3::zeros(),
+            rest_length: 1.0,
+            stiffness: 100.0,
+            damping: 1.0,
+        }
+    }
+}
+
+impl Joint for ComponentSpringJoint {
+    fn connected_body(&self) -> GameObjectRef {
+        self.connected_body
+    }
+
+    fn anchor(&self) -> Vec3 {
+        self.anchor
+    }
+
+    fn connected_anchor(&self) -> Vec3 {
+        self.connected_anchor
+    }
+
+    fn build(&self) -> GenericJoint {
+        SpringJointBuilder::new(self.rest_length, self.stiffness, self.damping)
+            .local_anchor1(self.anchor.into())
+            .local_anchor2(self.connected_anchor.into())
+            .build()
+            .into()
+    }
+}
+
+impl Component for ComponentSpringJoint {
+    fn draw_gizmos(&self, scene: &Scene, game_object: GameObject, gizmos: &mut Gizmos) {
+        self.draw_joint_gizmos(scene, game_object, gizmos);
+    }
+}
@@ -1,12 +1,15 @@
 pub use animator::*;
 pub use bone::*;
 pub use camera::*;
+pub use character_controller::*;
 pub use collider::*;
 pub use component::*;
 pub use directional_light::*;
 pub use id::*;
+pub use joint::*;
 pub use mesh::*;
 pub use point_light::*;
+pub use prefab_instance::*;
 pub use rigid_body::*;
 pub use skinned_mesh::*;
 pub use sky_light::*;
@@ -15,12 +18,15 @@ pub use transform::*;
 mod animator;
 mod bone;
 mod camera;
+mod character_controller;
 mod collider;
 mod component;
 mod directional_light;
 mod id;
+mod joint;
 mod mesh;
 mod point_light;
+mod prefab_instance;
 mod rigid_body;
 mod skinned_mesh;
 mod sky_light;
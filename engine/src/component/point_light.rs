@@ -19,6 +19,9 @@ pub struct ComponentPointLight {
     #[reflect_attr(min = 0.0, speed = 0.1)]
     pub radius: f32,
     pub color: Color32,
+    /// Mask of the layers of the game objects this light affects.
+    #[reflect_attr(layers)]
+    pub layer_mask: u32,
 }
 
 impl Default for ComponentPointLight {
@@ -27,6 +30,7 @@ impl Default for ComponentPointLight {
             active: true,
             radius: 10.0,
             color: Color32::WHITE,
+            layer_mask: u32::MAX,
         }
     }
 }
@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use crate as engine;
+use crate::assets::AssetRef;
+use crate::component::{Component, ReflectComponent};
+use crate::reflect::{Reflect, ReflectDefault};
+use crate::scene::{Prefab, PrefabExpansion, PrefabOverrides};
+use crate::utils::{ReflectTypeUuidDynamic, TypeUuid};
+use serde::{Deserialize, Serialize};
+
+/// Root of an instance of a prefab, see [`Scene::instantiate_prefab`].
+///
+/// Scenes save instances as a link to their prefab along with the fields that differ from it,
+/// and rebuild them from the prefab when loaded or when the prefab changes.
+///
+/// [`Scene::instantiate_prefab`]: crate::scene::Scene::instantiate_prefab
+#[derive(Default, TypeUuid, Serialize, Deserialize, Component, Reflect)]
+#[uuid = "4d1b7e3a-92c5-4f08-b6ad-1e5c7a9f3d20"]
+#[reflect(Default, TypeUuidDynamic, Component)]
+#[reflect_attr(name = "Prefab Instance")]
+#[serde(default)]
+pub struct ComponentPrefabInstance {
+    pub prefab: AssetRef<Prefab>,
+    /// Overrides the instance was built with, kept up to date only when the scene is saved,
+    /// see [`Scene::prefab_overrides`](crate::scene::Scene::prefab_overrides).
+    #[reflect_skip]
+    pub overrides: PrefabOverrides,
+    #[serde(skip)]
+    #[reflect_skip]
+    pub(crate) expansion: Option<Arc<PrefabExpansion>>,
+}
+
+impl Component for ComponentPrefabInstance {}
@@ -5,7 +5,7 @@ use crate::utils::{ReflectTypeUuidDynamic, TypeUuid};
 use rapier3d::dynamics::RigidBodyType;
 use serde::{Deserialize, Serialize};
 
-#[derive(TypeUuid, Serialize, Deserialize, Component, Reflect)]
+#[derive(Clone, TypeUuid, Serialize, Deserialize, Component, Reflect)]
 #[uuid = "8cac1bae-e3c3-4ee6-b672-7689e9c10f7e"]
 #[reflect(Default, TypeUuidDynamic, Component)]
 #[reflect_attr(name = "Rigid Body")]
@@ -19,6 +19,9 @@ pub struct ComponentRigidBody {
     #[reflect_attr(min = 0.0, speed = 0.01)]
     pub gravity_scale: f32,
     pub can_sleep: bool,
+    /// Continuous collision detection, keeps fast bodies from tunneling through thin colliders.
+    #[reflect_attr(name = "CCD Enabled")]
+    pub ccd_enabled: bool,
     #[serde(skip)]
     #[reflect_skip]
     pub dirty: bool,
@@ -32,6 +35,7 @@ impl Default for ComponentRigidBody {
             mass: 100.0,
             gravity_scale: 1.0,
             can_sleep: true,
+            ccd_enabled: false,
             dirty: true,
         }
     }
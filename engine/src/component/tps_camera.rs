@@ -11,7 +11,7 @@ use serde::{Deserialize, Serialize};
 
 #[derive(TypeUuid, Serialize, Deserialize, Component, Reflect)]
 #[reflect(Default, TypeUuidDynamic, Component)]
-#[reflect_attr(name = "Third Person Camera", update)]
+#[reflect_attr(name = "Third Person Camera", late_update)]
 #[repr(C)]
 pub struct ComponentThirdPersonCamera {
     pub target: GameObjectRef,
@@ -23,6 +23,7 @@ pub struct ComponentThirdPersonCamera {
     pub distance: f32,
     #[serde(skip)]
     #[reflect_skip]
+    #[save_state]
     rotation: Vec2,
 }
 
@@ -39,7 +40,7 @@ impl Default for ComponentThirdPersonCamera {
 }
 
 impl Component for ComponentThirdPersonCamera {
-    fn update(
+    fn late_update(
         &mut self,
         ComponentEventContext {
             scene, game_object, ..
@@ -18,12 +18,42 @@ pub struct AssetContext {
     pub component_registry: Ref<ComponentRegistry>,
 }
 
+/// The engine assets shipped along with the editor and games, in the `assets` directory
+/// they are launched from.
+pub fn default_engine_assets_path() -> Result<PathBuf, BoxedError> {
+    Ok(std::env::current_dir().map_err(Box::new)?.join("assets"))
+}
+
 impl AssetContext {
     pub fn new(
         cc: &eframe::CreationContext,
         project_path: impl Into<PathBuf>,
+        engine_assets_path: impl Into<PathBuf>,
+    ) -> Result<Self, BoxedError> {
+        Self::with_render_context(
+            RenderContext::from_eframe(cc),
+            project_path,
+            engine_assets_path,
+        )
+    }
+
+    /// Creates an asset context without a window, for CLI tools, tests and dedicated servers.
+    /// Uses a GPU device of its own if one can be created, and no GPU at all otherwise.
+    pub fn headless(
+        project_path: impl Into<PathBuf>,
+        engine_assets_path: impl Into<PathBuf>,
+    ) -> Result<Self, BoxedError> {
+        let render_context =
+            RenderContext::headless().unwrap_or_else(|_| RenderContext::without_gpu());
+        Self::with_render_context(render_context, project_path, engine_assets_path)
+    }
+
+    pub fn with_render_context(
+        render_context: RenderContext,
+        project_path: impl Into<PathBuf>,
+        engine_assets_path: impl Into<PathBuf>,
     ) -> Result<Self, BoxedError> {
-        let render_context = Arc::new(RenderContext::from_eframe(cc));
+        let render_context = Arc::new(render_context);
         let mut type_registry = TypeRegistry::new();
         for f in inventory::iter::<ReflectRegistrationFn>() {
             (f.function)(&mut type_registry);
@@ -32,6 +62,7 @@ impl AssetContext {
         let component_registry = Ref::new(ComponentRegistry::new(&type_registry.read()));
         let asset_registry = AssetRegistry::new(
             project_path,
+            engine_assets_path,
             render_context.clone(),
             type_registry.clone(),
             component_registry.clone(),
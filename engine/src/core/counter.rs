@@ -7,7 +7,7 @@ pub struct Counter<T: Default + PrimInt> {
 impl<T: Default + PrimInt> Counter<T> {
     pub fn next(&mut self) -> T {
         let value = self.next;
-        self.next = self.next.checked_add(&1).unwrap_or_default();
+        self.next = self.next.checked_add(&T::one()).unwrap_or_default();
         value
     }
 }
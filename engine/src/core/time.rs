@@ -1,12 +1,11 @@
-use crate as engine;
 use crate::resource::Resource;
+use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
 pub type TimeType = f32;
 
-#[derive(Resource)]
 pub struct Time {
     timers: RefCell<HashMap<&'static str, Instant>>,
     last_time: Instant,
@@ -55,6 +54,40 @@ impl Time {
     time_member_getter!(time_scale);
 }
 
+/// Part of [`Time`] kept in save games.
+#[derive(Serialize, Deserialize)]
+struct TimeState {
+    time: TimeType,
+    static_time: TimeType,
+    time_scale: TimeType,
+}
+
+impl Resource for Time {
+    fn state_name(&self) -> Option<&'static str> {
+        Some("time")
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(TimeState {
+            time: self.time,
+            static_time: self.static_time,
+            time_scale: self.time_scale,
+        })
+        .ok()
+    }
+
+    fn load_state(&mut self, state: &serde_json::Value) {
+        let Ok(state) = serde_json::from_value::<TimeState>(state.clone()) else {
+            return;
+        };
+        self.time = state.time;
+        self.static_time = state.static_time;
+        self.time_scale = state.time_scale;
+        // The time spent loading doesn't count as a frame
+        self.last_time = Instant::now();
+    }
+}
+
 impl Default for Time {
     fn default() -> Self {
         Self {
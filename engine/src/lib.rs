@@ -18,6 +18,9 @@ pub mod resource;
 pub mod scene;
 pub mod utils;
 
+#[cfg(test)]
+mod test_utils;
+
 pub use engine_derive::*;
 use inventory::collect;
 use reflect::type_registry::TypeRegistry;
@@ -64,6 +64,12 @@ impl Server {
         transport.send_packets(server);
     }
 
+    pub fn disconnect_all(&mut self) {
+        let Self { server, transport } = self;
+        server.disconnect_all();
+        transport.send_packets(server);
+    }
+
     pub fn send_message<I: Into<u8>>(
         &mut self,
         client_id: ClientId,
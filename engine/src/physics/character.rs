@@ -0,0 +1,157 @@
+use nalgebra_glm::Vec3;
+use rapier3d::control::{CharacterAutostep, CharacterLength, KinematicCharacterController};
+use rapier3d::prelude::*;
+
+use crate::component::{ComponentCharacterController, ComponentCollider};
+use crate::physics::SceneQueryFilter;
+use crate::scene::{GameObject, Scene};
+
+impl ComponentCharacterController {
+    fn controller(&self) -> KinematicCharacterController {
+        KinematicCharacterController {
+            offset: CharacterLength::Absolute(self.offset),
+            slide: self.slide,
+            autostep: (self.step_height > 0.0).then_some(CharacterAutostep {
+                max_height: CharacterLength::Absolute(self.step_height),
+                min_width: CharacterLength::Absolute(self.step_min_width),
+                include_dynamic_bodies: false,
+            }),
+            max_slope_climb_angle: self.max_slope_climb_angle,
+            min_slope_slide_angle: self.min_slope_slide_angle,
+            snap_to_ground: (self.snap_to_ground > 0.0)
+                .then_some(CharacterLength::Absolute(self.snap_to_ground)),
+            ..Default::default()
+        }
+    }
+}
+
+impl Scene {
+    /// Moves a game object with a character controller and a collider by up to
+    /// `desired_translation`, stopping at or sliding along the colliders in its way.
+    /// Returns the world-space translation that was actually applied.
+    pub fn move_and_slide(&mut self, game_object: GameObject, desired_translation: Vec3) -> Vec3 {
+        let Some((controller, (shape, collision_mask))) =
+            self.entry(game_object).and_then(|entry| {
+                let controller = entry
+                    .get_component::<ComponentCharacterController>()
+                    .ok()?
+                    .controller();
+                let collider = entry.get_component::<ComponentCollider>().ok()?;
+                Some((
+                    controller,
+                    (collider.shape.clone(), collider.collision_mask),
+                ))
+            })
+        else {
+            return Vec3::zeros();
+        };
+        let Some(shape) = self.physics.collider_shape(self.assets(), &shape) else {
+            return Vec3::zeros();
+        };
+
+        let mut transform = self.get_world_transform(game_object);
+        let filter = SceneQueryFilter {
+            layers: collision_mask,
+            include_sensors: false,
+            exclude: Some(game_object),
+        };
+        let physics = &self.physics;
+        let movement = controller.move_shape(
+            physics.integration_parameters.dt,
+            &physics.bodies,
+            &physics.colliders,
+            &physics.query_pipeline,
+            &*shape,
+            &Isometry::from_parts(transform.position.into(), transform.rotation),
+            desired_translation,
+            filter.query_filter(physics),
+            |_| {},
+        );
+
+        transform.position += movement.translation;
+        transform.update_matrix();
+        self.set_world_transform(game_object, transform.matrix);
+        self.clear_transform_cache();
+        self.write_component(game_object, |c: &mut ComponentCharacterController| {
+            c.grounded = movement.grounded;
+            c.sliding_down_slope = movement.is_sliding_down_slope;
+        });
+
+        // Keep the physics objects in sync so later moves and queries this frame see the new position
+        let position = transform.position;
+        let physics = &mut self.physics;
+        if let Some(handle) = physics.rigid_body_handle(game_object.entity) {
+            let body = &mut physics.bodies[handle];
+            body.set_translation(position, true);
+            body.set_next_kinematic_translation(position);
+            physics
+                .bodies
+                .propagate_modified_body_positions_to_colliders(&mut physics.colliders);
+        } else if let Some(handle) = physics.collider_handle(game_object.entity) {
+            physics.colliders[handle].set_translation(position);
+        }
+        physics.query_pipeline.update(&physics.colliders);
+
+        movement.translation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm::Vec3;
+
+    use crate::component::{ColliderShape, ComponentCharacterController, ComponentCollider};
+    use crate::math::Transform;
+    use crate::scene::Scene;
+    use crate::test_utils;
+
+    fn static_box(scene: &mut Scene, position: Vec3, half_extents: Vec3) {
+        let game_object = scene.create_game_object(None, None);
+        scene.set_transform(
+            game_object,
+            Transform::from_xyz(position.x, position.y, position.z).matrix,
+        );
+        scene.add_component(
+            game_object,
+            ComponentCollider {
+                shape: ColliderShape::Cuboid { half_extents },
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn move_and_slide() {
+        let mut scene = test_utils::scene();
+        static_box(&mut scene, Vec3::zeros(), Vec3::new(10.0, 0.5, 10.0));
+        static_box(
+            &mut scene,
+            Vec3::new(3.0, 1.0, 0.0),
+            Vec3::new(0.5, 5.0, 10.0),
+        );
+        let character = scene.create_game_object(None, None);
+        scene.set_transform(character, Transform::from_xyz(0.0, 1.5, 0.0).matrix);
+        scene.add_component(
+            character,
+            ComponentCollider {
+                shape: ColliderShape::Sphere { radius: 0.5 },
+                ..Default::default()
+            },
+        );
+        scene.add_component(character, ComponentCharacterController::default());
+        scene.prepare();
+
+        // Falls onto the ground
+        let moved = scene.move_and_slide(character, Vec3::new(0.0, -2.0, 0.0));
+        assert!((moved.y + 0.5).abs() < 0.05);
+        assert!(scene
+            .read_component(character, ComponentCharacterController::is_grounded)
+            .unwrap());
+
+        // Stops against the wall
+        scene.move_and_slide(character, Vec3::new(5.0, 0.0, 0.0));
+        let position = scene.get_world_transform(character).position;
+        assert!((position.x - 2.0).abs() < 0.05);
+        assert!((position.y - 1.0).abs() < 0.05);
+    }
+}
@@ -1,7 +1,29 @@
 use nalgebra_glm::Vec3;
+use serde::{Deserialize, Serialize};
 
+use crate as engine;
+use crate::reflect::{Reflect, ReflectDefault};
+use crate::utils::TypeUuid;
+
+#[derive(Clone, TypeUuid, Serialize, Deserialize, Reflect)]
+#[uuid = "c2b1f0a4-6d3e-4a8b-9f57-1e2d3c4b5a69"]
+#[reflect(Default)]
+#[reflect_attr(name = "Physics")]
+#[serde(default)]
 pub struct PhysicsConfiguration {
     pub gravity: Vec3,
+    /// Duration of a single simulation step, in seconds.
+    #[reflect_attr(min = 0.001, speed = 0.001)]
+    pub fixed_time_step: f32,
+    /// Maximum number of steps run in a single frame. Time past that is dropped
+    /// so that a slow frame can't snowball into slower and slower frames.
+    pub max_substeps: u32,
+    pub solver_iterations: usize,
+    /// Enables continuous collision detection for rigid bodies that request it.
+    #[reflect_attr(name = "CCD Enabled")]
+    pub ccd_enabled: bool,
+    #[reflect_attr(name = "Max CCD Substeps")]
+    pub max_ccd_substeps: usize,
     pub physics_pipeline_active: bool,
     pub query_pipeline_active: bool,
 }
@@ -10,6 +32,11 @@ impl Default for PhysicsConfiguration {
     fn default() -> Self {
         Self {
             gravity: Vec3::new(0.0, -9.81, 0.0),
+            fixed_time_step: 1.0 / 60.0,
+            max_substeps: 8,
+            solver_iterations: 4,
+            ccd_enabled: true,
+            max_ccd_substeps: 1,
             physics_pipeline_active: true,
             query_pipeline_active: true,
         }
@@ -1,15 +1,37 @@
+use crate as engine;
+use crate::assets::mesh::Mesh;
+use crate::assets::{AssetAccess, AssetRef};
 use crate::component::{
-    ColliderShape, ComponentCollider, ComponentRigidBody, ComponentTransform, Orientation,
+    ColliderShape, Component, ComponentCollider, ComponentFixedJoint, ComponentPrismaticJoint,
+    ComponentRevoluteJoint, ComponentRigidBody, ComponentRopeJoint, ComponentSphericalJoint,
+    ComponentSpringJoint, ComponentTransform, Joint, Orientation,
 };
+use crate::context::ReadOnlyAssetContext;
 use crate::core::{Time, TimeType};
 use crate::math::Transform;
-use crate::physics::PhysicsConfiguration;
-use crate::scene::{GameObject, Scene};
-use legion::{Entity, IntoQuery};
-use nalgebra::{UnitQuaternion, Vector3};
+use crate::physics::{Collision, CollisionEventCollector, PhysicsConfiguration, PhysicsEvent};
+use crate::reflect::{Reflect, ReflectDefault};
+use crate::scene::{GameObject, ReflectSystem, Scene, System, SystemStage};
+use crate::utils::{ReflectTypeUuidDynamic, TypeUuid};
+use indexmap::IndexSet;
+use legion::query::component;
+use legion::systems::Builder;
+use legion::{Entity, EntityStore, IntoQuery, SystemBuilder};
+use nalgebra::{DMatrix, UnitQuaternion, Vector3};
 use nalgebra_glm::Mat4;
 use rapier3d::prelude::*;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum MeshShapeKind {
+    ConvexHull,
+    TriMesh,
+    Heightfield(u32, u32),
+}
 
 #[derive(Default)]
 pub struct PhysicsContext {
@@ -37,14 +59,39 @@ pub struct PhysicsContext {
     pub query_pipeline: QueryPipeline,
     /// The integration parameters, controlling various low-level coefficient of the simulation.
     pub integration_parameters: IntegrationParameters,
+    /// The scene's physics settings, saved with the scene.
+    pub config: PhysicsConfiguration,
     entity_rigid_body: HashMap<Entity, RigidBodyHandle>,
     entity_collider: HashMap<Entity, ColliderHandle>,
+    collider_entity: HashMap<ColliderHandle, Entity>,
+    /// Joints keyed by the entity and type of the joint component that created them.
+    entity_joint: HashMap<(Entity, Uuid), ImpulseJointHandle>,
+    /// Shapes built from meshes, along with the version of the mesh they were built from.
+    mesh_shapes: Mutex<HashMap<(Uuid, MeshShapeKind), (u32, SharedShape)>>,
+    /// Pairs of non-sensor colliders currently touching, in the order they started touching
+    /// so collision stay events are sent in the same order on every run.
+    contact_pairs: IndexSet<(ColliderHandle, ColliderHandle)>,
+    event_collector: CollisionEventCollector,
     accumulated_time: TimeType,
 }
 
-impl PhysicsContext {
-    const TIME_STEP: f32 = 1.0 / 60.0;
+/// Runtime state of a scene's simulation kept in save games, see [`PhysicsContext::save_state`].
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct PhysicsState {
+    /// Time not simulated yet, see [`PhysicsConfiguration::fixed_time_step`].
+    pub accumulated_time: TimeType,
+    /// State of the rigid bodies, keyed by the ids of their game objects.
+    pub bodies: BTreeMap<Uuid, RigidBodyState>,
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct RigidBodyState {
+    pub linear_velocity: Vector3<Real>,
+    pub angular_velocity: Vector3<Real>,
+    pub sleeping: bool,
+}
 
+impl PhysicsContext {
     fn rigid_body_from_component(
         transform: &Transform,
         rigid_body: &ComponentRigidBody,
@@ -57,11 +104,18 @@ impl PhysicsContext {
             .additional_mass(rigid_body.mass)
             .gravity_scale(rigid_body.gravity_scale)
             .can_sleep(rigid_body.can_sleep)
+            .ccd_enabled(rigid_body.ccd_enabled)
             .build()
     }
 
-    fn collider_shape(shape: ColliderShape) -> SharedShape {
-        match shape {
+    /// Builds the shape of a collider. Returns `None` if the shape's mesh can't be loaded
+    /// or has no usable geometry.
+    pub(crate) fn collider_shape(
+        &self,
+        assets: &ReadOnlyAssetContext,
+        shape: &ColliderShape,
+    ) -> Option<SharedShape> {
+        Some(match *shape {
             ColliderShape::Sphere { radius } => SharedShape::ball(radius),
             ColliderShape::Capsule {
                 orientation,
@@ -76,142 +130,1086 @@ impl PhysicsContext {
                 SharedShape::cuboid(half_extents.x, half_extents.y, half_extents.z)
             }
             ColliderShape::Cone { height, radius } => SharedShape::cone(height, radius),
+            ColliderShape::ConvexHull(ref mesh) => {
+                self.mesh_shape(assets, mesh, MeshShapeKind::ConvexHull)?
+            }
+            ColliderShape::TriMesh(ref mesh) => {
+                self.mesh_shape(assets, mesh, MeshShapeKind::TriMesh)?
+            }
+            ColliderShape::Heightfield {
+                ref mesh,
+                rows,
+                columns,
+            } => self.mesh_shape(assets, mesh, MeshShapeKind::Heightfield(rows, columns))?,
+        })
+    }
+
+    /// Returns the cached shape built from a mesh, building it if the mesh was never used
+    /// with this kind of shape or was reloaded since.
+    fn mesh_shape(
+        &self,
+        assets: &ReadOnlyAssetContext,
+        mesh: &AssetRef<Mesh>,
+        kind: MeshShapeKind,
+    ) -> Option<SharedShape> {
+        let key = (mesh.id(), kind);
+        let version = assets.asset_registry.read().asset_version(mesh.id());
+        if let Some((cached_version, shape)) = self.mesh_shapes.lock().unwrap().get(&key) {
+            if *cached_version == version {
+                return Some(shape.clone());
+            }
         }
+        let mesh = mesh.get_ref(assets)?;
+        let mesh = mesh.read();
+        let shape = match kind {
+            MeshShapeKind::ConvexHull => Self::convex_hull_shape(&mesh),
+            MeshShapeKind::TriMesh => Self::trimesh(&mesh).map(SharedShape::new),
+            MeshShapeKind::Heightfield(rows, columns) => {
+                Self::heightfield_shape(&mesh, rows, columns)
+            }
+        }?;
+        self.mesh_shapes
+            .lock()
+            .unwrap()
+            .insert(key, (version, shape.clone()));
+        Some(shape)
+    }
+
+    fn convex_hull_shape(mesh: &Mesh) -> Option<SharedShape> {
+        let points = mesh
+            .vertices
+            .iter()
+            .map(|v| Point::from(*v))
+            .collect::<Vec<_>>();
+        SharedShape::convex_hull(&points)
+    }
+
+    fn trimesh(mesh: &Mesh) -> Option<TriMesh> {
+        let vertices = mesh.vertices.iter().map(|v| Point::from(*v)).collect();
+        let indices = mesh
+            .indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]])
+            .collect();
+        TriMesh::new(vertices, indices).ok()
     }
 
-    fn collider_from_component(transform: &Transform, collider: &ComponentCollider) -> Collider {
+    fn heightfield_shape(mesh: &Mesh, rows: u32, columns: u32) -> Option<SharedShape> {
+        let trimesh = Self::trimesh(mesh)?;
+        let aabb = trimesh.local_aabb();
+        let half_x = aabb.mins.x.abs().max(aabb.maxs.x.abs());
+        let half_z = aabb.mins.z.abs().max(aabb.maxs.z.abs());
+        let (rows, columns) = (rows.max(2) as usize, columns.max(2) as usize);
+        let heights = DMatrix::from_fn(rows, columns, |i, j| {
+            let x = (j as f32 / (columns - 1) as f32 - 0.5) * 2.0 * half_x;
+            let z = (i as f32 / (rows - 1) as f32 - 0.5) * 2.0 * half_z;
+            let ray = Ray::new(Point::new(x, aabb.maxs.y + 1.0, z), -Vector::y());
+            trimesh
+                .cast_local_ray(&ray, Real::MAX, false)
+                .map_or(aabb.mins.y, |toi| ray.point_at(toi).y)
+        });
+        Some(SharedShape::heightfield(
+            heights,
+            Vector::new(2.0 * half_x, 1.0, 2.0 * half_z),
+        ))
+    }
+
+    /// Drops the shapes built from meshes that were reloaded and flags the colliders using
+    /// them to be synced again.
+    fn reload_mesh_shapes(scene: &mut Scene) {
+        let registry = scene.assets().asset_registry.clone();
+        let registry = registry.read();
+        let mut reloaded = HashSet::new();
+        scene
+            .physics
+            .mesh_shapes
+            .lock()
+            .unwrap()
+            .retain(|(id, _), (version, _)| {
+                let current = registry.asset_version(*id) == *version;
+                if !current {
+                    reloaded.insert(*id);
+                }
+                current
+            });
+        if reloaded.is_empty() {
+            return;
+        }
+        for (_, c_collider) in scene.query_mut::<&mut ComponentCollider>().iter_mut() {
+            if c_collider
+                .shape
+                .mesh()
+                .is_some_and(|mesh| reloaded.contains(&mesh.id()))
+            {
+                c_collider.dirty = true;
+            }
+        }
+    }
+
+    fn collision_groups(collider: &ComponentCollider) -> InteractionGroups {
+        InteractionGroups::new(
+            Group::from_bits_truncate(collider.collision_layers),
+            Group::from_bits_truncate(collider.collision_mask),
+        )
+    }
+
+    fn collider_from_component(
+        transform: &Transform,
+        shape: SharedShape,
+        collider: &ComponentCollider,
+    ) -> Collider {
         let (z, y, x) = transform.rotation.euler_angles();
-        ColliderBuilder::new(Self::collider_shape(collider.shape))
+        ColliderBuilder::new(shape)
             .position(transform.position.into())
             .rotation(Vector3::new(x, y, z))
             .friction(collider.friction)
             .density(collider.density)
+            .sensor(collider.sensor)
+            .collision_groups(Self::collision_groups(collider))
+            .active_events(ActiveEvents::COLLISION_EVENTS)
             .build()
     }
 
+    pub fn rigid_body_handle(&self, entity: Entity) -> Option<RigidBodyHandle> {
+        self.entity_rigid_body.get(&entity).copied()
+    }
+
+    pub fn collider_handle(&self, entity: Entity) -> Option<ColliderHandle> {
+        self.entity_collider.get(&entity).copied()
+    }
+
+    pub fn collider_entity(&self, handle: ColliderHandle) -> Option<Entity> {
+        self.collider_entity.get(&handle).copied()
+    }
+
+    /// Time not simulated yet, see [`PhysicsConfiguration::fixed_time_step`].
+    pub fn accumulated_time(&self) -> TimeType {
+        self.accumulated_time
+    }
+
+    /// Removes the rigid body of an entity from the simulation. Colliders attached to it
+    /// are detached, not removed.
+    pub fn remove_rigid_body(&mut self, entity: Entity) -> Option<RigidBody> {
+        let handle = self.entity_rigid_body.remove(&entity)?;
+        self.bodies.remove(
+            handle,
+            &mut self.islands,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            false,
+        )
+    }
+
+    /// Removes the collider of an entity from the simulation.
+    pub fn remove_collider(&mut self, entity: Entity) -> Option<Collider> {
+        let handle = self.entity_collider.remove(&entity)?;
+        self.collider_entity.remove(&handle);
+        self.contact_pairs
+            .retain(|(h1, h2)| *h1 != handle && *h2 != handle);
+        self.colliders
+            .remove(handle, &mut self.islands, &mut self.bodies, true)
+    }
+
+    /// Removes every physics object belonging to an entity.
+    pub fn remove_entity(&mut self, entity: Entity) {
+        self.remove_collider(entity);
+        self.remove_rigid_body(entity);
+    }
+
+    /// Flags the rigid bodies and colliders of a game object and its descendants
+    /// to be synced again, after it was moved in the hierarchy.
+    pub fn mark_dirty(scene: &mut Scene, game_object: GameObject) {
+        let game_objects = scene.get_descendants(game_object).collect::<Vec<_>>();
+        for go in game_objects {
+            let Some(mut entry) = scene.entry_mut(go) else {
+                continue;
+            };
+            if let Ok(c_rb) = entry.get_component_mut::<ComponentRigidBody>() {
+                c_rb.dirty = true;
+            }
+            if let Ok(c_c) = entry.get_component_mut::<ComponentCollider>() {
+                c_c.dirty = true;
+            }
+        }
+    }
+
+    /// Removes the physics objects of entities that were deleted or lost their component.
+    fn remove_stale(scene: &mut Scene) {
+        let stale_rigid_bodies = scene
+            .physics
+            .entity_rigid_body
+            .keys()
+            .filter(|entity| {
+                scene.world.entry_ref(**entity).map_or(true, |entry| {
+                    entry.get_component::<ComponentRigidBody>().is_err()
+                })
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        for entity in stale_rigid_bodies {
+            scene.physics.remove_rigid_body(entity);
+            // Detached colliders need to find the next rigid body up the hierarchy
+            if let Some(go) = scene.get_game_object_from_entity(entity) {
+                Self::mark_dirty(scene, go);
+            }
+        }
+        let stale_colliders = scene
+            .physics
+            .entity_collider
+            .keys()
+            .filter(|entity| {
+                scene.world.entry_ref(**entity).map_or(true, |entry| {
+                    entry.get_component::<ComponentCollider>().is_err()
+                })
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        for entity in stale_colliders {
+            scene.physics.remove_collider(entity);
+        }
+    }
+
     pub fn prepare(scene: &mut Scene) {
-        let mut query = <(Entity, &ComponentRigidBody)>::query();
-        let mut entities: Vec<Entity> = Default::default();
-        for (entity, c_rigid_body) in query.iter(&scene.world).filter(|(_, c_rb)| c_rb.dirty) {
-            if let Some(go) = scene.get_game_object_from_entity(*entity) {
-                let transform = scene.get_world_transform(go);
-                let rb_handle =
-                    if let Some(handle) = scene.physics.entity_rigid_body.get(&go.entity) {
-                        *handle
-                    } else {
-                        let rigid_body = Self::rigid_body_from_component(&transform, c_rigid_body);
-                        let handle = scene.physics.bodies.insert(rigid_body);
-                        scene.physics.entity_rigid_body.insert(go.entity, handle);
-                        handle
-                    };
-                let rb = &mut scene.physics.bodies[rb_handle];
-                rb.set_position(transform.position.into(), true);
-                rb.set_rotation(transform.rotation, true);
-                rb.set_enabled(c_rigid_body.enabled);
-                rb.set_body_type(c_rigid_body.ty, true);
-                rb.set_additional_mass(c_rigid_body.mass, true);
-                rb.set_gravity_scale(c_rigid_body.gravity_scale, true);
-                if !c_rigid_body.can_sleep {
-                    rb.activation_mut().normalized_linear_threshold = -1.0;
-                    rb.activation_mut().angular_threshold = -1.0;
-                }
-                entities.push(*entity);
+        Self::remove_stale(scene);
+        Self::reload_mesh_shapes(scene);
+        let mut new_bodies: Vec<GameObject> = Default::default();
+        let dirty_bodies = scene
+            .query::<&ComponentRigidBody>()
+            .iter()
+            .filter(|(_, c_rb)| c_rb.dirty)
+            .map(|(go, c_rb)| (go, c_rb.clone()))
+            .collect::<Vec<_>>();
+        for &(go, ref c_rigid_body) in &dirty_bodies {
+            let transform = scene.get_world_transform(go);
+            let active = scene.is_active_in_hierarchy(go)
+                && scene.is_component_enabled::<ComponentRigidBody>(go);
+            let rb_handle = if let Some(handle) = scene.physics.entity_rigid_body.get(&go.entity) {
+                *handle
+            } else {
+                let rigid_body = Self::rigid_body_from_component(&transform, c_rigid_body);
+                let handle = scene.physics.bodies.insert(rigid_body);
+                scene.physics.entity_rigid_body.insert(go.entity, handle);
+                new_bodies.push(go);
+                handle
+            };
+            let rb = &mut scene.physics.bodies[rb_handle];
+            rb.set_position(transform.position.into(), true);
+            rb.set_rotation(transform.rotation, true);
+            rb.set_enabled(c_rigid_body.enabled && active);
+            rb.set_body_type(c_rigid_body.ty, true);
+            rb.set_additional_mass(c_rigid_body.mass, true);
+            rb.set_gravity_scale(c_rigid_body.gravity_scale, true);
+            rb.enable_ccd(c_rigid_body.ccd_enabled);
+            if !c_rigid_body.can_sleep {
+                rb.activation_mut().normalized_linear_threshold = -1.0;
+                rb.activation_mut().angular_threshold = -1.0;
             }
         }
-        for entity in entities.drain(0..) {
-            if let Some(mut entry) = scene.world.entry(entity) {
-                if let Ok(c_rb) = entry.get_component_mut::<ComponentRigidBody>() {
-                    c_rb.dirty = false;
-                }
+        for (_, c_rb) in scene.query_mut::<&mut ComponentRigidBody>().iter_mut() {
+            c_rb.dirty = false;
+        }
+        // Colliders below a new rigid body may have to be attached to it
+        for go in new_bodies {
+            let colliders = scene
+                .get_descendants_with_component::<ComponentCollider>(go)
+                .collect::<Vec<_>>();
+            for collider in colliders {
+                scene.write_component(collider, |c_c: &mut ComponentCollider| c_c.dirty = true);
             }
         }
-        let mut query = <(Entity, &ComponentCollider)>::query();
-        entities.clear();
-        for (entity, c_collider) in query.iter(&scene.world).filter(|(_, c_c)| c_c.dirty) {
-            if let Some(go) = scene.get_game_object_from_entity(*entity) {
-                let parent = scene.get_ancestor_with_component::<ComponentRigidBody>(go);
-                let rb_handle =
-                    parent.and_then(|parent| scene.physics.entity_rigid_body.get(&parent.entity));
-                let transform = parent
-                    .map(|parent| scene.get_transform_relative_to(go, parent))
-                    .unwrap_or_else(|| scene.get_world_transform(go));
-                let c_handle = if let Some(handle) = scene.physics.entity_collider.get(&go.entity) {
-                    *handle
-                } else {
-                    let collider = Self::collider_from_component(&transform, c_collider);
-                    let handle = match rb_handle {
-                        None => scene.physics.colliders.insert(collider),
-                        Some(rb_handle) => scene.physics.colliders.insert_with_parent(
-                            collider,
-                            *rb_handle,
-                            &mut scene.physics.bodies,
-                        ),
-                    };
-                    scene.physics.entity_collider.insert(go.entity, handle);
-                    handle
+        let dirty_colliders = scene
+            .query::<&ComponentCollider>()
+            .iter()
+            .filter(|(_, c_c)| c_c.dirty)
+            .map(|(go, c_c)| (go, c_c.clone()))
+            .collect::<Vec<_>>();
+        for &(go, ref c_collider) in &dirty_colliders {
+            let Some(shape) = scene
+                .physics
+                .collider_shape(scene.assets(), &c_collider.shape)
+            else {
+                scene.physics.remove_collider(go.entity);
+                continue;
+            };
+            let parent = scene.get_ancestor_with_component::<ComponentRigidBody>(go);
+            let rb_handle = parent
+                .and_then(|parent| scene.physics.entity_rigid_body.get(&parent.entity))
+                .copied();
+            let transform = parent
+                .map(|parent| scene.get_transform_relative_to(go, parent))
+                .unwrap_or_else(|| scene.get_world_transform(go));
+            let active = scene.is_active_in_hierarchy(go)
+                && scene.is_component_enabled::<ComponentCollider>(go);
+            let c_handle = if let Some(handle) = scene.physics.entity_collider.get(&go.entity) {
+                scene
+                    .physics
+                    .colliders
+                    .set_parent(*handle, rb_handle, &mut scene.physics.bodies);
+                *handle
+            } else {
+                let collider = Self::collider_from_component(&transform, shape.clone(), c_collider);
+                let handle = match rb_handle {
+                    None => scene.physics.colliders.insert(collider),
+                    Some(rb_handle) => scene.physics.colliders.insert_with_parent(
+                        collider,
+                        rb_handle,
+                        &mut scene.physics.bodies,
+                    ),
                 };
-                let c = &mut scene.physics.colliders[c_handle];
+                scene.physics.entity_collider.insert(go.entity, handle);
+                scene.physics.collider_entity.insert(handle, go.entity);
+                handle
+            };
+            let body_position = rb_handle.map(|handle| *scene.physics.bodies[handle].position());
+            let c = &mut scene.physics.colliders[c_handle];
+            if let Some(body_position) = body_position {
+                let position = Isometry::from_parts(transform.position.into(), transform.rotation);
+                c.set_position_wrt_parent(position);
+                c.set_position(body_position * position);
+            } else {
                 c.set_position(transform.position.into());
                 c.set_rotation(transform.rotation);
-                c.set_shape(Self::collider_shape(c_collider.shape));
-                c.set_friction(c_collider.friction);
-                c.set_density(c_collider.density);
-                entities.push(*entity);
             }
+            c.set_enabled(c_collider.enabled && active);
+            c.set_shape(shape);
+            c.set_friction(c_collider.friction);
+            c.set_density(c_collider.density);
+            c.set_sensor(c_collider.sensor);
+            c.set_collision_groups(Self::collision_groups(c_collider));
+        }
+        for (_, c_collider) in scene.query_mut::<&mut ComponentCollider>().iter_mut() {
+            c_collider.dirty = false;
+        }
+        let mut joints = HashSet::new();
+        Self::sync_joints::<ComponentFixedJoint>(scene, &mut joints);
+        Self::sync_joints::<ComponentRevoluteJoint>(scene, &mut joints);
+        Self::sync_joints::<ComponentPrismaticJoint>(scene, &mut joints);
+        Self::sync_joints::<ComponentSphericalJoint>(scene, &mut joints);
+        Self::sync_joints::<ComponentRopeJoint>(scene, &mut joints);
+        Self::sync_joints::<ComponentSpringJoint>(scene, &mut joints);
+        let stale_joints = scene
+            .physics
+            .entity_joint
+            .keys()
+            .filter(|key| !joints.contains(key))
+            .copied()
+            .collect::<Vec<_>>();
+        for key in stale_joints {
+            scene.physics.remove_joint(key);
         }
-        for entity in entities.drain(0..) {
-            if let Some(mut entry) = scene.world.entry(entity) {
-                if let Ok(c_c) = entry.get_component_mut::<ComponentCollider>() {
-                    c_c.dirty = false;
+        // Keep scene queries up to date when the simulation isn't stepping
+        let physics = &mut scene.physics;
+        physics
+            .bodies
+            .propagate_modified_body_positions_to_colliders(&mut physics.colliders);
+        physics.query_pipeline.update(&physics.colliders);
+    }
+
+    /// Returns the rigid body a game object is attached to, along with the transform
+    /// converting positions from the game object's local space to the body's local space.
+    fn attached_body(
+        scene: &Scene,
+        game_object: GameObject,
+    ) -> Option<(RigidBodyHandle, Transform)> {
+        let body = scene.get_ancestor_with_component::<ComponentRigidBody>(game_object)?;
+        let handle = scene.physics.rigid_body_handle(body.entity)?;
+        Some((handle, scene.get_transform_relative_to(game_object, body)))
+    }
+
+    fn joint_frame(transform: &Transform, frame: &Isometry<Real>) -> Isometry<Real> {
+        Isometry::from_parts(
+            transform
+                .transform_position(&frame.translation.vector)
+                .into(),
+            transform.rotation * frame.rotation,
+        )
+    }
+
+    fn remove_joint(&mut self, key: (Entity, Uuid)) {
+        if let Some(handle) = self.entity_joint.remove(&key) {
+            self.impulse_joints.remove(handle, true);
+        }
+    }
+
+    /// Creates, updates or removes the rapier joints backing every joint component of type `T`.
+    /// The key of every joint that is still valid is added to `synced`.
+    fn sync_joints<T: Component + Joint + TypeUuid>(
+        scene: &mut Scene,
+        synced: &mut HashSet<(Entity, Uuid)>,
+    ) {
+        let joints = scene
+            .query::<&T>()
+            .iter()
+            .map(|(go, joint)| (go, joint.connected_body(), joint.build()))
+            .collect::<Vec<_>>();
+        for (go, connected_body, mut data) in joints {
+            let key = (go.entity, T::type_uuid());
+            let bodies = connected_body.game_object(scene).and_then(|connected| {
+                Self::attached_body(scene, go).zip(Self::attached_body(scene, connected))
+            });
+            let Some(((body1, transform1), (body2, transform2))) = bodies else {
+                scene.physics.remove_joint(key);
+                continue;
+            };
+            if body1 == body2 {
+                scene.physics.remove_joint(key);
+                continue;
+            }
+            data.local_frame1 = Self::joint_frame(&transform1, &data.local_frame1);
+            data.local_frame2 = Self::joint_frame(&transform2, &data.local_frame2);
+            synced.insert(key);
+
+            let physics = &mut scene.physics;
+            // Joints are removed along with their rigid bodies, so the handle may be stale
+            let existing = physics.entity_joint.get(&key).and_then(|handle| {
+                physics
+                    .impulse_joints
+                    .get(*handle)
+                    .map(|joint| (*handle, joint))
+            });
+            if let Some((handle, joint)) = existing {
+                if joint.body1 == body1 && joint.body2 == body2 {
+                    if joint.data != data {
+                        physics.impulse_joints.get_mut(handle, true).unwrap().data = data;
+                    }
+                    continue;
                 }
             }
+            physics.remove_joint(key);
+            let handle = physics.impulse_joints.insert(body1, body2, data, true);
+            physics.entity_joint.insert(key, handle);
+        }
+    }
+
+    pub fn update(scene: &mut Scene, time: &Time) {
+        for _ in 0..scene.physics.pending_steps(time) {
+            Self::step_scene(scene);
+        }
+        Self::sync_transforms(scene);
+    }
+
+    /// Adds a frame's time to the simulation and returns how many steps should run to catch up.
+    pub(crate) fn pending_steps(&mut self, time: &Time) -> u32 {
+        if !self.config.physics_pipeline_active {
+            return 0;
+        }
+        let time_step = self.config.fixed_time_step.max(f32::EPSILON);
+        let max_substeps = self.config.max_substeps.max(1);
+        self.accumulated_time += time.delta_time * time.time_scale;
+        let steps = (self.accumulated_time / time_step) as u32;
+        if steps > max_substeps {
+            // Running behind, drop the remaining time instead of falling further behind
+            self.accumulated_time %= time_step;
+            return max_substeps;
         }
+        self.accumulated_time -= steps as f32 * time_step;
+        steps
     }
 
-    pub fn update(scene: &mut Scene, time: &Time, config: &PhysicsConfiguration) {
-        scene.physics.step_simulation(time, config);
-        let mut query = <(Entity, &ComponentTransform, &ComponentRigidBody)>::query();
-        let mut transforms: HashMap<GameObject, Mat4> = Default::default();
-        for (entity, _, _) in query.iter(&scene.world) {
-            if let Some(go) = scene.get_game_object_from_entity(*entity) {
-                if let Some(rb_handle) = scene.physics.entity_rigid_body.get(entity).copied() {
-                    let rb = &scene.physics.bodies[rb_handle];
-                    let old_transform = scene.get_world_transform(go);
-                    let transform = Transform::from_components(
-                        *rb.translation(),
-                        UnitQuaternion::from(*rb.rotation()),
-                        old_transform.scale,
-                    )
-                    .matrix;
-                    transforms.insert(go, transform);
+    /// Saves what the scene doesn't keep of the simulation. Rigid body positions are kept by
+    /// the transforms of their game objects.
+    pub fn save_state(scene: &Scene) -> PhysicsState {
+        let mut bodies = BTreeMap::new();
+        for (&entity, &handle) in &scene.physics.entity_rigid_body {
+            let Some(go) = scene.get_game_object_from_entity(entity) else {
+                continue;
+            };
+            let rb = &scene.physics.bodies[handle];
+            bodies.insert(
+                scene.get_game_object_uuid(go),
+                RigidBodyState {
+                    linear_velocity: *rb.linvel(),
+                    angular_velocity: *rb.angvel(),
+                    sleeping: rb.is_sleeping(),
+                },
+            );
+        }
+        PhysicsState {
+            accumulated_time: scene.physics.accumulated_time,
+            bodies,
+        }
+    }
+
+    /// Restores the state saved by [`PhysicsContext::save_state`], once the rigid bodies of
+    /// the scene are created by [`PhysicsContext::prepare`].
+    pub fn load_state(scene: &mut Scene, state: &PhysicsState) {
+        scene.physics.accumulated_time = state.accumulated_time;
+        for (id, body) in &state.bodies {
+            let Some(go) = scene.get_game_object_by_uuid(*id) else {
+                continue;
+            };
+            let Some(handle) = scene.physics.entity_rigid_body.get(&go.entity).copied() else {
+                continue;
+            };
+            let rb = &mut scene.physics.bodies[handle];
+            rb.set_linvel(body.linear_velocity, true);
+            rb.set_angvel(body.angular_velocity, true);
+            if body.sleeping {
+                rb.sleep();
+            }
+        }
+    }
+
+    /// Runs a single simulation step and dispatches the collision events it raised.
+    pub(crate) fn step_scene(scene: &mut Scene) {
+        scene.physics.step();
+        let events = Self::collect_events(scene);
+        scene.dispatch_physics_events(events);
+    }
+
+    /// Copies the simulated rigid body positions back to their game objects.
+    pub(crate) fn sync_transforms(scene: &mut Scene) {
+        let RigidBodyPoses(poses) = Self::rigid_body_poses(scene);
+        for (entity, matrix) in poses {
+            if let Some(go) = scene.get_game_object_from_entity(entity) {
+                scene.set_transform(go, matrix);
+            }
+        }
+    }
+
+    /// Reads the local transforms the simulated rigid bodies should be given.
+    pub(crate) fn rigid_body_poses(scene: &Scene) -> RigidBodyPoses {
+        let mut poses = HashMap::new();
+        for (go, _) in scene
+            .query::<&ComponentRigidBody>()
+            .with::<ComponentTransform>()
+            .iter()
+        {
+            let Some(rb_handle) = scene.physics.entity_rigid_body.get(&go.entity).copied() else {
+                continue;
+            };
+            let rb = &scene.physics.bodies[rb_handle];
+            let old_transform = scene.get_world_transform(go);
+            let transform = Transform::from_components(
+                *rb.translation(),
+                UnitQuaternion::from(*rb.rotation()),
+                old_transform.scale,
+            )
+            .matrix;
+            let parent_transform = scene
+                .get_parent_game_object(go)
+                .map_or(Mat4::identity(), |parent| {
+                    scene.get_world_transform(parent).inverse_matrix
+                });
+            poses.insert(go.entity, parent_transform * transform);
+        }
+        RigidBodyPoses(poses)
+    }
+
+    /// Advances the simulation by a single fixed time step.
+    pub fn step(&mut self) {
+        let config = &self.config;
+        self.integration_parameters.dt = config.fixed_time_step.max(f32::EPSILON);
+        self.integration_parameters.num_solver_iterations =
+            NonZeroUsize::new(config.solver_iterations).unwrap_or(NonZeroUsize::MIN);
+        self.integration_parameters.max_ccd_substeps = if config.ccd_enabled {
+            config.max_ccd_substeps.max(1)
+        } else {
+            0
+        };
+        let query_pipeline = if config.query_pipeline_active {
+            Some(&mut self.query_pipeline)
+        } else {
+            None
+        };
+        self.physics_pipeline.step(
+            &config.gravity,
+            &self.integration_parameters,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            &mut self.ccd_solver,
+            query_pipeline,
+            &(),
+            &self.event_collector,
+        );
+    }
+
+    /// Turns the collision events of the last step into events for the game objects
+    /// owning the colliders involved. Pairs where a collider was removed are skipped.
+    fn collect_events(scene: &mut Scene) -> Vec<(GameObject, PhysicsEvent)> {
+        let mut events = Vec::new();
+        let mut started = HashSet::new();
+        for event in scene.physics.event_collector.drain() {
+            let pair = (event.collider1(), event.collider2());
+            match (event, event.sensor()) {
+                (CollisionEvent::Started(..), false) => {
+                    scene.physics.contact_pairs.insert(pair);
+                    started.insert(pair);
+                }
+                (CollisionEvent::Stopped(..), false) => {
+                    scene.physics.contact_pairs.shift_remove(&pair);
+                    scene.physics.contact_pairs.shift_remove(&(pair.1, pair.0));
+                }
+                _ => {}
+            }
+            let Some((go1, go2)) = Self::collider_pair_game_objects(scene, pair) else {
+                continue;
+            };
+            match (event, event.sensor()) {
+                (CollisionEvent::Started(..), true) => {
+                    events.push((go1, PhysicsEvent::TriggerEnter(go2)));
+                    events.push((go2, PhysicsEvent::TriggerEnter(go1)));
+                }
+                (CollisionEvent::Stopped(..), true) => {
+                    events.push((go1, PhysicsEvent::TriggerExit(go2)));
+                    events.push((go2, PhysicsEvent::TriggerExit(go1)));
+                }
+                (CollisionEvent::Started(..), false) => {
+                    let (c1, c2) = Self::collisions(scene, pair, (go1, go2));
+                    events.push((go1, PhysicsEvent::CollisionEnter(c1)));
+                    events.push((go2, PhysicsEvent::CollisionEnter(c2)));
+                }
+                (CollisionEvent::Stopped(..), false) => {
+                    let (c1, c2) = Self::collisions(scene, pair, (go1, go2));
+                    events.push((go1, PhysicsEvent::CollisionExit(c1)));
+                    events.push((go2, PhysicsEvent::CollisionExit(c2)));
                 }
             }
         }
-        for (go, transform) in transforms {
-            scene.set_world_transform(go, transform);
+        for pair in scene.physics.contact_pairs.iter().copied() {
+            if started.contains(&pair) {
+                continue;
+            }
+            let Some(game_objects) = Self::collider_pair_game_objects(scene, pair) else {
+                continue;
+            };
+            let (c1, c2) = Self::collisions(scene, pair, game_objects);
+            events.push((game_objects.0, PhysicsEvent::CollisionStay(c1)));
+            events.push((game_objects.1, PhysicsEvent::CollisionStay(c2)));
         }
+        events
     }
 
-    pub fn step_simulation(&mut self, time: &Time, config: &PhysicsConfiguration) {
-        self.accumulated_time += time.delta_time * time.time_scale;
-        while self.accumulated_time > Self::TIME_STEP {
-            self.integration_parameters.dt = Self::TIME_STEP;
-            self.physics_pipeline.step(
-                &config.gravity,
-                &self.integration_parameters,
-                &mut self.islands,
-                &mut self.broad_phase,
-                &mut self.narrow_phase,
-                &mut self.bodies,
-                &mut self.colliders,
-                &mut self.impulse_joints,
-                &mut self.multibody_joints,
-                &mut self.ccd_solver,
-                Some(&mut self.query_pipeline),
-                &(),
-                &(),
+    fn collider_pair_game_objects(
+        scene: &Scene,
+        (h1, h2): (ColliderHandle, ColliderHandle),
+    ) -> Option<(GameObject, GameObject)> {
+        let game_object = |handle| {
+            scene
+                .physics
+                .collider_entity
+                .get(&handle)
+                .and_then(|entity| scene.get_game_object_from_entity(*entity))
+        };
+        Some((game_object(h1)?, game_object(h2)?))
+    }
+
+    fn collisions(
+        scene: &Scene,
+        (h1, h2): (ColliderHandle, ColliderHandle),
+        (go1, go2): (GameObject, GameObject),
+    ) -> (Collision, Collision) {
+        let contact_pair = scene.physics.narrow_phase.contact_pair(h1, h2);
+        (
+            Collision::from_contact_pair(go2, h1, contact_pair),
+            Collision::from_contact_pair(go1, h2, contact_pair),
+        )
+    }
+}
+
+/// Local transforms of the simulated rigid bodies, by entity.
+pub(crate) struct RigidBodyPoses(HashMap<Entity, Mat4>);
+
+/// Copies the simulated rigid body positions back to their transforms, before components update.
+#[derive(Default, TypeUuid, Reflect)]
+#[uuid = "8e3f51c6-0d27-4b9a-b4e8-61a9c2d7f305"]
+#[reflect(Default, TypeUuidDynamic, System)]
+#[reflect_attr(priority = -1000)]
+pub struct RigidBodySyncSystem;
+
+impl System for RigidBodySyncSystem {
+    fn stage(&self) -> SystemStage {
+        SystemStage::Update
+    }
+
+    fn add_to_schedule(&self, schedule: &mut Builder) {
+        schedule.add_system(
+            SystemBuilder::new("rigid_body_sync")
+                .read_resource::<RigidBodyPoses>()
+                .with_query(
+                    <(Entity, &mut ComponentTransform)>::query()
+                        .filter(component::<ComponentRigidBody>()),
+                )
+                .build(|_, world, poses, query| {
+                    query.par_for_each_mut(world, |(entity, c_transform)| {
+                        if let Some(matrix) = poses.0.get(entity) {
+                            c_transform.transform.set_local_matrix(matrix);
+                        }
+                    });
+                }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm::Vec3;
+    use serde::{Deserialize, Serialize};
+
+    use rapier3d::prelude::JointAxis;
+
+    use super::PhysicsContext;
+    use crate as engine;
+    use crate::assets::mesh::Mesh;
+    use crate::component::{
+        ColliderShape, Component, ComponentCollider, ComponentEventContext, ComponentFixedJoint,
+        ComponentRevoluteJoint, ComponentRigidBody, ReflectComponent,
+    };
+    use crate::core::Time;
+    use crate::math::Transform;
+    use crate::physics::Collision;
+    use crate::reflect::{Reflect, ReflectDefault};
+    use crate::scene::{GameObject, GameObjectRef, Scene};
+    use crate::test_utils;
+    use crate::utils::{ReflectTypeUuidDynamic, TypeUuid};
+
+    #[derive(Default, TypeUuid, Serialize, Deserialize, Component, Reflect)]
+    #[uuid = "0f5b7a3e-4c1d-4f27-9a0c-5d6e2b8c9f14"]
+    #[reflect(Default, TypeUuidDynamic, Component)]
+    struct CollisionRecorder {
+        collision_enter: u32,
+        collision_stay: u32,
+        collision_exit: u32,
+        trigger_enter: u32,
+        trigger_exit: u32,
+        #[serde(skip)]
+        #[reflect_skip]
+        normal: Vec3,
+    }
+
+    impl Component for CollisionRecorder {
+        fn on_collision_enter(&mut self, _: ComponentEventContext, collision: &Collision) {
+            self.collision_enter += 1;
+            self.normal = collision.normal;
+        }
+
+        fn on_collision_stay(&mut self, _: ComponentEventContext, _: &Collision) {
+            self.collision_stay += 1;
+        }
+
+        fn on_collision_exit(&mut self, _: ComponentEventContext, _: &Collision) {
+            self.collision_exit += 1;
+        }
+
+        fn on_trigger_enter(&mut self, _: ComponentEventContext, _: GameObject) {
+            self.trigger_enter += 1;
+        }
+
+        fn on_trigger_exit(&mut self, _: ComponentEventContext, _: GameObject) {
+            self.trigger_exit += 1;
+        }
+    }
+
+    fn physics_object(scene: &mut Scene, parent: Option<GameObject>, body: bool) -> GameObject {
+        let game_object = scene.create_game_object(None, parent);
+        if body {
+            scene.add_component(game_object, ComponentRigidBody::default());
+        }
+        scene.add_component(game_object, ComponentCollider::default());
+        game_object
+    }
+
+    fn step(scene: &mut Scene) {
+        let mut time = Time::default();
+        time.delta_time = 0.1;
+        scene.prepare();
+        PhysicsContext::update(scene, &time);
+    }
+
+    #[test]
+    fn delete_subtree_during_simulation() {
+        let mut scene = test_utils::scene();
+        let other = physics_object(&mut scene, None, true);
+        let root = physics_object(&mut scene, None, true);
+        let child = physics_object(&mut scene, Some(root), false);
+        let grandchild = physics_object(&mut scene, Some(child), true);
+        step(&mut scene);
+        assert_eq!(scene.physics.bodies.len(), 3);
+        assert_eq!(scene.physics.colliders.len(), 4);
+
+        scene.delete_game_object(root);
+        step(&mut scene);
+        for go in [root, child, grandchild] {
+            assert!(scene.physics.rigid_body_handle(go.entity).is_none());
+            assert!(scene.physics.collider_handle(go.entity).is_none());
+        }
+        assert_eq!(scene.physics.bodies.len(), 1);
+        assert_eq!(scene.physics.colliders.len(), 1);
+        let body = scene.physics.rigid_body_handle(other.entity).unwrap();
+        assert_eq!(scene.physics.bodies[body].colliders().len(), 1);
+        step(&mut scene);
+    }
+
+    #[test]
+    fn remove_components_and_reparent() {
+        let mut scene = test_utils::scene();
+        let parent = physics_object(&mut scene, None, true);
+        let body = physics_object(&mut scene, Some(parent), true);
+        let child = physics_object(&mut scene, Some(body), false);
+        step(&mut scene);
+        let child_collider = scene.physics.collider_handle(child.entity).unwrap();
+        let parent_body = scene.physics.rigid_body_handle(parent.entity);
+        let body_handle = scene.physics.rigid_body_handle(body.entity);
+        assert_eq!(
+            scene.physics.colliders[child_collider].parent(),
+            body_handle
+        );
+
+        // The collider falls back to the next rigid body up the hierarchy
+        scene
+            .entry_mut(body)
+            .unwrap()
+            .remove_component::<ComponentRigidBody>();
+        step(&mut scene);
+        assert!(scene.physics.rigid_body_handle(body.entity).is_none());
+        assert_eq!(scene.physics.bodies.len(), 1);
+        assert_eq!(
+            scene.physics.colliders[child_collider].parent(),
+            parent_body
+        );
+
+        scene
+            .entry_mut(child)
+            .unwrap()
+            .remove_component::<ComponentCollider>();
+        step(&mut scene);
+        assert!(scene.physics.collider_handle(child.entity).is_none());
+        assert_eq!(scene.physics.colliders.len(), 2);
+
+        let body_collider = scene.physics.collider_handle(body.entity).unwrap();
+        scene.set_parent(body, None);
+        step(&mut scene);
+        assert_eq!(scene.physics.colliders[body_collider].parent(), None);
+    }
+
+    #[test]
+    fn collision_and_trigger_events() {
+        let mut scene = test_utils::scene();
+        let ground = scene.create_game_object(None, None);
+        scene.add_component(
+            ground,
+            ComponentCollider {
+                shape: ColliderShape::Cuboid {
+                    half_extents: Vec3::new(5.0, 0.5, 5.0),
+                },
+                ..Default::default()
+            },
+        );
+        let trigger = scene.create_game_object(None, None);
+        scene.set_transform(trigger, Transform::from_xyz(0.0, 2.5, 0.0).matrix);
+        scene.add_component(
+            trigger,
+            ComponentCollider {
+                shape: ColliderShape::Cuboid {
+                    half_extents: Vec3::new(1.0, 0.25, 1.0),
+                },
+                sensor: true,
+                ..Default::default()
+            },
+        );
+        let ball = scene.create_game_object(None, None);
+        scene.set_transform(ball, Transform::from_xyz(0.0, 2.0, 0.0).matrix);
+        scene.add_component(ball, ComponentRigidBody::default());
+        scene.add_component(
+            ball,
+            ComponentCollider {
+                shape: ColliderShape::Sphere { radius: 0.5 },
+                ..Default::default()
+            },
+        );
+        scene.add_component(ball, CollisionRecorder::default());
+        scene.add_component(ground, CollisionRecorder::default());
+
+        for _ in 0..20 {
+            step(&mut scene);
+        }
+        scene
+            .read_component(ball, |recorder: &CollisionRecorder| {
+                assert_eq!(recorder.trigger_enter, 1);
+                assert_eq!(recorder.trigger_exit, 1);
+                assert_eq!(recorder.collision_enter, 1);
+                assert!(recorder.collision_stay > 0);
+                assert_eq!(recorder.collision_exit, 0);
+                // Pointing from the ground towards the ball
+                assert!(recorder.normal.y > 0.9);
+            })
+            .unwrap();
+        scene
+            .read_component(ground, |recorder: &CollisionRecorder| {
+                assert_eq!(recorder.collision_enter, 1);
+                assert!(recorder.normal.y < -0.9);
+            })
+            .unwrap();
+
+        // Disabled components don't receive events
+        scene.set_component_enabled::<CollisionRecorder>(ground, false);
+        scene.write_component(ball, |rb: &mut ComponentRigidBody| rb.dirty = true);
+        scene.set_transform(ball, Transform::from_xyz(0.0, 10.0, 0.0).matrix);
+        step(&mut scene);
+        scene
+            .read_component(ball, |recorder: &CollisionRecorder| {
+                assert_eq!(recorder.collision_exit, 1);
+            })
+            .unwrap();
+        assert_eq!(
+            scene.read_component(ground, |recorder: &CollisionRecorder| recorder
+                .collision_exit),
+            Some(0)
+        );
+
+        // Nor do disabled rigid bodies and colliders simulate
+        scene.set_component_enabled::<ComponentRigidBody>(ball, false);
+        scene.set_component_enabled::<ComponentCollider>(ground, false);
+        step(&mut scene);
+        let body = scene.physics.rigid_body_handle(ball.entity).unwrap();
+        assert!(!scene.physics.bodies[body].is_enabled());
+        let collider = scene.physics.entity_collider[&ground.entity];
+        assert!(!scene.physics.colliders[collider].is_enabled());
+        scene.set_component_enabled::<ComponentRigidBody>(ball, true);
+        step(&mut scene);
+        assert!(scene.physics.bodies[body].is_enabled());
+    }
+
+    #[test]
+    fn scene_physics_configuration() {
+        let mut scene = test_utils::scene();
+        scene.physics.config.gravity = Vec3::new(0.0, -1.0, 0.0);
+        scene.physics.config.max_substeps = 3;
+        let ball = scene.create_game_object(None, None);
+        scene.add_component(ball, ComponentRigidBody::default());
+        scene.add_component(ball, ComponentCollider::default());
+
+        let mut scene = scene.clone();
+        assert_eq!(scene.physics.config.max_substeps, 3);
+
+        // A very long frame only runs up to max_substeps steps
+        let mut time = Time::default();
+        time.delta_time = 10.0;
+        scene.prepare();
+        PhysicsContext::update(&mut scene, &time);
+        let config = &scene.physics.config;
+        assert!(scene.physics.accumulated_time < config.fixed_time_step);
+        let ball = scene.root_objects().next().unwrap();
+        let body = scene.physics.rigid_body_handle(ball.entity).unwrap();
+        let velocity = scene.physics.bodies[body].linvel().y;
+        assert!((velocity + 3.0 * config.fixed_time_step).abs() < 1e-4);
+    }
+
+    #[test]
+    fn joints() {
+        let mut scene = test_utils::scene();
+        let hinge = physics_object(&mut scene, None, true);
+        let door = physics_object(&mut scene, None, true);
+        scene.set_transform(door, Transform::from_xyz(2.0, 0.0, 0.0).matrix);
+        scene.add_component(
+            hinge,
+            ComponentRevoluteJoint {
+                connected_body: GameObjectRef::new(scene.get_game_object_uuid(door)),
+                anchor: Vec3::new(1.0, 0.0, 0.0),
+                connected_anchor: Vec3::new(-1.0, 0.0, 0.0),
+                ..Default::default()
+            },
+        );
+        scene.add_component(
+            hinge,
+            ComponentFixedJoint {
+                connected_body: GameObjectRef::new(scene.get_game_object_uuid(hinge)),
+                ..Default::default()
+            },
+        );
+        step(&mut scene);
+
+        // Joints connecting a body to itself are ignored
+        assert_eq!(scene.physics.impulse_joints.len(), 1);
+        let hinge_body = scene.physics.rigid_body_handle(hinge.entity).unwrap();
+        let door_body = scene.physics.rigid_body_handle(door.entity).unwrap();
+        let (_, joint) = scene.physics.impulse_joints.iter().next().unwrap();
+        assert_eq!((joint.body1, joint.body2), (hinge_body, door_body));
+        assert!((joint.data.local_anchor2().coords - Vec3::new(-1.0, 0.0, 0.0)).norm() < 1e-4);
+
+        scene.write_component(hinge, |joint: &mut ComponentRevoluteJoint| {
+            joint.limits_enabled = true;
+        });
+        step(&mut scene);
+        let (_, joint) = scene.physics.impulse_joints.iter().next().unwrap();
+        assert!(joint.data.limits(JointAxis::AngX).is_some());
+
+        scene
+            .entry_mut(hinge)
+            .unwrap()
+            .remove_component::<ComponentRevoluteJoint>();
+        step(&mut scene);
+        assert_eq!(scene.physics.impulse_joints.len(), 0);
+    }
+
+    #[test]
+    fn mesh_collider_shapes() {
+        let mut scene = test_utils::scene();
+        // Square pyramid with a base from -1 to 1 and its apex at y = 1
+        let pyramid = scene
+            .assets()
+            .asset_registry
+            .read()
+            .load_or_create("tests/pyramid", || Mesh {
+                vertices: vec![
+                    Vec3::new(-1.0, 0.0, -1.0),
+                    Vec3::new(1.0, 0.0, -1.0),
+                    Vec3::new(1.0, 0.0, 1.0),
+                    Vec3::new(-1.0, 0.0, 1.0),
+                    Vec3::new(0.0, 1.0, 0.0),
+                ],
+                indices: vec![0, 2, 1, 0, 3, 2, 0, 1, 4, 1, 2, 4, 2, 3, 4, 3, 0, 4],
+                ..Default::default()
+            });
+        let shapes = [
+            ColliderShape::ConvexHull(pyramid.clone().into()),
+            ColliderShape::TriMesh(pyramid.clone().into()),
+            ColliderShape::Heightfield {
+                mesh: pyramid.clone().into(),
+                rows: 9,
+                columns: 9,
+            },
+        ];
+        for (i, shape) in shapes.into_iter().enumerate() {
+            let game_object = scene.create_game_object(None, None);
+            scene.set_transform(
+                game_object,
+                Transform::from_xyz(i as f32 * 5.0, 0.0, 0.0).matrix,
+            );
+            scene.add_component(
+                game_object,
+                ComponentCollider {
+                    shape,
+                    ..Default::default()
+                },
             );
-            self.accumulated_time -= Self::TIME_STEP;
+        }
+        // Colliders without a mesh are skipped
+        let empty = scene.create_game_object(None, None);
+        scene.add_component(
+            empty,
+            ComponentCollider {
+                shape: ColliderShape::TriMesh(Default::default()),
+                ..Default::default()
+            },
+        );
+        scene.prepare();
+        assert_eq!(scene.physics.colliders.len(), 3);
+        assert!(scene.physics.collider_handle(empty.entity).is_none());
+        assert_eq!(scene.physics.mesh_shapes.lock().unwrap().len(), 3);
+
+        for i in 0..3 {
+            let origin = Vec3::new(i as f32 * 5.0 + 0.25, 10.0, 0.0);
+            let hit = scene
+                .raycast(origin, -Vec3::y(), 100.0, Default::default())
+                .unwrap();
+            assert!((hit.point.y - 0.75).abs() < 1e-3);
         }
     }
 }
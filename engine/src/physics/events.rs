@@ -0,0 +1,108 @@
+use std::sync::Mutex;
+
+use nalgebra_glm::Vec3;
+use rapier3d::prelude::*;
+
+use crate::component::{Component, ComponentEventContext};
+use crate::scene::GameObject;
+
+/// Contact information passed to the collision hooks of a component.
+#[derive(Debug, Clone)]
+pub struct Collision {
+    /// The game object owning the other collider.
+    pub other: GameObject,
+    /// World-space contact normal, pointing from the other collider towards this one.
+    pub normal: Vec3,
+    /// World-space contact points.
+    pub points: Vec<Vec3>,
+    /// Sum of the impulses applied along the normal during the last step.
+    pub impulse: f32,
+}
+
+impl Collision {
+    pub(crate) fn from_contact_pair(
+        other: GameObject,
+        collider: ColliderHandle,
+        contact_pair: Option<&ContactPair>,
+    ) -> Self {
+        let mut collision = Self {
+            other,
+            normal: Vec3::zeros(),
+            points: Default::default(),
+            impulse: 0.0,
+        };
+        let Some(contact_pair) = contact_pair else {
+            return collision;
+        };
+        if let Some(manifold) = contact_pair.manifolds.first() {
+            // The manifold normal points from the first collider of the pair towards the second
+            collision.normal = if contact_pair.collider2 == collider {
+                manifold.data.normal
+            } else {
+                -manifold.data.normal
+            };
+        }
+        collision.points = contact_pair
+            .manifolds
+            .iter()
+            .flat_map(|m| m.data.solver_contacts.iter())
+            .map(|contact| contact.point.coords)
+            .collect();
+        collision.impulse = contact_pair.total_impulse_magnitude();
+        collision
+    }
+}
+
+pub enum PhysicsEvent {
+    CollisionEnter(Collision),
+    CollisionStay(Collision),
+    CollisionExit(Collision),
+    TriggerEnter(GameObject),
+    TriggerExit(GameObject),
+}
+
+impl PhysicsEvent {
+    pub fn dispatch(&self, component: &mut dyn Component, ctx: ComponentEventContext) {
+        match self {
+            PhysicsEvent::CollisionEnter(collision) => component.on_collision_enter(ctx, collision),
+            PhysicsEvent::CollisionStay(collision) => component.on_collision_stay(ctx, collision),
+            PhysicsEvent::CollisionExit(collision) => component.on_collision_exit(ctx, collision),
+            PhysicsEvent::TriggerEnter(other) => component.on_trigger_enter(ctx, *other),
+            PhysicsEvent::TriggerExit(other) => component.on_trigger_exit(ctx, *other),
+        }
+    }
+}
+
+/// Buffers the collision events emitted by rapier during a step.
+#[derive(Default)]
+pub(crate) struct CollisionEventCollector {
+    events: Mutex<Vec<CollisionEvent>>,
+}
+
+impl CollisionEventCollector {
+    pub fn drain(&self) -> Vec<CollisionEvent> {
+        std::mem::take(&mut *self.events.lock().unwrap())
+    }
+}
+
+impl EventHandler for CollisionEventCollector {
+    fn handle_collision_event(
+        &self,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        event: CollisionEvent,
+        _contact_pair: Option<&ContactPair>,
+    ) {
+        self.events.lock().unwrap().push(event);
+    }
+
+    fn handle_contact_force_event(
+        &self,
+        _dt: Real,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        _contact_pair: &ContactPair,
+        _total_force_magnitude: Real,
+    ) {
+    }
+}
@@ -1,7 +1,12 @@
 pub use config::*;
 pub use context::*;
 pub use debug::*;
+pub use events::*;
+pub use query::*;
 
+mod character;
 mod config;
 mod context;
 mod debug;
+mod events;
+mod query;
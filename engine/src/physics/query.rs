@@ -0,0 +1,321 @@
+use nalgebra::UnitQuaternion;
+use nalgebra_glm::Vec3;
+use rapier3d::parry::query::ShapeCastOptions;
+use rapier3d::prelude::*;
+
+use crate::component::ColliderShape;
+use crate::physics::PhysicsContext;
+use crate::scene::{GameObject, Scene};
+
+#[derive(Debug, Clone, Copy)]
+pub struct RaycastHit {
+    pub game_object: GameObject,
+    /// World-space point where the ray hit the collider.
+    pub point: Vec3,
+    /// World-space surface normal at the hit point.
+    pub normal: Vec3,
+    /// Distance along the ray from its origin to the hit point.
+    pub distance: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShapeHit {
+    pub game_object: GameObject,
+    /// World-space contact point on the collider that was hit.
+    pub point: Vec3,
+    /// World-space surface normal of the collider that was hit.
+    pub normal: Vec3,
+    /// Distance travelled by the cast shape before the hit.
+    pub distance: f32,
+}
+
+/// Restricts which colliders a scene query can hit.
+#[derive(Debug, Clone, Copy)]
+pub struct SceneQueryFilter {
+    /// Bit mask of the collision layers that can be hit.
+    pub layers: u32,
+    pub include_sensors: bool,
+    /// Game object whose rigid body and collider are ignored, usually the one running the query.
+    pub exclude: Option<GameObject>,
+}
+
+impl Default for SceneQueryFilter {
+    fn default() -> Self {
+        Self {
+            layers: u32::MAX,
+            include_sensors: false,
+            exclude: None,
+        }
+    }
+}
+
+impl SceneQueryFilter {
+    pub fn layers(layers: u32) -> Self {
+        Self {
+            layers,
+            ..Default::default()
+        }
+    }
+
+    pub fn excluding(game_object: GameObject) -> Self {
+        Self {
+            exclude: Some(game_object),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn query_filter(&self, physics: &PhysicsContext) -> QueryFilter<'static> {
+        let mut filter = QueryFilter::new().groups(InteractionGroups::new(
+            Group::ALL,
+            Group::from_bits_truncate(self.layers),
+        ));
+        if !self.include_sensors {
+            filter = filter.exclude_sensors();
+        }
+        if let Some(game_object) = self.exclude {
+            if let Some(handle) = physics.rigid_body_handle(game_object.entity) {
+                filter = filter.exclude_rigid_body(handle);
+            }
+            if let Some(handle) = physics.collider_handle(game_object.entity) {
+                filter = filter.exclude_collider(handle);
+            }
+        }
+        filter
+    }
+}
+
+impl Scene {
+    /// Casts a ray and returns the closest hit, if any.
+    pub fn raycast(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+        filter: SceneQueryFilter,
+    ) -> Option<RaycastHit> {
+        let physics = &self.physics;
+        let ray = Ray::new(origin.into(), direction.normalize());
+        physics
+            .query_pipeline
+            .cast_ray_and_get_normal(
+                &physics.bodies,
+                &physics.colliders,
+                &ray,
+                max_distance,
+                true,
+                filter.query_filter(physics),
+            )
+            .and_then(|(handle, hit)| self.raycast_hit(&ray, handle, hit))
+    }
+
+    /// Casts a ray and returns every hit, sorted by distance.
+    pub fn raycast_all(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+        filter: SceneQueryFilter,
+    ) -> Vec<RaycastHit> {
+        let physics = &self.physics;
+        let ray = Ray::new(origin.into(), direction.normalize());
+        let mut hits = Vec::new();
+        physics.query_pipeline.intersections_with_ray(
+            &physics.bodies,
+            &physics.colliders,
+            &ray,
+            max_distance,
+            true,
+            filter.query_filter(physics),
+            |handle, hit| {
+                hits.extend(self.raycast_hit(&ray, handle, hit));
+                true
+            },
+        );
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+
+    /// Returns the game objects whose colliders intersect a sphere.
+    pub fn overlap_sphere(
+        &self,
+        center: Vec3,
+        radius: f32,
+        filter: SceneQueryFilter,
+    ) -> Vec<GameObject> {
+        self.overlap_shape(
+            ColliderShape::Sphere { radius },
+            center,
+            UnitQuaternion::identity(),
+            filter,
+        )
+    }
+
+    /// Returns the game objects whose colliders intersect a shape.
+    pub fn overlap_shape(
+        &self,
+        shape: ColliderShape,
+        position: Vec3,
+        rotation: UnitQuaternion<f32>,
+        filter: SceneQueryFilter,
+    ) -> Vec<GameObject> {
+        let physics = &self.physics;
+        let Some(shape) = physics.collider_shape(self.assets(), &shape) else {
+            return Vec::new();
+        };
+        let mut game_objects = Vec::new();
+        physics.query_pipeline.intersections_with_shape(
+            &physics.bodies,
+            &physics.colliders,
+            &Isometry::from_parts(position.into(), rotation),
+            &*shape,
+            filter.query_filter(physics),
+            |handle| {
+                game_objects.extend(self.collider_game_object(handle));
+                true
+            },
+        );
+        game_objects
+    }
+
+    /// Sweeps a shape along a direction and returns the first collider it hits.
+    pub fn shape_cast(
+        &self,
+        shape: ColliderShape,
+        position: Vec3,
+        rotation: UnitQuaternion<f32>,
+        direction: Vec3,
+        max_distance: f32,
+        filter: SceneQueryFilter,
+    ) -> Option<ShapeHit> {
+        let physics = &self.physics;
+        let shape = physics.collider_shape(self.assets(), &shape)?;
+        let (handle, hit) = physics.query_pipeline.cast_shape(
+            &physics.bodies,
+            &physics.colliders,
+            &Isometry::from_parts(position.into(), rotation),
+            &direction.normalize(),
+            &*shape,
+            ShapeCastOptions::with_max_time_of_impact(max_distance),
+            filter.query_filter(physics),
+        )?;
+        let collider = &physics.colliders[handle];
+        Some(ShapeHit {
+            game_object: self.collider_game_object(handle)?,
+            point: (collider.position() * hit.witness1).coords,
+            normal: collider.position() * hit.normal1.into_inner(),
+            distance: hit.time_of_impact,
+        })
+    }
+
+    fn raycast_hit(
+        &self,
+        ray: &Ray,
+        handle: ColliderHandle,
+        hit: RayIntersection,
+    ) -> Option<RaycastHit> {
+        Some(RaycastHit {
+            game_object: self.collider_game_object(handle)?,
+            point: ray.point_at(hit.time_of_impact).coords,
+            normal: hit.normal,
+            distance: hit.time_of_impact,
+        })
+    }
+
+    fn collider_game_object(&self, handle: ColliderHandle) -> Option<GameObject> {
+        self.physics
+            .collider_entity(handle)
+            .and_then(|entity| self.get_game_object_from_entity(entity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::UnitQuaternion;
+    use nalgebra_glm::Vec3;
+
+    use super::SceneQueryFilter;
+    use crate::component::{ColliderShape, ComponentCollider, ComponentRigidBody};
+    use crate::math::Transform;
+    use crate::scene::{GameObject, Scene};
+    use crate::test_utils;
+
+    fn query_scene() -> (Scene, GameObject, GameObject) {
+        let mut scene = test_utils::scene();
+        let ground = scene.create_game_object(None, None);
+        scene.add_component(
+            ground,
+            ComponentCollider {
+                shape: ColliderShape::Cuboid {
+                    half_extents: Vec3::new(5.0, 0.5, 5.0),
+                },
+                ..Default::default()
+            },
+        );
+        let ball = scene.create_game_object(None, None);
+        scene.set_transform(ball, Transform::from_xyz(0.0, 3.0, 0.0).matrix);
+        scene.add_component(ball, ComponentRigidBody::default());
+        scene.add_component(
+            ball,
+            ComponentCollider {
+                shape: ColliderShape::Sphere { radius: 0.5 },
+                collision_layers: 0b10,
+                ..Default::default()
+            },
+        );
+        scene.prepare();
+        (scene, ground, ball)
+    }
+
+    #[test]
+    fn raycasts() {
+        let (scene, ground, ball) = query_scene();
+        let origin = Vec3::new(0.0, 10.0, 0.0);
+        let down = Vec3::new(0.0, -1.0, 0.0);
+
+        let hit = scene
+            .raycast(origin, down, 100.0, Default::default())
+            .unwrap();
+        assert_eq!(hit.game_object, ball);
+        assert!((hit.distance - 6.5).abs() < 1e-4);
+        assert!((hit.point - Vec3::new(0.0, 3.5, 0.0)).norm() < 1e-4);
+        assert!((hit.normal - Vec3::y()).norm() < 1e-4);
+
+        let hits = scene.raycast_all(origin, down, 100.0, Default::default());
+        assert_eq!(
+            hits.iter().map(|h| h.game_object).collect::<Vec<_>>(),
+            [ball, ground]
+        );
+
+        let hit = scene.raycast(origin, down, 100.0, SceneQueryFilter::layers(0b01));
+        assert_eq!(hit.map(|h| h.game_object), Some(ground));
+        let hit = scene.raycast(origin, down, 100.0, SceneQueryFilter::excluding(ball));
+        assert_eq!(hit.map(|h| h.game_object), Some(ground));
+        assert!(scene
+            .raycast(origin, down, 5.0, Default::default())
+            .is_none());
+    }
+
+    #[test]
+    fn shape_queries() {
+        let (scene, ground, ball) = query_scene();
+        let overlaps = scene.overlap_sphere(Vec3::new(0.0, 2.0, 0.0), 1.0, Default::default());
+        assert_eq!(overlaps, [ball]);
+        let overlaps = scene.overlap_sphere(Vec3::new(4.0, 0.0, 4.0), 1.0, Default::default());
+        assert_eq!(overlaps, [ground]);
+
+        let hit = scene
+            .shape_cast(
+                ColliderShape::Sphere { radius: 0.5 },
+                Vec3::new(4.0, 10.0, 0.0),
+                UnitQuaternion::identity(),
+                Vec3::new(0.0, -1.0, 0.0),
+                100.0,
+                Default::default(),
+            )
+            .unwrap();
+        assert_eq!(hit.game_object, ground);
+        assert!((hit.distance - 9.0).abs() < 1e-3);
+        assert!((hit.normal - Vec3::y()).norm() < 1e-3);
+        assert!((hit.point.y - 0.5).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,37 @@
+use std::collections::{BTreeMap, HashMap};
+
+use engine_derive::reflect_trait;
+
+use crate as engine;
+use crate::reflect::Reflect;
+
+/// Gives access to the reflected values held by a collection, for the types whose elements
+/// can't be reached as struct fields.
+#[reflect_trait]
+pub trait Container {
+    fn elements(&self) -> Vec<&dyn Reflect>;
+}
+
+impl<T: Reflect> Container for Vec<T> {
+    fn elements(&self) -> Vec<&dyn Reflect> {
+        self.iter().map(|value| value.as_reflect()).collect()
+    }
+}
+
+impl<T: Reflect> Container for Option<T> {
+    fn elements(&self) -> Vec<&dyn Reflect> {
+        self.iter().map(|value| value.as_reflect()).collect()
+    }
+}
+
+impl<K: Send + Sync + 'static, T: Reflect> Container for HashMap<K, T> {
+    fn elements(&self) -> Vec<&dyn Reflect> {
+        self.values().map(|value| value.as_reflect()).collect()
+    }
+}
+
+impl<K: Send + Sync + 'static, T: Reflect> Container for BTreeMap<K, T> {
+    fn elements(&self) -> Vec<&dyn Reflect> {
+        self.values().map(|value| value.as_reflect()).collect()
+    }
+}
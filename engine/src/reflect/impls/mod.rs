@@ -1,8 +1,10 @@
+pub use container::{Container, ReflectContainer};
 pub use std_default::ReflectDefault;
 pub use std_float::ReflectGenericFloat;
 pub use std_int::ReflectGenericInt;
 
 mod color32;
+mod container;
 mod glm;
 mod rapier;
 mod std;
@@ -1,3 +1,5 @@
+use std::collections::BTreeSet;
+
 use engine_derive::impl_reflect_value;
 
 use crate as engine;
@@ -27,3 +29,4 @@ impl_reflect_value!(f32(Default, GenericFloat));
 impl_reflect_value!(f64(Default, GenericFloat));
 
 impl_reflect_value!(String(Default));
+impl_reflect_value!(BTreeSet<String>(Default));
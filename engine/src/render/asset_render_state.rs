@@ -27,7 +27,7 @@ impl AssetRenderState {
         LockedAssetRenderState {
             mesh_instance_groups: meshes
                 .iter()
-                .map(|(id, m)| (*id, m.instance_bind_group(device)))
+                .filter_map(|(id, m)| Some((*id, m.instance_bind_group(device)?)))
                 .collect(),
             meshes,
             materials: self.materials.lock_read(),
@@ -67,8 +67,8 @@ impl LockedAssetRenderState<'_> {
     pub fn mesh(&self, id: AssetId) -> &RwLockReadGuard<Mesh> {
         self.meshes.get(&id).unwrap()
     }
-    pub fn mesh_instance_group(&self, id: AssetId) -> &wgpu::BindGroup {
-        self.mesh_instance_groups.get(&id).unwrap()
+    pub fn mesh_instance_group(&self, id: AssetId) -> Option<&wgpu::BindGroup> {
+        self.mesh_instance_groups.get(&id)
     }
     pub fn material(&self, id: AssetId) -> &RwLockReadGuard<Material> {
         self.materials.get(&id).unwrap()
@@ -9,6 +9,8 @@ pub struct Camera {
     pub fov_x: f32,
     pub near_plane: f32,
     pub far_plane: f32,
+    /// Mask of the layers of the game objects to render.
+    pub culling_mask: u32,
 }
 
 impl Default for Camera {
@@ -25,6 +27,7 @@ impl Camera {
             fov_x,
             near_plane,
             far_plane,
+            culling_mask: u32::MAX,
         };
         camera.update_projection();
         camera
@@ -145,8 +145,8 @@ impl GizmoRenderer {
 
             wire_circle_mesh: game.asset_registry.read().wire_circle(),
             wire_cube_mesh: game.asset_registry.read().wire_cube(),
-            lines_mesh: Mesh::new(&render_context),
-            points_mesh: Mesh::new(&render_context),
+            lines_mesh: Mesh::default(),
+            points_mesh: Mesh::default(),
 
             shader,
             gizmo_bind_group,
@@ -0,0 +1,255 @@
+//! Helpers for comparing rendered images against stored golden PNGs.
+//!
+//! Set the `CALYX_UPDATE_GOLDEN` environment variable to (re)write the golden
+//! images from the current output instead of comparing against them.
+
+use std::path::Path;
+
+use image::RgbaImage;
+
+use crate::error::BoxedError;
+
+pub const UPDATE_GOLDEN_ENV: &str = "CALYX_UPDATE_GOLDEN";
+
+#[derive(Debug, Clone, Copy)]
+pub struct Tolerance {
+    /// Largest per-channel difference for two pixels to be considered equal.
+    pub channel: u8,
+    /// Fraction of pixels, between 0 and 1, allowed to differ by more than `channel`.
+    pub max_mismatched_ratio: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            channel: 2,
+            max_mismatched_ratio: 0.001,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageDiff {
+    pub mismatched_pixels: usize,
+    pub total_pixels: usize,
+    pub max_channel_difference: u8,
+}
+
+impl ImageDiff {
+    pub fn mismatched_ratio(&self) -> f32 {
+        if self.total_pixels == 0 {
+            return 0.0;
+        }
+        self.mismatched_pixels as f32 / self.total_pixels as f32
+    }
+
+    pub fn is_within(&self, tolerance: Tolerance) -> bool {
+        self.mismatched_ratio() <= tolerance.max_mismatched_ratio
+    }
+}
+
+/// Compares two images pixel by pixel. Fails if their dimensions differ.
+pub fn compare_images(
+    actual: &RgbaImage,
+    expected: &RgbaImage,
+    tolerance: Tolerance,
+) -> Result<ImageDiff, BoxedError> {
+    if actual.dimensions() != expected.dimensions() {
+        return Err(format!(
+            "image size {:?} does not match expected size {:?}",
+            actual.dimensions(),
+            expected.dimensions()
+        )
+        .into());
+    }
+    let mut diff = ImageDiff {
+        mismatched_pixels: 0,
+        total_pixels: (actual.width() * actual.height()) as usize,
+        max_channel_difference: 0,
+    };
+    for (a, e) in actual.pixels().zip(expected.pixels()) {
+        let difference =
+            a.0.iter()
+                .zip(e.0.iter())
+                .map(|(a, e)| a.abs_diff(*e))
+                .max()
+                .unwrap_or_default();
+        diff.max_channel_difference = diff.max_channel_difference.max(difference);
+        if difference > tolerance.channel {
+            diff.mismatched_pixels += 1;
+        }
+    }
+    Ok(diff)
+}
+
+/// Compares an image against the golden PNG at `path`. On mismatch, the actual
+/// image is written next to the golden one with an `.actual.png` extension.
+pub fn check_golden(
+    actual: &RgbaImage,
+    path: impl AsRef<Path>,
+    tolerance: Tolerance,
+) -> Result<ImageDiff, BoxedError> {
+    let path = path.as_ref();
+    if std::env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(Box::new)?;
+        }
+        actual.save(path).map_err(Box::new)?;
+        return compare_images(actual, actual, tolerance);
+    }
+    let expected = image::open(path)
+        .map_err(|e| {
+            format!(
+                "unable to open golden image {:?} ({}), run with {}=1 to create it",
+                path, e, UPDATE_GOLDEN_ENV
+            )
+        })?
+        .to_rgba8();
+    let diff = compare_images(actual, &expected, tolerance)?;
+    if !diff.is_within(tolerance) {
+        actual
+            .save(path.with_extension("actual.png"))
+            .map_err(Box::new)?;
+        return Err(format!(
+            "image does not match golden {:?}: {} of {} pixels differ (max channel difference {})",
+            path, diff.mismatched_pixels, diff.total_pixels, diff.max_channel_difference
+        )
+        .into());
+    }
+    Ok(diff)
+}
+
+/// Panicking version of [`check_golden`] for use in tests.
+pub fn assert_golden(actual: &RgbaImage, path: impl AsRef<Path>, tolerance: Tolerance) {
+    if let Err(err) = check_golden(actual, path, tolerance) {
+        panic!("{}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use egui::Color32;
+    use image::{Rgba, RgbaImage};
+
+    use super::{assert_golden, compare_images, Tolerance};
+    use crate::assets::material::Material;
+    use crate::assets::mesh::Mesh;
+    use crate::component::{ComponentDirectionalLight, ComponentMesh};
+    use crate::context::AssetContext;
+    use crate::math::Transform;
+    use crate::render::{
+        Camera, RenderContext, RenderUtils, SceneRenderer, SceneRendererOptions, Shader,
+    };
+
+    /// Renders a small scene with a GPU device, software ones included, comparing it against
+    /// `tests/golden/{name}.png`. Skipped when no wgpu adapter is available.
+    fn render_golden(name: &str, gizmos: bool) {
+        let render_context = match RenderContext::headless() {
+            Ok(render_context) => render_context,
+            Err(err) => {
+                eprintln!("skipping golden image {}: {}", name, err);
+                return;
+            }
+        };
+        let assets_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets");
+        let assets =
+            AssetContext::with_render_context(render_context, &assets_path, &assets_path).unwrap();
+        let context = assets.lock_read();
+        let (mesh, material) = {
+            let registry = assets.asset_registry.read();
+            let shader = registry.load::<Shader>("shaders/pbr").unwrap();
+            let material = registry
+                .create(
+                    format!("tests/golden/{}", name),
+                    Material::from_shader(&context, shader),
+                )
+                .unwrap();
+            (registry.load::<Mesh>("meshes/cube").unwrap(), material)
+        };
+
+        let mut scene = assets.scene();
+        let cube = scene.create_game_object(None, None);
+        scene.add_component(
+            cube,
+            ComponentMesh {
+                mesh: Some(mesh).into(),
+                material: Some(material).into(),
+            },
+        );
+        let mut light_transform = Transform::from_xyz(2.0, 3.0, 4.0);
+        light_transform.look_at(&Default::default());
+        let light = scene.create_game_object(None, None);
+        scene.set_transform(light, light_transform.matrix);
+        scene.add_component(light, ComponentDirectionalLight::default());
+
+        let mut renderer = SceneRenderer::new(
+            &context,
+            SceneRendererOptions {
+                grid: false,
+                gizmos,
+                clear_color: Color32::from_rgb(20, 30, 40),
+                samples: 1,
+            },
+        );
+        renderer.resize_textures(64, 64);
+        let camera = Camera::new(1.0, 60f32.to_radians(), 0.1, 100.0);
+        let mut camera_transform = Transform::from_xyz(2.0, 2.0, 3.0);
+        camera_transform.look_at(&Default::default());
+        let image = renderer.render_to_image(&scene, &camera, &camera_transform);
+
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(name)
+            .with_extension("png");
+        assert_golden(&image, path, Tolerance::default());
+    }
+
+    #[test]
+    fn golden_pbr() {
+        render_golden("pbr", false);
+    }
+
+    #[test]
+    fn golden_gizmos() {
+        render_golden("gizmos", true);
+    }
+
+    #[test]
+    fn compare_images_within_tolerance() {
+        let expected = RgbaImage::from_pixel(4, 4, Rgba([100, 100, 100, 255]));
+        let mut actual = expected.clone();
+        actual.put_pixel(0, 0, Rgba([102, 100, 99, 255]));
+        actual.put_pixel(1, 0, Rgba([120, 100, 100, 255]));
+
+        let tolerance = Tolerance {
+            channel: 2,
+            max_mismatched_ratio: 0.1,
+        };
+        let diff = compare_images(&actual, &expected, tolerance).unwrap();
+        assert_eq!(diff.mismatched_pixels, 1);
+        assert_eq!(diff.total_pixels, 16);
+        assert_eq!(diff.max_channel_difference, 20);
+        assert!(diff.is_within(tolerance));
+        assert!(!diff.is_within(Tolerance {
+            max_mismatched_ratio: 0.0,
+            ..tolerance
+        }));
+    }
+
+    #[test]
+    fn compare_images_size_mismatch() {
+        let a = RgbaImage::new(4, 4);
+        let b = RgbaImage::new(4, 2);
+        assert!(compare_images(&a, &b, Tolerance::default()).is_err());
+    }
+
+    #[test]
+    fn unpack_rg11b10_ufloat() {
+        // r = 1.0 (exponent 15), g = 0.5 (exponent 14), b = 2.0 (exponent 16)
+        let packed = (15 << 6) | ((14 << 6) << 11) | ((16 << 5) << 22);
+        assert_eq!(RenderUtils::unpack_rg11b10_ufloat(packed), [1.0, 0.5, 2.0]);
+        assert_eq!(RenderUtils::unpack_rg11b10_ufloat(0), [0.0, 0.0, 0.0]);
+    }
+}
@@ -16,6 +16,7 @@ pub mod buffer;
 mod camera;
 mod gizmo_renderer;
 mod gizmos;
+pub mod golden;
 mod pipeline_options;
 pub mod postprocess;
 mod render_context;
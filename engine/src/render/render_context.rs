@@ -1,43 +1,137 @@
 use std::sync::Arc;
 
+use crate::error::BoxedError;
 use crate::render::{PipelineOptionsBuilder, RenderUtils};
 use egui::epaint;
 use egui_wgpu::{wgpu, Renderer};
 
 pub struct RenderContext {
-    render_state: egui_wgpu::RenderState,
+    render_state: Option<egui_wgpu::RenderState>,
     texture_manager: Arc<epaint::mutex::RwLock<epaint::TextureManager>>,
 }
 
 impl RenderContext {
+    pub const REQUIRED_FEATURES: wgpu::Features =
+        wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+            .union(wgpu::Features::POLYGON_MODE_LINE)
+            .union(wgpu::Features::CLEAR_TEXTURE)
+            .union(wgpu::Features::FLOAT32_FILTERABLE)
+            .union(wgpu::Features::DEPTH32FLOAT_STENCIL8)
+            .union(wgpu::Features::BUFFER_BINDING_ARRAY)
+            .union(wgpu::Features::TEXTURE_BINDING_ARRAY)
+            .union(wgpu::Features::STORAGE_RESOURCE_BINDING_ARRAY)
+            .union(wgpu::Features::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING);
+
     pub fn from_eframe(cc: &eframe::CreationContext) -> Self {
         Self {
-            render_state: cc
-                .wgpu_render_state
-                .clone()
-                .expect("eframe context not using wgpu"),
+            render_state: Some(
+                cc.wgpu_render_state
+                    .clone()
+                    .expect("eframe context not using wgpu"),
+            ),
             texture_manager: cc.egui_ctx.tex_manager(),
         }
     }
 
+    /// Creates a render context that owns its wgpu device, without any window or surface.
+    /// A hardware adapter is preferred, but a fallback (software) adapter is accepted, as long
+    /// as it supports all of [`Self::REQUIRED_FEATURES`].
+    pub fn headless() -> Result<Self, BoxedError> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let mut missing_features = None;
+        let adapter = [false, true]
+            .into_iter()
+            .filter_map(|force_fallback_adapter| {
+                pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::HighPerformance,
+                    force_fallback_adapter,
+                    compatible_surface: None,
+                }))
+            })
+            .find(|adapter| {
+                let missing = Self::REQUIRED_FEATURES.difference(adapter.features());
+                if !missing.is_empty() {
+                    missing_features = Some((adapter.get_info().name, missing));
+                }
+                missing.is_empty()
+            });
+        let adapter = match (adapter, missing_features) {
+            (Some(adapter), _) => adapter,
+            (None, Some((name, missing))) => {
+                return Err(format!(
+                    "wgpu adapter '{}' is missing required features: {:?}",
+                    name, missing
+                )
+                .into())
+            }
+            (None, None) => return Err("no suitable wgpu adapter found".into()),
+        };
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("headless_device"),
+                required_features: Self::REQUIRED_FEATURES,
+                required_limits: Self::required_limits(),
+                ..Default::default()
+            },
+            None,
+        ))
+        .map_err(Box::new)?;
+        let target_format = wgpu::TextureFormat::Rgba8Unorm;
+        let renderer = Renderer::new(&device, target_format, None, 1, false);
+        Ok(Self {
+            render_state: Some(egui_wgpu::RenderState {
+                available_adapters: vec![adapter.clone()],
+                adapter,
+                device,
+                queue,
+                target_format,
+                renderer: Arc::new(epaint::mutex::RwLock::new(renderer)),
+            }),
+            texture_manager: Default::default(),
+        })
+    }
+
+    /// Creates a render context without any GPU device. GPU-backed asset types
+    /// (shaders, textures, materials, skyboxes) cannot be loaded with this context.
+    pub fn without_gpu() -> Self {
+        Self {
+            render_state: None,
+            texture_manager: Default::default(),
+        }
+    }
+
+    pub fn required_limits() -> wgpu::Limits {
+        wgpu::Limits {
+            max_storage_textures_per_shader_stage: 5,
+            max_uniform_buffers_per_shader_stage: 30,
+            ..Default::default()
+        }
+    }
+
+    pub fn has_gpu(&self) -> bool {
+        self.render_state.is_some()
+    }
+
     pub fn render_state(&self) -> &egui_wgpu::RenderState {
-        &self.render_state
+        self.render_state
+            .as_ref()
+            .expect("render context has no GPU device")
     }
 
     pub fn device(&self) -> &wgpu::Device {
-        &self.render_state.device
+        &self.render_state().device
     }
 
     pub fn queue(&self) -> &wgpu::Queue {
-        &self.render_state.queue
+        &self.render_state().queue
     }
 
     pub fn renderer(&self) -> Arc<epaint::mutex::RwLock<Renderer>> {
-        self.render_state.renderer.clone()
+        self.render_state().renderer.clone()
     }
 
     pub fn target_format(&self) -> wgpu::TextureFormat {
-        self.render_state.target_format
+        self.render_state().target_format
     }
 
     pub fn texture_manager(&self) -> Arc<epaint::mutex::RwLock<epaint::TextureManager>> {
@@ -115,4 +115,24 @@ impl RenderUtils {
         Self::bind_mesh_buffers(render_pass, mesh);
         Self::draw_mesh_instanced(render_pass, mesh, 0..(mesh.instances.len() as u32));
     }
+
+    /// Decodes a texel of a `Rg11b10Ufloat` texture into linear RGB values.
+    pub fn unpack_rg11b10_ufloat(packed: u32) -> [f32; 3] {
+        fn unpack(bits: u32, mantissa_bits: u32) -> f32 {
+            let mantissa = (bits & ((1 << mantissa_bits) - 1)) as f32;
+            let exponent = (bits >> mantissa_bits) & 0x1f;
+            let scale = (1 << mantissa_bits) as f32;
+            match exponent {
+                0 => mantissa / scale * 2f32.powi(-14),
+                31 if mantissa == 0.0 => f32::INFINITY,
+                31 => f32::NAN,
+                _ => 2f32.powi(exponent as i32 - 15) * (1.0 + mantissa / scale),
+            }
+        }
+        [
+            unpack(packed & 0x7ff, 6),
+            unpack((packed >> 11) & 0x7ff, 6),
+            unpack((packed >> 22) & 0x3ff, 5),
+        ]
+    }
 }
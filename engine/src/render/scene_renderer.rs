@@ -5,8 +5,8 @@ use crate::assets::skybox::SkyboxShaders;
 use crate::assets::texture::Texture;
 use crate::assets::AssetId;
 use crate::component::{
-    ComponentDirectionalLight, ComponentMesh, ComponentPointLight, ComponentSkinnedMesh,
-    ComponentSkyLight,
+    ComponentDirectionalLight, ComponentID, ComponentMesh, ComponentPointLight,
+    ComponentSkinnedMesh, ComponentSkyLight,
 };
 use crate::context::ReadOnlyAssetContext;
 use crate::core::Ref;
@@ -15,11 +15,12 @@ use crate::render::asset_render_state::AssetRenderState;
 use crate::render::buffer::ResizableBuffer;
 use crate::render::render_utils::RenderUtils;
 use crate::render::{Camera, GizmoRenderer, PipelineOptions, Shader};
-use crate::scene::Scene;
+use crate::scene::{GameObject, Scene};
+use crate::utils::TypeUuid;
 use egui::Color32;
 use egui_wgpu::wgpu::util::DeviceExt;
 use egui_wgpu::{wgpu, RenderState};
-use legion::{Entity, IntoQuery};
+use image::{Rgba, RgbaImage};
 use nalgebra_glm as glm;
 use nalgebra_glm::{Mat4, Vec3};
 use rapier3d::pipeline::DebugRenderPipeline;
@@ -62,14 +63,14 @@ struct PointLight {
     position: [f32; 3],
     radius: f32,
     color: [f32; 3],
-    _padding: f32,
+    layer_mask: u32,
 }
 
 #[repr(C)]
 #[derive(Default, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 struct DirectionalLight {
     direction: [f32; 3],
-    _padding: f32,
+    layer_mask: u32,
     color: [f32; 3],
     _padding2: f32,
 }
@@ -88,6 +89,7 @@ pub struct DrawListElement {
     mat_id: AssetId,
     mesh_id: AssetId,
     bone_transform_index: i32,
+    layers: u32,
     transform: [[f32; 4]; 4],
 }
 
@@ -243,12 +245,31 @@ impl SceneRenderer {
         camera_transform: &Transform,
         scene: &Scene,
         physics_debug_pipeline: Option<&mut DebugRenderPipeline>,
+    ) {
+        self.render_scenes(
+            render_state,
+            camera,
+            camera_transform,
+            &[scene],
+            physics_debug_pipeline,
+        );
+    }
+
+    /// Renders several scenes together, like the ones loaded in a
+    /// [`SceneManager`](crate::scene::SceneManager). Gizmos are drawn for the first scene only.
+    pub fn render_scenes(
+        &mut self,
+        render_state: &RenderState,
+        camera: &Camera,
+        camera_transform: &Transform,
+        scenes: &[&Scene],
+        physics_debug_pipeline: Option<&mut DebugRenderPipeline>,
     ) {
         let queue = &render_state.queue;
         let device = &render_state.device;
 
         self.load_camera_uniforms(queue, camera, camera_transform);
-        if self.options.gizmos {
+        if let (true, Some(scene)) = (self.options.gizmos, scenes.first()) {
             self.gizmo_renderer.draw_gizmos(
                 device,
                 queue,
@@ -261,7 +282,7 @@ impl SceneRenderer {
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("encoder"),
         });
-        self.render_meshes(render_state, scene, &mut encoder);
+        self.render_meshes(render_state, scenes, camera.culling_mask, &mut encoder);
         self.render_skybox(render_state, &mut encoder);
         if self.options.grid {
             self.render_grid(render_state, &mut encoder);
@@ -287,6 +308,78 @@ impl SceneRenderer {
         queue.submit(Some(encoder.finish()));
     }
 
+    /// Renders the scene and reads the resolved scene texture back to the CPU.
+    /// The image has the size of the scene texture, see [`SceneRenderer::resize_textures`].
+    pub fn render_to_image(
+        &mut self,
+        scene: &Scene,
+        camera: &Camera,
+        camera_transform: &Transform,
+    ) -> RgbaImage {
+        let render_context = self.asset_context.render_context.clone();
+        let render_state = render_context.render_state();
+        self.render_scene(render_state, camera, camera_transform, scene, None);
+
+        let device = &render_state.device;
+        let queue = &render_state.queue;
+        let size = self.scene_texture.descriptor.size;
+        let bytes_per_pixel = 4;
+        let padded_bytes_per_row =
+            (size.width * bytes_per_pixel).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("scene_readback_buffer"),
+            size: (padded_bytes_per_row * size.height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("readback_encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.scene_texture.texture,
+                mip_level: 0,
+                origin: Default::default(),
+                aspect: Default::default(),
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(size.height),
+                },
+            },
+            size,
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |result| {
+            result.expect("failed to map scene readback buffer")
+        });
+        device.poll(wgpu::Maintain::Wait);
+
+        let mut image = RgbaImage::new(size.width, size.height);
+        {
+            let data = slice.get_mapped_range();
+            for (y, row) in data.chunks_exact(padded_bytes_per_row as usize).enumerate() {
+                for (x, texel) in row
+                    .chunks_exact(bytes_per_pixel as usize)
+                    .take(size.width as usize)
+                    .enumerate()
+                {
+                    let packed = u32::from_le_bytes([texel[0], texel[1], texel[2], texel[3]]);
+                    let [r, g, b] = RenderUtils::unpack_rg11b10_ufloat(packed)
+                        .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+                    image.put_pixel(x as u32, y as u32, Rgba([r, g, b, 255]));
+                }
+            }
+        }
+        buffer.unmap();
+        image
+    }
+
     fn scene_bind_group(
         &self,
         device: &wgpu::Device,
@@ -356,7 +449,8 @@ impl SceneRenderer {
     fn render_meshes(
         &mut self,
         render_state: &RenderState,
-        scene: &Scene,
+        scenes: &[&Scene],
+        culling_mask: u32,
         encoder: &mut wgpu::CommandEncoder,
     ) {
         let device = &render_state.device;
@@ -372,10 +466,10 @@ impl SceneRenderer {
             })])
             .build()
             .expect("invalid builder options");
-        self.build_asset_data(render_state, scene, &options);
+        self.build_asset_data(render_state, scenes, culling_mask, &options);
         let draw_list = self.build_draw_list();
         self.build_mesh_data(render_state);
-        self.build_light_data(render_state, scene);
+        self.build_light_data(render_state, scenes);
         let assets = self.assets.lock(device);
         let material_bind_groups = self.build_material_bind_groups(device, &assets);
         let black_texture_cube = self.default_assets.black_texture_cube.read();
@@ -420,6 +514,9 @@ impl SceneRenderer {
             for (shader_id, mat_id, mesh_id, instances) in draw_list {
                 let shader = assets.shader(shader_id);
                 let mesh = assets.mesh(mesh_id);
+                let Some(instance_group) = assets.mesh_instance_group(mesh_id) else {
+                    continue;
+                };
                 if shader_id != last.0 {
                     if let Some(pipeline) = shader.get_pipeline(&options) {
                         render_pass.set_pipeline(pipeline);
@@ -435,7 +532,7 @@ impl SceneRenderer {
                     }
                 }
                 if mesh_id != last.2 {
-                    render_pass.set_bind_group(1, assets.mesh_instance_group(mesh_id), &[]);
+                    render_pass.set_bind_group(1, instance_group, &[]);
                 }
                 RenderUtils::bind_mesh_buffers(&mut render_pass, mesh);
                 RenderUtils::draw_mesh_instanced(&mut render_pass, mesh, instances);
@@ -497,6 +594,7 @@ impl SceneRenderer {
                     1,
                     Instance {
                         bone_transform_index: -1,
+                        layers: u32::MAX,
                         _padding: Default::default(),
                         transform: Mat4::identity().into(),
                     },
@@ -604,6 +702,7 @@ impl SceneRenderer {
             mat_id,
             mesh_id,
             bone_transform_index,
+            layers,
             transform,
         } in self.draw_list.drain(0..)
         {
@@ -615,6 +714,7 @@ impl SceneRenderer {
             if let Some(ref mut mesh) = &mut mesh {
                 mesh.instances.push(Instance {
                     bone_transform_index,
+                    layers,
                     _padding: Default::default(),
                     transform,
                 });
@@ -631,6 +731,7 @@ impl SceneRenderer {
         mesh_ref: &Ref<Mesh>,
         mat_ref: &Ref<Material>,
         bone_transform_index: Option<i32>,
+        layers: u32,
         transform: [[f32; 4]; 4],
     ) {
         let Some(shader_ref) = mat_ref.read().shader.get_ref(&self.asset_context) else {
@@ -641,6 +742,7 @@ impl SceneRenderer {
             mat_id: mat_ref.id(),
             mesh_id: mesh_ref.id(),
             bone_transform_index: bone_transform_index.unwrap_or(-1),
+            layers,
             transform,
         });
         self.assets
@@ -660,77 +762,98 @@ impl SceneRenderer {
     fn build_asset_data(
         &mut self,
         render_state: &RenderState,
-        scene: &Scene,
+        scenes: &[&Scene],
+        culling_mask: u32,
         render_options: &PipelineOptions,
     ) {
-        let world = &scene.world;
         self.draw_list.clear();
-        let mut query = <(Entity, &ComponentMesh)>::query();
-        for (entity, c_mesh) in query.iter(world) {
-            let Some(game_object) = scene.get_game_object_from_entity(*entity) else {
-                continue;
-            };
-            let Some(mesh_ref) = c_mesh.mesh.get_ref(&self.asset_context) else {
-                continue;
-            };
-            let Some(mat_ref) = c_mesh.material.get_ref(&self.asset_context) else {
-                continue;
-            };
-            let transform = scene.get_world_transform(game_object);
-            self.insert_draw_list_entry(&mesh_ref, &mat_ref, None, transform.matrix.into());
-        }
         let mut skinned_meshes: HashSet<Uuid> = Default::default();
-        let mut query = <(Entity, &ComponentSkinnedMesh)>::query();
-        for (entity, c_skinned_mesh) in query.iter(world) {
-            let Some(game_object) = scene.get_game_object_from_entity(*entity) else {
-                continue;
-            };
-            let Some(mesh_ref) = c_skinned_mesh.mesh.get_ref(&self.asset_context) else {
-                continue;
-            };
-            let Some(mat_ref) = c_skinned_mesh.material.get_ref(&self.asset_context) else {
-                continue;
+        let mut skybox = None;
+        for scene in scenes {
+            let visible = |game_object: GameObject, c_id: &ComponentID, component_id: Uuid| {
+                c_id.layers & culling_mask != 0
+                    && c_id.is_component_enabled(component_id)
+                    && scene.is_active_in_hierarchy(game_object)
             };
-            let mesh_id = mesh_ref.id();
-            let transform = scene.get_world_transform(game_object);
-            let bone_transform_index;
+            for (game_object, (c_mesh, c_id)) in scene
+                .query::<(&ComponentMesh, &ComponentID)>()
+                .iter()
+                .filter(|(go, (_, c_id))| visible(*go, c_id, ComponentMesh::type_uuid()))
             {
-                let mut mesh = mesh_ref.write();
-                if !skinned_meshes.contains(&mesh_id) {
-                    mesh.bone_transforms.clear();
+                let Some(mesh_ref) = c_mesh.mesh.get_ref(&self.asset_context) else {
+                    continue;
+                };
+                let Some(mat_ref) = c_mesh.material.get_ref(&self.asset_context) else {
+                    continue;
+                };
+                let transform = scene.get_world_transform(game_object);
+                self.insert_draw_list_entry(
+                    &mesh_ref,
+                    &mat_ref,
+                    None,
+                    c_id.layers,
+                    transform.matrix.into(),
+                );
+            }
+            for (game_object, (c_skinned_mesh, c_id)) in scene
+                .query::<(&ComponentSkinnedMesh, &ComponentID)>()
+                .iter()
+                .filter(|(go, (_, c_id))| visible(*go, c_id, ComponentSkinnedMesh::type_uuid()))
+            {
+                let Some(mesh_ref) = c_skinned_mesh.mesh.get_ref(&self.asset_context) else {
+                    continue;
+                };
+                let Some(mat_ref) = c_skinned_mesh.material.get_ref(&self.asset_context) else {
+                    continue;
+                };
+                let mesh_id = mesh_ref.id();
+                let transform = scene.get_world_transform(game_object);
+                let bone_transform_index;
+                {
+                    let mut mesh = mesh_ref.write();
+                    if !skinned_meshes.contains(&mesh_id) {
+                        mesh.bone_transforms.clear();
+                    }
+                    skinned_meshes.insert(mesh_id);
+                    bone_transform_index = mesh.bone_transforms.len() / mesh.bones.len();
+                    mesh.bone_transforms
+                        .extend(c_skinned_mesh.bone_transforms.iter().copied());
                 }
-                skinned_meshes.insert(mesh_id);
-                bone_transform_index = mesh.bone_transforms.len() / mesh.bones.len();
-                mesh.bone_transforms
-                    .extend(c_skinned_mesh.bone_transforms.iter().copied());
+                self.insert_draw_list_entry(
+                    &mesh_ref,
+                    &mat_ref,
+                    Some(bone_transform_index as i32),
+                    c_id.layers,
+                    transform.matrix.into(),
+                );
+            }
+            for (_, c_sky_light) in scene
+                .query::<&ComponentSkyLight>()
+                .iter()
+                .filter(|(go, s)| {
+                    s.active
+                        && scene.is_component_enabled::<ComponentSkyLight>(*go)
+                        && scene.is_active_in_hierarchy(*go)
+                })
+            {
+                let Some(skybox_ref) = c_sky_light.skybox.get_ref(&self.asset_context) else {
+                    continue;
+                };
+                let skybox_id = skybox_ref.id();
+                self.assets
+                    .skyboxes
+                    .entry(skybox_id)
+                    .or_insert(skybox_ref.clone());
+                self.assets
+                    .meshes
+                    .entry(self.default_assets.cube.id())
+                    .or_insert(self.default_assets.cube.clone());
+                self.assets
+                    .meshes
+                    .entry(self.default_assets.screen_space_quad.id())
+                    .or_insert(self.default_assets.screen_space_quad.clone());
+                skybox = Some(skybox_id);
             }
-            self.insert_draw_list_entry(
-                &mesh_ref,
-                &mat_ref,
-                Some(bone_transform_index as i32),
-                transform.matrix.into(),
-            );
-        }
-        let mut query = <&ComponentSkyLight>::query();
-        let mut skybox = None;
-        for c_sky_light in query.iter(world).filter(|s| s.active) {
-            let Some(skybox_ref) = c_sky_light.skybox.get_ref(&self.asset_context) else {
-                continue;
-            };
-            let skybox_id = skybox_ref.id();
-            self.assets
-                .skyboxes
-                .entry(skybox_id)
-                .or_insert(skybox_ref.clone());
-            self.assets
-                .meshes
-                .entry(self.default_assets.cube.id())
-                .or_insert(self.default_assets.cube.clone());
-            self.assets
-                .meshes
-                .entry(self.default_assets.screen_space_quad.id())
-                .or_insert(self.default_assets.screen_space_quad.clone());
-            skybox = Some(skybox_id);
         }
         self.skybox = skybox;
         for (_, mut mesh) in self.assets.meshes.lock_write() {
@@ -788,11 +911,14 @@ impl SceneRenderer {
         }
     }
 
-    fn build_light_data(&mut self, render_state: &RenderState, scene: &Scene) {
+    fn build_light_data(&mut self, render_state: &RenderState, scenes: &[&Scene]) {
         let device = &render_state.device;
         let queue = &render_state.queue;
 
-        let point_lights = Self::collect_point_lights(scene);
+        let point_lights: Vec<_> = scenes
+            .iter()
+            .flat_map(|scene| Self::collect_point_lights(scene))
+            .collect();
         let size =
             (16 + std::cmp::max(point_lights.len(), 1) * std::mem::size_of::<PointLight>()) as u64;
         self.point_light_storage_buffer.resize(device, size);
@@ -811,7 +937,10 @@ impl SceneRenderer {
             );
         }
 
-        let directional_lights = Self::collect_directional_lights(scene);
+        let directional_lights: Vec<_> = scenes
+            .iter()
+            .flat_map(|scene| Self::collect_directional_lights(scene))
+            .collect();
         let size = (16
             + std::cmp::max(directional_lights.len(), 1) * std::mem::size_of::<DirectionalLight>())
             as u64;
@@ -834,22 +963,22 @@ impl SceneRenderer {
 
     fn collect_point_lights(scene: &Scene) -> Vec<PointLight> {
         let mut point_lights = Vec::new();
-        let mut query = <(Entity, &ComponentPointLight)>::query();
-        for (game_object, light) in query
-            .iter(&scene.world)
-            .filter(|(_, light)| light.active)
-            .filter_map(|(entity, light)| {
-                scene
-                    .get_game_object_from_entity(*entity)
-                    .map(|go| (go, light))
-            })
+        for (game_object, light) in
+            scene
+                .query::<&ComponentPointLight>()
+                .iter()
+                .filter(|(go, light)| {
+                    light.active
+                        && scene.is_component_enabled::<ComponentPointLight>(*go)
+                        && scene.is_active_in_hierarchy(*go)
+                })
         {
             let color = light.color.to_normalized_gamma_f32();
             point_lights.push(PointLight {
                 color: [color[0], color[1], color[2]],
                 radius: light.radius,
                 position: scene.get_world_transform(game_object).position.into(),
-                ..Default::default()
+                layer_mask: light.layer_mask,
             });
         }
         point_lights
@@ -857,15 +986,15 @@ impl SceneRenderer {
 
     fn collect_directional_lights(scene: &Scene) -> Vec<DirectionalLight> {
         let mut directional_lights = Vec::new();
-        let mut query = <(Entity, &ComponentDirectionalLight)>::query();
-        for (game_object, light) in query
-            .iter(&scene.world)
-            .filter(|(_, light)| light.active)
-            .filter_map(|(entity, light)| {
-                scene
-                    .get_game_object_from_entity(*entity)
-                    .map(|go| (go, light))
-            })
+        for (game_object, light) in
+            scene
+                .query::<&ComponentDirectionalLight>()
+                .iter()
+                .filter(|(go, light)| {
+                    light.active
+                        && scene.is_component_enabled::<ComponentDirectionalLight>(*go)
+                        && scene.is_active_in_hierarchy(*go)
+                })
         {
             let color = light.color.to_normalized_gamma_f32();
             directional_lights.push(DirectionalLight {
@@ -874,6 +1003,7 @@ impl SceneRenderer {
                     .get_world_transform(game_object)
                     .transform_direction(&Vec3::z_axis())
                     .into(),
+                layer_mask: light.layer_mask,
                 ..Default::default()
             })
         }
@@ -907,7 +1037,9 @@ impl SceneRenderer {
                 sample_count: 1,
                 dimension: wgpu::TextureDimension::D2,
                 format: wgpu::TextureFormat::Rg11b10Ufloat,
-                usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+                usage: wgpu::TextureUsages::COPY_DST
+                    | wgpu::TextureUsages::COPY_SRC
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
                 view_formats: &[],
             },
             None,
@@ -45,6 +45,13 @@ impl Asset for Shader {
         &["wgsl"]
     }
 
+    fn requires_gpu() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
     fn from_file(
         game: &ReadOnlyAssetContext,
         path: &Path,
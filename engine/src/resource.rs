@@ -1,13 +1,26 @@
 use crate::background::Background;
 use crate::core::{Ref, Time};
 use crate::net::Network;
+use crate::scene::LayerNames;
 use downcast_rs::{impl_downcast, Downcast};
 pub use engine_derive::Resource;
 use paste::paste;
 use std::any::{Any, TypeId};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-pub trait Resource: Downcast + Any + 'static {}
+pub trait Resource: Downcast + Any + 'static {
+    /// Name the state of the resource is saved under in save games, which has to stay the
+    /// same across builds. Only resources with a name are saved.
+    fn state_name(&self) -> Option<&'static str> {
+        None
+    }
+    /// State of the resource kept in save games, see [`SaveGame`](crate::scene::SaveGame).
+    fn save_state(&self) -> Option<serde_json::Value> {
+        None
+    }
+    /// Restores the state saved by `save_state`.
+    fn load_state(&mut self, _state: &serde_json::Value) {}
+}
 impl_downcast!(Resource);
 
 pub struct ResourceMap {
@@ -41,6 +54,7 @@ impl ResourceMap {
         resources.insert_default::<Time>();
         resources.insert::<Ref<Background>>(Background::new());
         resources.insert_default::<Network>();
+        resources.insert_default::<LayerNames>();
         resources
     }
 
@@ -87,7 +101,27 @@ impl ResourceMap {
         }
     }
 
+    /// State of the resources kept in save games, keyed by their `state_name`.
+    pub fn save_state(&self) -> BTreeMap<String, serde_json::Value> {
+        self.inner
+            .values()
+            .filter_map(|resource| {
+                Some((resource.state_name()?.to_string(), resource.save_state()?))
+            })
+            .collect()
+    }
+
+    /// Restores the state of the resources saved by [`ResourceMap::save_state`].
+    pub fn load_state(&mut self, state: &BTreeMap<String, serde_json::Value>) {
+        for resource in self.inner.values_mut() {
+            if let Some(state) = resource.state_name().and_then(|name| state.get(name)) {
+                resource.load_state(state);
+            }
+        }
+    }
+
     impl_getter!(mut time, Time);
     impl_getter!(mut background, Ref<Background>);
     impl_getter!(mut network, Network);
+    impl_getter!(mut layer_names, LayerNames);
 }
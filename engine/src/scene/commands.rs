@@ -0,0 +1,380 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::assets::{AssetAccess, AssetRef};
+use crate::component::{Component, ComponentID};
+use crate::scene::{GameObject, LoadSceneMode, Prefab, Scene, SceneLoad};
+use crate::utils::TypeUuid;
+
+/// Handle to a game object that will be created when the scene's commands are applied.
+/// Once they are, [`Scene::spawned_game_object`] gives the game object it stands for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PendingGameObject(u64);
+
+/// A game object commands can target, either one already in the scene or one created by an
+/// earlier command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CommandTarget {
+    GameObject(GameObject),
+    Pending(PendingGameObject),
+}
+
+impl From<GameObject> for CommandTarget {
+    fn from(value: GameObject) -> Self {
+        Self::GameObject(value)
+    }
+}
+
+impl From<PendingGameObject> for CommandTarget {
+    fn from(value: PendingGameObject) -> Self {
+        Self::Pending(value)
+    }
+}
+
+type AddComponentFn = Box<dyn FnOnce(&mut Scene, GameObject) + Send + Sync>;
+
+enum SceneCommand {
+    CreateGameObject {
+        pending: PendingGameObject,
+        id: Option<ComponentID>,
+        parent: Option<CommandTarget>,
+    },
+    SpawnPrefab {
+        pending: PendingGameObject,
+        prefab: AssetRef<Prefab>,
+        parent: Option<CommandTarget>,
+    },
+    AddComponent(CommandTarget, AddComponentFn),
+    RemoveComponent(CommandTarget, Uuid),
+    SetParent(CommandTarget, Option<CommandTarget>),
+    Destroy(CommandTarget),
+    LoadScene(SceneLoad),
+    MakePersistent(CommandTarget),
+}
+
+/// Commands the [`SceneManager`](crate::scene::SceneManager) carries out once its scenes are
+/// updated, as they reach past the scene that recorded them.
+pub(crate) enum SceneRequest {
+    LoadScene(SceneLoad),
+    MakePersistent(GameObject),
+}
+
+/// Structural changes to a scene recorded while it updates, and applied in order once every
+/// component got its `late_update`, or with [`Scene::apply_commands`].
+///
+/// Components can read and write other components through the scene they are given, but
+/// creating, deleting or moving game objects while the scene goes through them should be
+/// recorded here instead:
+/// ```ignore
+/// let commands = ctx.scene.commands();
+/// let bullet = commands.spawn_prefab(self.bullet.clone(), None);
+/// commands.add_component(bullet, ComponentLifetime { seconds: 2.0 });
+/// ```
+#[derive(Default)]
+pub struct SceneCommands {
+    commands: Vec<SceneCommand>,
+    requests: Vec<SceneRequest>,
+    next_pending: u64,
+}
+
+impl SceneCommands {
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn create_game_object(
+        &mut self,
+        id: Option<ComponentID>,
+        parent: Option<impl Into<CommandTarget>>,
+    ) -> PendingGameObject {
+        let pending = self.next_pending();
+        self.commands.push(SceneCommand::CreateGameObject {
+            pending,
+            id,
+            parent: parent.map(Into::into),
+        });
+        pending
+    }
+
+    pub fn spawn_prefab(
+        &mut self,
+        prefab: AssetRef<Prefab>,
+        parent: Option<impl Into<CommandTarget>>,
+    ) -> PendingGameObject {
+        let pending = self.next_pending();
+        self.commands.push(SceneCommand::SpawnPrefab {
+            pending,
+            prefab,
+            parent: parent.map(Into::into),
+        });
+        pending
+    }
+
+    pub fn add_component<T: Component + Send + Sync + 'static>(
+        &mut self,
+        target: impl Into<CommandTarget>,
+        component: T,
+    ) {
+        self.commands.push(SceneCommand::AddComponent(
+            target.into(),
+            Box::new(move |scene, game_object| scene.add_component(game_object, component)),
+        ));
+    }
+
+    pub fn add_component_dyn(&mut self, target: impl Into<CommandTarget>, type_uuid: Uuid) {
+        self.commands.push(SceneCommand::AddComponent(
+            target.into(),
+            Box::new(move |scene, game_object| scene.bind_component_dyn(game_object, type_uuid)),
+        ));
+    }
+
+    pub fn remove_component<T: Component + TypeUuid>(&mut self, target: impl Into<CommandTarget>) {
+        self.remove_component_dyn(target, T::type_uuid());
+    }
+
+    pub fn remove_component_dyn(&mut self, target: impl Into<CommandTarget>, type_uuid: Uuid) {
+        self.commands
+            .push(SceneCommand::RemoveComponent(target.into(), type_uuid));
+    }
+
+    pub fn set_parent(
+        &mut self,
+        target: impl Into<CommandTarget>,
+        parent: Option<impl Into<CommandTarget>>,
+    ) {
+        self.commands.push(SceneCommand::SetParent(
+            target.into(),
+            parent.map(Into::into),
+        ));
+    }
+
+    /// Deletes the game object and its descendants the next time the scene is prepared.
+    pub fn destroy(&mut self, target: impl Into<CommandTarget>) {
+        self.commands.push(SceneCommand::Destroy(target.into()));
+    }
+
+    /// Loads a scene in the background once the scene manager is done updating its scenes,
+    /// see [`SceneManager::load_scene_async`](crate::scene::SceneManager::load_scene_async).
+    /// The returned handle reports the progress of the load.
+    pub fn load_scene(&mut self, scene: &AssetRef<Scene>, mode: LoadSceneMode) -> SceneLoad {
+        let load = SceneLoad::new(AssetAccess::id(scene), mode);
+        self.commands.push(SceneCommand::LoadScene(load.clone()));
+        load
+    }
+
+    /// Moves the game object to the persistent scene once the scene manager is done updating
+    /// its scenes, see [`SceneManager::make_persistent`](crate::scene::SceneManager::make_persistent).
+    pub fn make_persistent(&mut self, target: impl Into<CommandTarget>) {
+        self.commands
+            .push(SceneCommand::MakePersistent(target.into()));
+    }
+
+    fn next_pending(&mut self) -> PendingGameObject {
+        let pending = PendingGameObject(self.next_pending);
+        self.next_pending += 1;
+        pending
+    }
+}
+
+impl Scene {
+    pub fn commands(&mut self) -> &mut SceneCommands {
+        &mut self.commands
+    }
+
+    /// The game object created for a pending one by the last [`Scene::apply_commands`].
+    pub fn spawned_game_object(&self, pending: PendingGameObject) -> Option<GameObject> {
+        self.spawned_game_objects.get(&pending).copied()
+    }
+
+    /// Applies the recorded commands in order, and returns the game objects that were created.
+    /// Commands targeting game objects that no longer exist, or that failed to be created,
+    /// are skipped.
+    pub fn apply_commands(&mut self) -> HashMap<PendingGameObject, GameObject> {
+        let mut spawned = HashMap::new();
+        for command in std::mem::take(&mut self.commands.commands) {
+            match command {
+                SceneCommand::CreateGameObject {
+                    pending,
+                    id,
+                    parent,
+                } => {
+                    let Some(parent) = self.resolve_parent(&spawned, parent) else {
+                        continue;
+                    };
+                    let game_object = self.create_game_object(id, parent);
+                    spawned.insert(pending, game_object);
+                }
+                SceneCommand::SpawnPrefab {
+                    pending,
+                    prefab,
+                    parent,
+                } => {
+                    let Some(parent) = self.resolve_parent(&spawned, parent) else {
+                        continue;
+                    };
+                    let Some(prefab) = prefab.get_ref(self.assets()) else {
+                        continue;
+                    };
+                    if let Some(game_object) = self.instantiate_prefab(&prefab, parent) {
+                        spawned.insert(pending, game_object);
+                    }
+                }
+                SceneCommand::AddComponent(target, add) => {
+                    if let Some(game_object) = self.resolve(&spawned, target) {
+                        add(self, game_object);
+                    }
+                }
+                SceneCommand::RemoveComponent(target, type_uuid) => {
+                    if let Some(game_object) = self.resolve(&spawned, target) {
+                        self.remove_component_dyn(game_object, type_uuid);
+                    }
+                }
+                SceneCommand::SetParent(target, parent) => {
+                    let Some(game_object) = self.resolve(&spawned, target) else {
+                        continue;
+                    };
+                    let Some(parent) = self.resolve_parent(&spawned, parent) else {
+                        continue;
+                    };
+                    if parent.is_some_and(|parent| self.is_descendant(game_object, parent)) {
+                        continue;
+                    }
+                    self.set_parent(game_object, parent);
+                }
+                SceneCommand::Destroy(target) => {
+                    if let Some(game_object) = self.resolve(&spawned, target) {
+                        self.delete_game_object(game_object);
+                    }
+                }
+                SceneCommand::LoadScene(load) => {
+                    self.commands.requests.push(SceneRequest::LoadScene(load));
+                }
+                SceneCommand::MakePersistent(target) => {
+                    if let Some(game_object) = self.resolve(&spawned, target) {
+                        self.commands
+                            .requests
+                            .push(SceneRequest::MakePersistent(game_object));
+                    }
+                }
+            }
+        }
+        self.spawned_game_objects.clone_from(&spawned);
+        spawned
+    }
+
+    /// Takes the commands recorded for the scene manager by the applied commands.
+    pub(crate) fn take_requests(&mut self) -> Vec<SceneRequest> {
+        std::mem::take(&mut self.commands.requests)
+    }
+
+    fn resolve(
+        &self,
+        spawned: &HashMap<PendingGameObject, GameObject>,
+        target: CommandTarget,
+    ) -> Option<GameObject> {
+        match target {
+            CommandTarget::GameObject(game_object) => self.entry(game_object).map(|_| game_object),
+            CommandTarget::Pending(pending) => spawned.get(&pending).copied(),
+        }
+    }
+
+    /// Resolves an optional parent, `None` meaning it couldn't be found.
+    fn resolve_parent(
+        &self,
+        spawned: &HashMap<PendingGameObject, GameObject>,
+        parent: Option<CommandTarget>,
+    ) -> Option<Option<GameObject>> {
+        match parent {
+            Some(parent) => self.resolve(spawned, parent).map(Some),
+            None => Some(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate as engine;
+    use crate::component::{Component, ComponentEventContext, ReflectComponent};
+    use crate::input::Input;
+    use crate::reflect::{Reflect, ReflectDefault};
+    use crate::resource::ResourceMap;
+    use crate::scene::{GameObjectRef, PendingGameObject};
+    use crate::test_utils::{self, Marker};
+    use crate::utils::{ReflectTypeUuidDynamic, TypeUuid};
+
+    #[derive(Default, TypeUuid, Serialize, Deserialize, Component, Reflect)]
+    #[uuid = "6f2d8b41-c09e-4a73-8e15-b4a7d3c2e960"]
+    #[reflect(Default, TypeUuidDynamic, Component)]
+    #[reflect_attr(update)]
+    struct Spawner {
+        victim: GameObjectRef,
+        #[serde(skip)]
+        #[reflect_skip]
+        spawned: Option<PendingGameObject>,
+    }
+
+    impl Component for Spawner {
+        fn update(&mut self, ctx: ComponentEventContext, _: &mut ResourceMap, _: &Input) {
+            if self.spawned.is_some() {
+                return;
+            }
+            let victim = self.victim.game_object(ctx.scene);
+            let commands = ctx.scene.commands();
+            let spawned = commands.create_game_object(None, Some(ctx.game_object));
+            commands.add_component(spawned, Marker);
+            if let Some(victim) = victim {
+                commands.destroy(victim);
+            }
+            self.spawned = Some(spawned);
+        }
+    }
+
+    #[test]
+    fn scene_commands() {
+        let mut scene = test_utils::scene();
+        let victim = scene.create_game_object(None, None);
+        let spawner = scene.create_game_object(None, None);
+        scene.add_component(
+            spawner,
+            Spawner {
+                victim: GameObjectRef::new(scene.get_game_object_uuid(victim)),
+                spawned: None,
+            },
+        );
+        test_utils::update(&mut scene, &mut ResourceMap::new());
+
+        // Applied once the update is over
+        let pending = scene
+            .read_component(spawner, |s: &Spawner| s.spawned)
+            .flatten()
+            .unwrap();
+        let spawned = scene.spawned_game_object(pending).unwrap();
+        assert_eq!(scene.get_parent_game_object(spawned), Some(spawner));
+        assert!(scene.read_component(spawned, |_: &Marker| ()).is_some());
+        assert!(scene.commands().is_empty());
+
+        scene.prepare();
+        assert!(scene.entry(victim).is_none());
+
+        // Game objects destroyed along with their parent, deleted in any order
+        let pairs = (0..8)
+            .map(|_| {
+                let parent = scene.create_game_object(None, None);
+                (parent, scene.create_game_object(None, Some(parent)))
+            })
+            .collect::<Vec<_>>();
+        for (parent, child) in pairs.iter().copied() {
+            scene.commands().destroy(child);
+            scene.commands().destroy(parent);
+        }
+        scene.apply_commands();
+        scene.prepare();
+        for (parent, child) in pairs {
+            assert!(scene.entry(parent).is_none());
+            assert!(scene.entry(child).is_none());
+        }
+    }
+}
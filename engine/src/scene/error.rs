@@ -5,6 +5,9 @@ pub enum SceneError {
     InvalidNodeId,
     ComponentNotBound,
     UnableToCreatePrefab,
+    NotPrefabInstance,
+    PrefabNotFound,
+    PrefabCycle,
 }
 
 impl fmt::Display for SceneError {
@@ -13,6 +16,11 @@ impl fmt::Display for SceneError {
             SceneError::InvalidNodeId => write!(f, "invalid node ID"),
             SceneError::ComponentNotBound => write!(f, "component not bound to entity specified"),
             SceneError::UnableToCreatePrefab => write!(f, "unable to create prefab"),
+            SceneError::NotPrefabInstance => {
+                write!(f, "game object is not part of a prefab instance")
+            }
+            SceneError::PrefabNotFound => write!(f, "prefab of the instance not found"),
+            SceneError::PrefabCycle => write!(f, "prefab would contain an instance of itself"),
         }
     }
 }
@@ -22,7 +30,10 @@ impl std::error::Error for SceneError {
         match *self {
             SceneError::InvalidNodeId
             | SceneError::ComponentNotBound
-            | SceneError::UnableToCreatePrefab => None,
+            | SceneError::UnableToCreatePrefab
+            | SceneError::NotPrefabInstance
+            | SceneError::PrefabNotFound
+            | SceneError::PrefabCycle => None,
         }
     }
 }
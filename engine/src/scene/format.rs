@@ -0,0 +1,309 @@
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::component::Component;
+use crate::context::ReadOnlyAssetContext;
+use crate::error::BoxedError;
+use crate::reflect::Reflect;
+use crate::scene::{Prefab, PrefabData, Scene, SceneData};
+
+/// Header of scenes and prefabs saved in the binary format, JSON files start with `{` instead.
+pub const BINARY_SCENE_MAGIC: &[u8; 4] = b"CXSB";
+
+pub type BinaryError = bincode::Error;
+
+/// Scene data with components encoded in binary, see [`SceneFormat::Binary`].
+pub type BinarySceneData = SceneData<BinaryComponentData>;
+
+/// Format scenes and prefabs are saved in. Both load from the same file extensions.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SceneFormat {
+    /// Pretty printed JSON, in a stable order so that scenes can be diffed and merged.
+    #[default]
+    Json,
+    /// Compact bincode encoding, with every component encoded on its own so that it loads
+    /// straight from its bytes. Meant for shipped builds, see [`export_assets`].
+    Binary,
+}
+
+impl SceneFormat {
+    /// Detects the format of a saved scene or prefab from its first bytes.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(BINARY_SCENE_MAGIC) {
+            Self::Binary
+        } else {
+            Self::Json
+        }
+    }
+}
+
+/// Encodes a value like components are in binary scenes.
+pub fn encode_binary<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, BinaryError> {
+    bincode::serialize(value)
+}
+
+/// Encodes a component for binary scenes, or returns `None` if the bytes don't decode back to
+/// the same value. bincode doesn't describe the data it writes, so fields left out with
+/// `skip_serializing_if` can't be read back; those components are saved as JSON instead.
+pub fn encode_component_binary<T: Serialize + DeserializeOwned>(value: &T) -> Option<Vec<u8>> {
+    let data = encode_binary(value).ok()?;
+    let decoded = decode_binary::<T>(&data).ok()?;
+    (serde_json::to_value(&decoded).ok()? == serde_json::to_value(value).ok()?).then_some(data)
+}
+
+pub fn decode_binary<T: DeserializeOwned>(data: &[u8]) -> Result<T, BinaryError> {
+    bincode::deserialize(data)
+}
+
+/// Data of a single component in a binary scene.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BinaryComponentData {
+    Binary(Vec<u8>),
+    /// JSON of a component that doesn't encode in binary, or that the scene couldn't load and
+    /// keeps to be written back.
+    Json(String),
+}
+
+/// How components are stored in [`SceneData`].
+pub trait SerializedComponent: Sized {
+    fn serialize(component: &dyn Component) -> Option<Self>;
+    fn deserialize(&self, component: &dyn Component) -> Result<Box<dyn Reflect>, String>;
+    /// Upgrades the data from `version` to the next one, returns false if it can't be migrated.
+    fn migrate(&mut self, component: &dyn Component, version: u32) -> bool;
+    /// The data to keep for a component that couldn't be loaded, if it can be kept.
+    fn into_opaque(self) -> Option<serde_json::Value>;
+    fn from_opaque(data: &serde_json::Value) -> Self;
+}
+
+impl SerializedComponent for serde_json::Value {
+    fn serialize(component: &dyn Component) -> Option<Self> {
+        component.serialize()
+    }
+
+    fn deserialize(&self, component: &dyn Component) -> Result<Box<dyn Reflect>, String> {
+        component
+            .deserialize(self.clone())
+            .map_err(|err| err.to_string())
+    }
+
+    fn migrate(&mut self, component: &dyn Component, version: u32) -> bool {
+        component.migrate(version, self);
+        true
+    }
+
+    fn into_opaque(self) -> Option<serde_json::Value> {
+        Some(self)
+    }
+
+    fn from_opaque(data: &serde_json::Value) -> Self {
+        data.clone()
+    }
+}
+
+impl SerializedComponent for BinaryComponentData {
+    fn serialize(component: &dyn Component) -> Option<Self> {
+        component
+            .serialize_binary()
+            .map(Self::Binary)
+            .or_else(|| Some(Self::Json(component.serialize()?.to_string())))
+    }
+
+    fn deserialize(&self, component: &dyn Component) -> Result<Box<dyn Reflect>, String> {
+        match self {
+            Self::Binary(data) => component
+                .deserialize_binary(data)
+                .map_err(|err| err.to_string()),
+            Self::Json(json) => serde_json::from_str(json)
+                .and_then(|value| component.deserialize(value))
+                .map_err(|err| err.to_string()),
+        }
+    }
+
+    fn migrate(&mut self, component: &dyn Component, version: u32) -> bool {
+        let Self::Json(json) = self else {
+            return false;
+        };
+        let Ok(mut value) = serde_json::from_str(json) else {
+            return false;
+        };
+        component.migrate(version, &mut value);
+        *json = value.to_string();
+        true
+    }
+
+    fn into_opaque(self) -> Option<serde_json::Value> {
+        match self {
+            Self::Binary(_) => None,
+            Self::Json(json) => serde_json::from_str(&json).ok(),
+        }
+    }
+
+    fn from_opaque(data: &serde_json::Value) -> Self {
+        Self::Json(data.to_string())
+    }
+}
+
+impl<C: Serialize> SceneData<C> {
+    /// Writes the data in binary, behind [`BINARY_SCENE_MAGIC`].
+    pub fn write_binary(&self, mut writer: impl Write) -> Result<(), BinaryError> {
+        writer.write_all(BINARY_SCENE_MAGIC)?;
+        bincode::serialize_into(writer, self)
+    }
+}
+
+impl BinarySceneData {
+    pub fn read_binary(bytes: &[u8]) -> Result<Self, BinaryError> {
+        let data = bytes.strip_prefix(BINARY_SCENE_MAGIC).ok_or_else(|| {
+            BinaryError::from(bincode::ErrorKind::Custom(String::from(
+                "missing binary scene header",
+            )))
+        })?;
+        decode_binary(data)
+    }
+}
+
+impl Scene {
+    /// Loads a scene saved in either format.
+    pub fn from_bytes(assets: &ReadOnlyAssetContext, bytes: &[u8]) -> Result<Self, BoxedError> {
+        Ok(match SceneFormat::detect(bytes) {
+            SceneFormat::Json => (assets, serde_json::from_slice::<SceneData>(bytes)?).into(),
+            SceneFormat::Binary => (assets, BinarySceneData::read_binary(bytes)?).into(),
+        })
+    }
+
+    pub fn save(&self, path: &Path, format: SceneFormat) -> Result<(), BoxedError> {
+        let writer = BufWriter::new(fs::File::create(path)?);
+        match format {
+            SceneFormat::Json => serde_json::to_writer_pretty(writer, self)?,
+            SceneFormat::Binary => BinarySceneData::from(self).write_binary(writer)?,
+        }
+        Ok(())
+    }
+}
+
+impl Prefab {
+    /// Loads a prefab saved in either format.
+    pub fn from_bytes(assets: &ReadOnlyAssetContext, bytes: &[u8]) -> Result<Self, BoxedError> {
+        Ok(match SceneFormat::detect(bytes) {
+            SceneFormat::Json => (assets, serde_json::from_slice::<PrefabData>(bytes)?).into(),
+            SceneFormat::Binary => {
+                let scene: Scene = (assets, BinarySceneData::read_binary(bytes)?).into();
+                Self {
+                    data: (&scene).into(),
+                    scene,
+                }
+            }
+        })
+    }
+
+    pub fn save(&self, path: &Path, format: SceneFormat) -> Result<(), BoxedError> {
+        let writer = BufWriter::new(fs::File::create(path)?);
+        match format {
+            SceneFormat::Json => serde_json::to_writer_pretty(writer, self)?,
+            SceneFormat::Binary => BinarySceneData::from(&self.scene).write_binary(writer)?,
+        }
+        Ok(())
+    }
+}
+
+/// Copies an assets directory for a shipped build, converting its scenes and prefabs to the
+/// binary format. Returns the number of converted files.
+pub fn export_assets(
+    assets: &ReadOnlyAssetContext,
+    source: &Path,
+    destination: &Path,
+) -> Result<usize, BoxedError> {
+    fs::create_dir_all(destination)?;
+    let mut converted = 0;
+    for entry in fs::read_dir(source)? {
+        let path = entry?.path();
+        let target = destination.join(path.file_name().unwrap_or_default());
+        if path.is_dir() {
+            converted += export_assets(assets, &path, &target)?;
+            continue;
+        }
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("cxscene") => {
+                Scene::from_bytes(assets, &fs::read(&path)?)?.save(&target, SceneFormat::Binary)?;
+                converted += 1;
+            }
+            Some("cxprefab") => {
+                Prefab::from_bytes(assets, &fs::read(&path)?)?
+                    .save(&target, SceneFormat::Binary)?;
+                converted += 1;
+            }
+            _ => {
+                fs::copy(&path, &target)?;
+            }
+        }
+    }
+    Ok(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::component::{ComponentCamera, ComponentID, ComponentPointLight};
+    use crate::scene::{
+        BinaryComponentData, BinarySceneData, Scene, SceneData, SceneFormat, SerializedComponent,
+    };
+    use crate::test_utils;
+    use crate::utils::TypeUuid;
+
+    #[test]
+    fn components_encode_in_binary() {
+        let assets = test_utils::assets();
+        let registry = assets.component_registry.read();
+        for (component_id, component) in registry.components() {
+            let data = BinaryComponentData::serialize(component.as_ref()).unwrap();
+            assert!(
+                data.deserialize(component.as_ref()).is_ok(),
+                "component {} doesn't decode from binary",
+                component_id
+            );
+        }
+    }
+
+    #[test]
+    fn binary_scene_roundtrip() {
+        let mut scene = test_utils::scene();
+        let parent = scene.create_game_object(None, None);
+        let child = scene.create_game_object(None, Some(parent));
+        scene.add_component(parent, ComponentCamera::default());
+        scene.add_component(child, ComponentPointLight::default());
+        scene.add_tag(child, "Light");
+        scene.set_component_enabled::<ComponentPointLight>(child, false);
+        let mut data = <SceneData>::from(&scene);
+        let unknown = Uuid::new_v4();
+        data.components[0].insert(unknown, json!({ "value": 1 }));
+        let scene = Scene::from((scene.assets(), data));
+        let json = serde_json::to_string(&scene).unwrap();
+
+        // Empty tags are skipped when serializing, so ids without any can't be read back from
+        // bincode and fall back to JSON.
+        let binary = BinarySceneData::from(&scene);
+        let ids = binary
+            .components
+            .values()
+            .map(|components| &components[&ComponentID::type_uuid()]);
+        assert!(ids
+            .clone()
+            .any(|data| matches!(data, BinaryComponentData::Json(_))));
+        assert!(ids
+            .clone()
+            .any(|data| matches!(data, BinaryComponentData::Binary(_))));
+
+        let mut bytes = Vec::new();
+        binary.write_binary(&mut bytes).unwrap();
+        assert_eq!(SceneFormat::detect(&bytes), SceneFormat::Binary);
+        let loaded = Scene::from_bytes(scene.assets(), &bytes).unwrap();
+        assert_eq!(loaded.load_report().errors.len(), 1);
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+    }
+}
@@ -0,0 +1,79 @@
+use crate as engine;
+use crate::resource::Resource;
+
+/// Number of layers a game object can be on, one per bit of a layer mask.
+pub const LAYER_COUNT: usize = 32;
+
+/// Layer mask of new game objects, the first layer.
+pub const DEFAULT_LAYERS: u32 = 1;
+
+/// Names of the layers of a project, indexed by their bit in layer masks.
+/// Inserted in the game's resources, unnamed layers are empty.
+#[derive(Clone, Debug, PartialEq, Resource)]
+pub struct LayerNames {
+    names: [String; LAYER_COUNT],
+}
+
+impl Default for LayerNames {
+    fn default() -> Self {
+        Self::new([String::from("Default")])
+    }
+}
+
+impl LayerNames {
+    /// Names the first layers, names past [`LAYER_COUNT`] are ignored.
+    pub fn new(names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let mut layer_names = Self {
+            names: Default::default(),
+        };
+        for (layer, name) in names.into_iter().take(LAYER_COUNT).enumerate() {
+            layer_names.names[layer] = name.into();
+        }
+        layer_names
+    }
+
+    pub fn name(&self, layer: usize) -> Option<&str> {
+        self.names
+            .get(layer)
+            .map(String::as_str)
+            .filter(|name| !name.is_empty())
+    }
+
+    pub fn set_name(&mut self, layer: usize, name: impl Into<String>) {
+        if let Some(layer_name) = self.names.get_mut(layer) {
+            *layer_name = name.into();
+        }
+    }
+
+    /// Finds a layer by name.
+    pub fn layer(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|layer_name| layer_name == name)
+    }
+
+    /// Builds the mask of the layers with the given names, unknown names are ignored.
+    pub fn mask(&self, names: &[&str]) -> u32 {
+        names
+            .iter()
+            .filter_map(|name| self.layer(name))
+            .fold(0, |mask, layer| mask | 1 << layer)
+    }
+
+    /// Names of every layer, unnamed ones included, with trailing unnamed layers removed.
+    pub fn to_vec(&self) -> Vec<String> {
+        let len = self
+            .names
+            .iter()
+            .rposition(|name| !name.is_empty())
+            .map_or(0, |last| last + 1);
+        self.names[..len].to_vec()
+    }
+
+    /// The named layers along with their index.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.names
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| !name.is_empty())
+            .map(|(layer, name)| (layer, name.as_str()))
+    }
+}
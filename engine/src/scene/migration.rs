@@ -0,0 +1,291 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::class_registry::ComponentRegistry;
+use crate::scene::{SceneData, SerializedComponent};
+
+/// Version of the scene format written by this build of the engine.
+pub const SCENE_FORMAT_VERSION: u32 = 1;
+
+/// Format migrations, the one at index `i` upgrades scene data from version `i` to `i + 1`.
+fn format_migrations<C>() -> [fn(&mut SceneData<C>); SCENE_FORMAT_VERSION as usize] {
+    [
+        // Scenes saved before versioning share the same layout, with every component at version 0
+        |_| {},
+    ]
+}
+
+/// Component data a scene couldn't load, kept as is to be written back when the scene is saved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpaqueComponent {
+    pub version: u32,
+    pub data: serde_json::Value,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneLoadErrorKind {
+    /// No component with this type uuid is registered.
+    UnknownComponent,
+    /// The data was saved with a newer version of the component than the registered one.
+    NewerVersion(u32),
+    /// The data was saved with an older version of the component and couldn't be migrated,
+    /// which happens to binary data. Binary scenes need to be exported again.
+    OutdatedVersion(u32),
+    /// The data doesn't deserialize into the component.
+    InvalidData(String),
+}
+
+/// A component that failed to load, and was kept as an [`OpaqueComponent`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SceneLoadError {
+    pub game_object: Uuid,
+    pub component: Uuid,
+    pub kind: SceneLoadErrorKind,
+}
+
+impl fmt::Display for SceneLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "component {} of game object {}: ",
+            self.component, self.game_object
+        )?;
+        match &self.kind {
+            SceneLoadErrorKind::UnknownComponent => write!(f, "unknown component type"),
+            SceneLoadErrorKind::NewerVersion(version) => {
+                write!(f, "saved with newer version {}", version)
+            }
+            SceneLoadErrorKind::OutdatedVersion(version) => {
+                write!(f, "saved with outdated version {}", version)
+            }
+            SceneLoadErrorKind::InvalidData(err) => write!(f, "invalid data, {}", err),
+        }
+    }
+}
+
+/// Migrations and errors of a scene load, see
+/// [`Scene::load_report`](crate::scene::Scene::load_report).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SceneLoadReport {
+    /// Format version the scene was saved with.
+    pub version: u32,
+    /// Component types with data that was migrated, along with the version it was saved with.
+    pub migrated: HashMap<Uuid, u32>,
+    pub errors: Vec<SceneLoadError>,
+}
+
+impl SceneLoadReport {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl<C> SceneData<C> {
+    /// Version the data of a component was saved with.
+    pub fn component_version(&self, game_object: Uuid, component: Uuid) -> u32 {
+        self.instance_versions
+            .get(&game_object)
+            .and_then(|versions| versions.get(&component))
+            .or_else(|| self.component_versions.get(&component))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl<C: SerializedComponent> SceneData<C> {
+    /// Upgrades the data to the current format, then migrates components saved with an older
+    /// version, see [`Component::migrate`](crate::component::Component::migrate). Components
+    /// that are unknown, saved with a newer version or binary are left untouched, and keep
+    /// the version they were saved with.
+    pub fn migrate(&mut self, components: &ComponentRegistry) -> SceneLoadReport {
+        let mut report = SceneLoadReport {
+            version: self.version,
+            ..Default::default()
+        };
+        for migration in format_migrations().iter().skip(self.version as usize) {
+            migration(self);
+        }
+        self.version = self.version.max(SCENE_FORMAT_VERSION);
+
+        let mut instance_versions = BTreeMap::<Uuid, BTreeMap<Uuid, u32>>::new();
+        let mut registered = BTreeSet::new();
+        for (game_object_id, game_object_components) in &mut self.components {
+            for (component_id, data) in game_object_components.iter_mut() {
+                let saved_version = self
+                    .instance_versions
+                    .get(game_object_id)
+                    .and_then(|versions| versions.get(component_id))
+                    .or_else(|| self.component_versions.get(component_id))
+                    .copied()
+                    .unwrap_or_default();
+                let Some(component) = components.component(*component_id) else {
+                    if saved_version != 0 {
+                        instance_versions
+                            .entry(*game_object_id)
+                            .or_default()
+                            .insert(*component_id, saved_version);
+                    }
+                    continue;
+                };
+                registered.insert(*component_id);
+                let current_version = components.component_version(*component_id);
+                let mut version = saved_version;
+                while version < current_version && data.migrate(component, version) {
+                    version += 1;
+                }
+                if version != current_version {
+                    instance_versions
+                        .entry(*game_object_id)
+                        .or_default()
+                        .insert(*component_id, version);
+                } else if saved_version != current_version {
+                    report.migrated.insert(*component_id, saved_version);
+                }
+            }
+        }
+        // Versions by type are only kept for registered types, unknown ones are by instance
+        self.component_versions = registered
+            .into_iter()
+            .map(|component_id| (component_id, components.component_version(component_id)))
+            .filter(|(_, version)| *version != 0)
+            .collect();
+        self.instance_versions = instance_versions;
+        report
+    }
+}
+
+/// Renames a field of serialized component data, keeping its value.
+pub fn rename_field(data: &mut serde_json::Value, from: &str, to: &str) {
+    if let Some(object) = data.as_object_mut() {
+        if let Some(value) = object.remove(from) {
+            object.insert(to.to_string(), value);
+        }
+    }
+}
+
+/// Gives a value to a field missing from serialized component data, for fields whose default
+/// differs from the one of older data.
+pub fn set_field_default(data: &mut serde_json::Value, field: &str, value: impl Serialize) {
+    if let Some(object) = data.as_object_mut() {
+        if !object.contains_key(field) {
+            if let Ok(value) = serde_json::to_value(value) {
+                object.insert(field.to_string(), value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate as engine;
+    use crate::component::{Component, ComponentID, ReflectComponent};
+    use crate::reflect::{Reflect, ReflectDefault};
+    use crate::scene::{
+        rename_field, set_field_default, Scene, SceneData, SceneLoadErrorKind, SCENE_FORMAT_VERSION,
+    };
+    use crate::test_utils;
+    use crate::utils::{ReflectTypeUuidDynamic, TypeUuid};
+
+    #[derive(Default, TypeUuid, Serialize, Deserialize, Component, Reflect)]
+    #[uuid = "6f0d2c4e-8a1b-4e7f-b3c9-5d2e8f1a0c47"]
+    #[reflect(Default, TypeUuidDynamic, Component)]
+    #[reflect_attr(version = 2)]
+    struct Runner {
+        max_speed: f32,
+        jump_height: f32,
+    }
+
+    impl Component for Runner {
+        fn migrate(&self, version: u32, data: &mut serde_json::Value) {
+            match version {
+                0 => rename_field(data, "speed", "max_speed"),
+                1 => set_field_default(data, "jump_height", 1.5),
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn migrate_and_keep_unknown_components() {
+        let (first, second, third) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let unknown = Uuid::new_v4();
+        let data: SceneData = serde_json::from_value(json!({
+            "components": {
+                first.to_string(): {
+                    ComponentID::type_uuid().to_string(): { "id": first, "name": "First" },
+                    Runner::type_uuid().to_string(): { "speed": 3.0 },
+                    unknown.to_string(): { "value": 1 },
+                },
+                second.to_string(): {
+                    ComponentID::type_uuid().to_string(): { "id": second, "name": "Second" },
+                    Runner::type_uuid().to_string(): { "speed": "fast" },
+                },
+                third.to_string(): {
+                    ComponentID::type_uuid().to_string(): { "id": third, "name": "Third" },
+                    Runner::type_uuid().to_string(): { "max_speed": 2.0, "sprint": true },
+                },
+            },
+            "component_versions": { unknown.to_string(): 4 },
+            "instance_versions": {
+                third.to_string(): { Runner::type_uuid().to_string(): 3 },
+            },
+            "hierarchy": {},
+        }))
+        .unwrap();
+        let assets = test_utils::assets().lock_read();
+        let scene = Scene::from((&assets, data));
+
+        let report = scene.load_report();
+        assert_eq!(report.version, 0);
+        assert_eq!(report.migrated.get(&Runner::type_uuid()), Some(&0));
+        let mut errors: Vec<_> = report
+            .errors
+            .iter()
+            .map(|e| (e.game_object, e.component, e.kind.clone()))
+            .collect();
+        errors.sort_by_key(|(_, component, _)| *component != unknown);
+        assert_eq!(
+            errors[0],
+            (first, unknown, SceneLoadErrorKind::UnknownComponent)
+        );
+        errors[1..].sort_by_key(|(game_object, _, _)| *game_object != second);
+        assert!(matches!(
+            errors[1],
+            (game_object, component, SceneLoadErrorKind::InvalidData(_))
+                if game_object == second && component == Runner::type_uuid()
+        ));
+        assert_eq!(
+            errors[2],
+            (
+                third,
+                Runner::type_uuid(),
+                SceneLoadErrorKind::NewerVersion(3)
+            )
+        );
+
+        let go = scene.get_game_object_by_uuid(first).unwrap();
+        let runner = scene.read_component(go, |r: &Runner| (r.max_speed, r.jump_height));
+        assert_eq!(runner, Some((3.0, 1.5)));
+
+        // Components that failed to load are written back along with the others
+        let saved = <SceneData>::from(&scene);
+        assert_eq!(saved.version, SCENE_FORMAT_VERSION);
+        assert_eq!(saved.component_versions[&Runner::type_uuid()], 2);
+        assert_eq!(saved.components[&first][&unknown], json!({ "value": 1 }));
+        // Components kept as they were saved keep their own version
+        assert_eq!(saved.component_version(first, unknown), 4);
+        assert_eq!(saved.component_version(second, Runner::type_uuid()), 2);
+        assert_eq!(saved.component_version(third, Runner::type_uuid()), 3);
+        assert_eq!(
+            saved.components[&second][&Runner::type_uuid()],
+            json!({ "max_speed": "fast", "jump_height": 1.5 })
+        );
+    }
+}
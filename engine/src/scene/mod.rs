@@ -1,11 +1,29 @@
+pub use self::commands::*;
 pub use self::error::*;
+pub use self::format::*;
 pub use self::game_object_ref::*;
+pub use self::layer::*;
+pub use self::migration::*;
 pub use self::prefab::*;
+pub use self::prefab_instance::*;
+pub use self::query::*;
+pub use self::save_game::*;
 pub use self::scene::*;
 pub use self::scene_manager::*;
+pub use self::snapshot::*;
+pub use self::system::*;
 
+mod commands;
 mod error;
+mod format;
 mod game_object_ref;
+mod layer;
+mod migration;
 mod prefab;
+mod prefab_instance;
+mod query;
+mod save_game;
 mod scene;
 mod scene_manager;
+mod snapshot;
+mod system;
@@ -8,7 +8,7 @@ use crate::component::{
 use crate::context::ReadOnlyAssetContext;
 use crate::core::Ref;
 use crate::math::{self, Transform};
-use crate::scene::{Scene, SceneData};
+use crate::scene::{Scene, SceneData, SceneFormat};
 use crate::utils::TypeUuid;
 use crate::{self as engine, utils};
 use nalgebra_glm::Mat4;
@@ -18,8 +18,7 @@ use russimp::sys::AI_CONFIG_IMPORT_FBX_PRESERVE_PIVOTS;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::borrow::Borrow;
-use std::collections::HashMap;
-use std::io::BufReader;
+use std::collections::{BTreeSet, HashMap};
 use std::path::Path;
 use uuid::Uuid;
 
@@ -38,9 +37,11 @@ pub struct PrefabData {
 
 impl From<(&ReadOnlyAssetContext, PrefabData)> for Prefab {
     fn from((game, value): (&ReadOnlyAssetContext, PrefabData)) -> Self {
+        let mut data = value.data;
+        data.migrate(&game.component_registry.read());
         Self {
-            data: value.data.clone(),
-            scene: (game, value.data).into(),
+            data: data.clone(),
+            scene: (game, data).into(),
         }
     }
 }
@@ -90,8 +91,7 @@ impl Asset for Prefab {
             let mut meshes = Vec::new();
             for mesh in &scene.meshes {
                 let name = format!("{}/{}", meta.name, mesh.name);
-                let mesh_ref = asset_registry
-                    .create(name, Mesh::from_russimp_mesh(&game.render_context, mesh))?;
+                let mesh_ref = asset_registry.create(name, Mesh::from_russimp_mesh(mesh))?;
                 meshes.push(mesh_ref.clone());
                 bones.extend(mesh.bones.iter().enumerate().map(|(i, b)| {
                     let offset_matrix = math::mat4_from_russimp(&b.offset_matrix);
@@ -140,16 +140,34 @@ impl Asset for Prefab {
                     .collect(),
             })
         } else {
-            let file = std::fs::OpenOptions::new()
-                .read(true)
-                .open(path)
-                .map_err(|_| AssetError::LoadError)?;
-            let reader = BufReader::new(file);
-            let data: PrefabData =
-                serde_json::from_reader(reader).map_err(|_| AssetError::LoadError)?;
-            Ok(LoadedAsset::new((game, data).into()))
+            let bytes = std::fs::read(path).map_err(|_| AssetError::LoadError)?;
+            Prefab::from_bytes(game, &bytes)
+                .map(LoadedAsset::new)
+                .map_err(|_| AssetError::LoadError)
         }
     }
+
+    fn to_file(&self, path: &Path) -> Result<(), std::io::Error> {
+        if path.extension().is_some_and(|ext| ext != "cxprefab") {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "imported prefabs can't be saved",
+            ));
+        }
+        self.save(path, SceneFormat::Json)
+            .map_err(|err| std::io::Error::other(err.to_string()))
+    }
+
+    fn has_dependencies() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn dependencies(&self) -> BTreeSet<Uuid> {
+        self.scene.dependencies()
+    }
 }
 
 impl Prefab {
@@ -0,0 +1,1027 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+
+use bimap::BiHashMap;
+use indexmap::IndexMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::assets::AssetAccess;
+use crate::component::{ComponentID, ComponentPrefabInstance};
+use crate::context::ReadOnlyAssetContext;
+use crate::core::Ref;
+use crate::reflect::type_registry::TypeRegistry;
+use crate::reflect::TypeInfo;
+use crate::scene::{
+    GameObject, GameObjectRef, OpaqueComponent, Prefab, Scene, SceneData, SceneError,
+};
+use crate::utils::{self, TypeUuid};
+
+/// Fields of a prefab instance that differ from its prefab, by id of the game object in the
+/// prefab, component type uuid and field path.
+///
+/// Field paths are reflect field names joined with dots, going into the fields that are
+/// reflected structs themselves. The empty path stands for the whole component, which is null
+/// for components removed from the instance. Game objects removed from the instance have their
+/// [`ComponentID`] removed.
+pub type PrefabOverrides = BTreeMap<Uuid, BTreeMap<Uuid, BTreeMap<String, OverrideValue>>>;
+
+/// Serialized value of an overridden field, kept as JSON text in binary scenes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OverrideValue(pub Value);
+
+impl Serialize for OverrideValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            self.0.serialize(serializer)
+        } else {
+            self.0.to_string().serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OverrideValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            Value::deserialize(deserializer).map(Self)
+        } else {
+            let json = String::deserialize(deserializer)?;
+            serde_json::from_str(&json)
+                .map(Self)
+                .map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+/// How a prefab instance was built, to tell its overrides apart and rebuild it when the prefab
+/// changes.
+pub struct PrefabExpansion {
+    prefab: Uuid,
+    /// Versions of the prefab and of the prefabs nested in it, see
+    /// [`AssetRegistry::asset_version`](crate::assets::AssetRegistry::asset_version).
+    versions: BTreeMap<Uuid, u32>,
+    /// Components of the prefab's game objects, referencing the game objects of the instance.
+    /// That's what the instance looks like without overrides.
+    baseline: IndexMap<Uuid, BTreeMap<Uuid, Value>>,
+    /// Ids of the game objects of the instance by id in the prefab, the root included.
+    pub(crate) game_objects: BiHashMap<Uuid, Uuid>,
+}
+
+impl PrefabExpansion {
+    fn remapped(&self, type_registry: &TypeRegistry, map: &impl Fn(Uuid) -> Option<Uuid>) -> Self {
+        let mut baseline = self.baseline.clone();
+        for (component_id, value) in baseline
+            .values_mut()
+            .flat_map(|components| components.iter_mut())
+        {
+            remap_ids(type_registry, *component_id, value, map);
+        }
+        Self {
+            prefab: self.prefab,
+            versions: self.versions.clone(),
+            baseline,
+            game_objects: self
+                .game_objects
+                .iter()
+                .map(|(prefab_id, id)| (*prefab_id, map(*id).unwrap_or(*id)))
+                .collect(),
+        }
+    }
+}
+
+/// The root of a prefab, its first game object without a parent.
+fn prefab_root(data: &SceneData) -> Option<Uuid> {
+    data.components
+        .keys()
+        .find(|id| !data.hierarchy.contains_key(*id))
+        .copied()
+}
+
+/// Id of a game object of a prefab instance, derived from the ids of the instance's root and of
+/// the game object in the prefab.
+pub(crate) fn instance_game_object_id(root_id: Uuid, prefab_id: Uuid) -> Uuid {
+    utils::uuid_from_str(&format!("{}/{}", root_id, prefab_id))
+}
+
+/// Replaces the game object ids held by the [`GameObjectRef`] fields of serialized component
+/// data, going into the fields that are reflected structs. The id of a [`ComponentID`] is
+/// replaced as well, being the id of the game object itself.
+pub(crate) fn remap_ids(
+    type_registry: &TypeRegistry,
+    type_uuid: Uuid,
+    value: &mut Value,
+    map: &impl Fn(Uuid) -> Option<Uuid>,
+) {
+    let remap = |value: &mut Value| {
+        if let Some(id) = value
+            .as_str()
+            .and_then(|id| Uuid::parse_str(id).ok())
+            .and_then(map)
+        {
+            *value = Value::String(id.to_string());
+        }
+    };
+    if type_uuid == GameObjectRef::type_uuid() {
+        remap(value);
+        return;
+    }
+    if type_uuid == ComponentID::type_uuid() {
+        if let Some(id) = value.get_mut("id") {
+            remap(id);
+        }
+    }
+    let (Some(TypeInfo::Struct(info)), Some(object)) = (
+        type_registry.type_info_by_id(type_uuid),
+        value.as_object_mut(),
+    ) else {
+        return;
+    };
+    for field in info.fields.values() {
+        if let Some(field_value) = object.get_mut(field.name) {
+            remap_ids(type_registry, field.type_uuid, field_value, map);
+        }
+    }
+}
+
+thread_local! {
+    /// Prefabs whose instances are being expanded on this thread. Expanding an instance loads
+    /// its prefab, which expands the instances nested in it, so this spans asset loads.
+    static EXPANDING_PREFABS: RefCell<Vec<Uuid>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Marks a prefab as being expanded until dropped, see [`EXPANDING_PREFABS`].
+struct PrefabExpansionGuard;
+
+impl PrefabExpansionGuard {
+    /// Returns `None` if the prefab is already being expanded, its instance being part of
+    /// itself.
+    fn enter(prefab_id: Uuid) -> Option<Self> {
+        EXPANDING_PREFABS.with_borrow_mut(|expanding| {
+            if expanding.contains(&prefab_id) {
+                return None;
+            }
+            expanding.push(prefab_id);
+            Some(Self)
+        })
+    }
+}
+
+impl Drop for PrefabExpansionGuard {
+    fn drop(&mut self) {
+        EXPANDING_PREFABS.with_borrow_mut(|expanding| expanding.pop());
+    }
+}
+
+/// Whether scene data holds an instance of a prefab, directly or through the prefabs nested
+/// in its instances.
+pub(crate) fn contains_prefab(
+    assets: &ReadOnlyAssetContext,
+    data: &SceneData,
+    prefab_id: Uuid,
+) -> bool {
+    let instance_prefabs = |data: &SceneData| {
+        data.components
+            .values()
+            .filter_map(|components| components.get(&ComponentPrefabInstance::type_uuid()))
+            .filter_map(|value| {
+                serde_json::from_value::<ComponentPrefabInstance>(value.clone()).ok()
+            })
+            .map(|instance| instance.prefab.id())
+            .filter(|id| !id.is_nil())
+            .collect::<Vec<_>>()
+    };
+    let mut visited = HashSet::new();
+    let mut pending = instance_prefabs(data);
+    while let Some(id) = pending.pop() {
+        if id == prefab_id {
+            return true;
+        }
+        if !visited.insert(id) {
+            continue;
+        }
+        let prefab = assets.asset_registry.read().load_by_id::<Prefab>(id);
+        if let Ok(prefab) = prefab {
+            pending.extend(instance_prefabs(&prefab.read().data));
+        }
+    }
+    false
+}
+
+fn field<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.is_empty() {
+        return Some(value);
+    }
+    path.split('.')
+        .try_fold(value, |value, name| value.get(name))
+}
+
+fn set_field(value: &mut Value, path: &str, field: Value) {
+    if path.is_empty() {
+        *value = field;
+        return;
+    }
+    let mut target = value;
+    for name in path.split('.') {
+        target = match target {
+            Value::Object(object) => object.entry(name).or_insert(Value::Null),
+            _ => return,
+        };
+    }
+    *target = field;
+}
+
+/// Lists the fields of `current` that differ from `baseline`, going into the fields that are
+/// reflected structs.
+fn diff_fields(
+    type_registry: &TypeRegistry,
+    type_uuid: Option<Uuid>,
+    path: &str,
+    baseline: &Value,
+    current: &Value,
+    overrides: &mut BTreeMap<String, OverrideValue>,
+) {
+    if baseline == current {
+        return;
+    }
+    let type_info = type_uuid.and_then(|type_uuid| type_registry.type_info_by_id(type_uuid));
+    if let (Some(TypeInfo::Struct(info)), Value::Object(baseline), Value::Object(current)) =
+        (type_info, baseline, current)
+    {
+        for name in baseline
+            .keys()
+            .chain(current.keys())
+            .collect::<BTreeSet<_>>()
+        {
+            let field_path = if path.is_empty() {
+                name.clone()
+            } else {
+                format!("{}.{}", path, name)
+            };
+            diff_fields(
+                type_registry,
+                info.fields.get(name.as_str()).map(|field| field.type_uuid),
+                &field_path,
+                baseline.get(name).unwrap_or(&Value::Null),
+                current.get(name).unwrap_or(&Value::Null),
+                overrides,
+            );
+        }
+        return;
+    }
+    overrides.insert(path.to_string(), OverrideValue(current.clone()));
+}
+
+fn is_removed(fields: Option<&BTreeMap<String, OverrideValue>>) -> bool {
+    fields
+        .and_then(|fields| fields.get(""))
+        .is_some_and(|value| value.0.is_null())
+}
+
+/// Components of the root of an instance that belong to the instance rather than the prefab.
+fn is_root_component(component_id: Uuid) -> bool {
+    component_id == ComponentID::type_uuid() || component_id == ComponentPrefabInstance::type_uuid()
+}
+
+impl Scene {
+    /// Adds an instance of a prefab to the scene, and returns its root.
+    ///
+    /// The instance stays linked to the prefab through the [`ComponentPrefabInstance`] of its
+    /// root: it's saved as its overrides and rebuilt whenever the prefab changes.
+    pub fn instantiate_prefab(
+        &mut self,
+        prefab: &Ref<Prefab>,
+        parent: Option<GameObject>,
+    ) -> Option<GameObject> {
+        let mut id = {
+            let prefab = prefab.read();
+            let root_id = prefab_root(&prefab.data)?;
+            prefab.data.components[&root_id]
+                .get(&ComponentID::type_uuid())
+                .and_then(|c_id| serde_json::from_value::<ComponentID>(c_id.clone()).ok())
+                .unwrap_or_default()
+        };
+        id.id = Uuid::new_v4();
+        let root = self.create_game_object(Some(id), parent);
+        self.add_component(
+            root,
+            ComponentPrefabInstance {
+                prefab: Some(prefab.clone()).into(),
+                ..Default::default()
+            },
+        );
+        self.expand_prefab_instance(root);
+        Some(root)
+    }
+
+    /// The root of the outermost prefab instance a game object is part of, along with the id of
+    /// the game object in that instance's prefab.
+    pub fn prefab_instance_of(&self, game_object: GameObject) -> Option<(GameObject, Uuid)> {
+        let id = self.get_game_object_uuid(game_object);
+        self.get_ancestors(game_object)
+            .filter_map(|go| {
+                let expansion = self.prefab_expansion(go)?;
+                let prefab_id = expansion.game_objects.get_by_right(&id)?;
+                Some((go, *prefab_id))
+            })
+            .last()
+    }
+
+    /// Fields of a prefab instance that differ from its prefab.
+    pub fn prefab_overrides(&self, root: GameObject) -> PrefabOverrides {
+        let Some(expansion) = self.prefab_expansion(root) else {
+            return self
+                .read_component(root, |c: &ComponentPrefabInstance| c.overrides.clone())
+                .unwrap_or_default();
+        };
+        expansion
+            .baseline
+            .keys()
+            .map(|prefab_id| {
+                (
+                    *prefab_id,
+                    self.object_overrides(&expansion, root, *prefab_id),
+                )
+            })
+            .filter(|(_, overrides)| !overrides.is_empty())
+            .collect()
+    }
+
+    /// Fields of the components of a game object that differ from the prefab instance it's
+    /// part of, by component type uuid.
+    pub fn game_object_overrides(
+        &self,
+        game_object: GameObject,
+    ) -> BTreeMap<Uuid, BTreeMap<String, OverrideValue>> {
+        self.prefab_instance_of(game_object)
+            .and_then(|(root, prefab_id)| {
+                let expansion = self.prefab_expansion(root)?;
+                Some(self.object_overrides(&expansion, root, prefab_id))
+            })
+            .unwrap_or_default()
+    }
+
+    /// Sets an overridden field of a component back to the value in the prefab, or the whole
+    /// component without a path.
+    pub fn revert_prefab_override(
+        &mut self,
+        game_object: GameObject,
+        component_id: Uuid,
+        path: Option<&str>,
+    ) {
+        let Some((root, prefab_id)) = self.prefab_instance_of(game_object) else {
+            return;
+        };
+        let Some(expansion) = self.prefab_expansion(root) else {
+            return;
+        };
+        let baseline = expansion
+            .baseline
+            .get(&prefab_id)
+            .and_then(|components| components.get(&component_id));
+        let current = self.component_values(game_object).remove(&component_id);
+        let value = match (path, current) {
+            (Some(path), Some(mut current)) => {
+                let value = baseline.and_then(|baseline| field(baseline, path));
+                set_field(&mut current, path, value.cloned().unwrap_or_default());
+                Some(current)
+            }
+            _ => baseline.cloned(),
+        };
+        self.set_component_value(game_object, component_id, value);
+    }
+
+    /// Writes an overridden field of a component to the prefab of the outermost instance the
+    /// game object is part of, or the whole component without a path, and returns the prefab.
+    ///
+    /// The instances of the prefab are rebuilt with the new value on the next
+    /// [`Scene::prepare`]. The prefab still has to be saved, see
+    /// [`AssetRegistry::persist`](crate::assets::AssetRegistry::persist).
+    pub fn apply_prefab_override(
+        &mut self,
+        game_object: GameObject,
+        component_id: Uuid,
+        path: Option<&str>,
+    ) -> Result<Ref<Prefab>, SceneError> {
+        let (root, prefab_id) = self
+            .prefab_instance_of(game_object)
+            .filter(|(root, _)| {
+                *root != game_object || component_id != ComponentPrefabInstance::type_uuid()
+            })
+            .ok_or(SceneError::NotPrefabInstance)?;
+        let expansion = self
+            .prefab_expansion(root)
+            .ok_or(SceneError::NotPrefabInstance)?;
+        let prefab = self
+            .assets()
+            .asset_registry
+            .read()
+            .load_by_id::<Prefab>(expansion.prefab)
+            .map_err(|_| SceneError::PrefabNotFound)?;
+
+        let current = self.component_values(game_object).remove(&component_id);
+        let baseline = expansion
+            .baseline
+            .get(&prefab_id)
+            .and_then(|components| components.get(&component_id))
+            .cloned();
+        let mut value = match (path, baseline) {
+            (Some(path), Some(mut baseline)) => {
+                let value = current.as_ref().and_then(|current| field(current, path));
+                set_field(&mut baseline, path, value.cloned().unwrap_or_default());
+                Some(baseline)
+            }
+            _ => current,
+        };
+        if let Some(value) = &mut value {
+            remap_ids(
+                &self.assets().type_registry.read(),
+                component_id,
+                value,
+                &|id| expansion.game_objects.get_by_right(&id).copied(),
+            );
+        }
+
+        let data = prefab.read().data.clone();
+        let mut source = Scene::from((self.assets(), data));
+        let target = source
+            .get_game_object_by_uuid(prefab_id)
+            .ok_or(SceneError::InvalidNodeId)?;
+        source.set_component_value(target, component_id, value);
+        let source_root = expansion
+            .game_objects
+            .get_by_right(&self.get_game_object_uuid(root))
+            .and_then(|id| source.get_game_object_by_uuid(*id))
+            .ok_or(SceneError::InvalidNodeId)?;
+        let data: SceneData = (&source, source_root).into();
+        if contains_prefab(self.assets(), &data, prefab.id()) {
+            return Err(SceneError::PrefabCycle);
+        }
+        {
+            let mut prefab = prefab.write();
+            prefab.scene = (self.assets(), data.clone()).into();
+            prefab.data = data;
+        }
+        self.assets()
+            .asset_registry
+            .read()
+            .asset_changed(prefab.id());
+        Ok(prefab)
+    }
+
+    pub(crate) fn prefab_expansion(&self, game_object: GameObject) -> Option<Arc<PrefabExpansion>> {
+        self.read_component(game_object, |c: &ComponentPrefabInstance| {
+            c.expansion.clone()
+        })
+        .flatten()
+    }
+
+    /// Leaves out the game objects that are part of prefab instances, which are saved along
+    /// with the root of their instance. Game objects have to come parents first.
+    pub(crate) fn without_prefab_members(
+        &self,
+        game_objects: impl Iterator<Item = GameObject>,
+    ) -> Vec<GameObject> {
+        let mut members = HashSet::new();
+        game_objects
+            .filter(|go| {
+                let id = self.get_game_object_uuid(*go);
+                if members.contains(&id) {
+                    return false;
+                }
+                if let Some(expansion) = self.prefab_expansion(*go) {
+                    members.extend(expansion.game_objects.right_values().copied());
+                }
+                true
+            })
+            .collect()
+    }
+
+    /// Rebuilds the prefab instances whose prefab, or a prefab nested in it, changed since they
+    /// were built, keeping their overrides and the game objects added under them.
+    pub(crate) fn refresh_prefab_instances(&mut self) {
+        let asset_registry_ref = self.assets().asset_registry.clone();
+        let asset_registry = asset_registry_ref.read();
+        let mut members = HashSet::new();
+        let mut outdated = Vec::new();
+        for game_object in self.game_objects() {
+            if members.contains(&self.get_game_object_uuid(game_object)) {
+                continue;
+            }
+            let Some((prefab, expansion)) = self
+                .read_component(game_object, |c: &ComponentPrefabInstance| {
+                    (c.prefab.id(), c.expansion.clone())
+                })
+            else {
+                continue;
+            };
+            match expansion {
+                Some(expansion) => {
+                    members.extend(expansion.game_objects.right_values().copied());
+                    if expansion.prefab != prefab
+                        || expansion
+                            .versions
+                            .iter()
+                            .any(|(id, version)| asset_registry.asset_version(*id) != *version)
+                    {
+                        outdated.push(game_object);
+                    }
+                }
+                None if !prefab.is_nil() => outdated.push(game_object),
+                None => {}
+            }
+        }
+        drop(asset_registry);
+        for root in outdated {
+            self.rebuild_prefab_instance(root);
+        }
+    }
+
+    fn rebuild_prefab_instance(&mut self, root: GameObject) {
+        let overrides = self.prefab_overrides(root);
+        let root_id = self.get_game_object_uuid(root);
+        let members = self
+            .prefab_expansion(root)
+            .iter()
+            .flat_map(|expansion| expansion.game_objects.right_values())
+            .copied()
+            .filter(|id| *id != root_id)
+            .collect::<HashSet<_>>();
+
+        // Game objects added under the instance are moved out of the way while it's rebuilt
+        let mut added = Vec::new();
+        for game_object in self.get_descendants_ordered(root).collect::<Vec<_>>() {
+            let Some(parent_id) = self.get_parent_uuid(game_object) else {
+                continue;
+            };
+            if !members.contains(&self.get_game_object_uuid(game_object))
+                && members.contains(&parent_id)
+            {
+                added.push((game_object, parent_id));
+            }
+        }
+        for (game_object, _) in &added {
+            self.set_parent(*game_object, Some(root));
+        }
+        for child in self.get_children(root).collect::<Vec<_>>() {
+            if members.contains(&self.get_game_object_uuid(child)) {
+                self.objects_to_delete.insert(child);
+            }
+        }
+        self.delete_game_objects();
+
+        let component_registry_ref = self.assets().component_registry.clone();
+        let component_registry = component_registry_ref.read();
+        for (component_id, component) in component_registry.components() {
+            if is_root_component(*component_id) {
+                continue;
+            }
+            if let Some(mut entry) = self.entry_mut(root) {
+                if component.get_instance_mut(&mut entry).is_some() {
+                    component.remove_instance(&mut entry);
+                }
+            }
+            self.components_to_start.remove(&(root, *component_id));
+        }
+        self.opaque_components.remove(&root_id);
+        self.write_component(root, |c: &mut ComponentPrefabInstance| {
+            c.overrides = overrides;
+            c.expansion = None;
+        });
+        self.expand_prefab_instance(root);
+        for (game_object, parent_id) in added {
+            if let Some(parent) = self.get_game_object_by_uuid(parent_id) {
+                self.set_parent(game_object, Some(parent));
+            }
+        }
+    }
+
+    /// Builds the game objects of a prefab instance under its root, from the prefab and the
+    /// overrides of the root's [`ComponentPrefabInstance`]. Game objects of the instance get
+    /// ids derived from the root's, so that references to them hold when it's rebuilt.
+    ///
+    /// Instances of a prefab within that same prefab, directly or through nested prefabs,
+    /// are left with their root only.
+    pub(crate) fn expand_prefab_instance(&mut self, root: GameObject) {
+        let root_id = self.get_game_object_uuid(root);
+        let Some((prefab, overrides)) = self.read_component(root, |c: &ComponentPrefabInstance| {
+            (c.prefab.clone(), c.overrides.clone())
+        }) else {
+            return;
+        };
+        let Some(_guard) = PrefabExpansionGuard::enter(prefab.id()) else {
+            return;
+        };
+        let Some(prefab) = prefab.get_ref(self.assets()) else {
+            return;
+        };
+        let data = prefab.read().data.clone();
+        let Some(source_root_id) = prefab_root(&data) else {
+            return;
+        };
+        // Prefabs nested in the prefab are expanded along with it
+        let source = Scene::from((self.assets(), data));
+        let Some(source_root) = source.get_game_object_by_uuid(source_root_id) else {
+            return;
+        };
+
+        let mut versions = BTreeMap::from([(
+            prefab.id(),
+            self.assets()
+                .asset_registry
+                .read()
+                .asset_version(prefab.id()),
+        )]);
+        let mut game_objects = BiHashMap::new();
+        let mut baseline = SceneData::<Value>::default();
+        for go in std::iter::once(source_root).chain(source.get_descendants_ordered(source_root)) {
+            let id = source.get_game_object_uuid(go);
+            let instance_id = if go == source_root {
+                root_id
+            } else {
+                instance_game_object_id(root_id, id)
+            };
+            game_objects.insert(id, instance_id);
+            source.serialize_game_object(go, &mut baseline, true);
+            if let Some(expansion) = source.prefab_expansion(go) {
+                versions.extend(&expansion.versions);
+            }
+        }
+        let map = |id| game_objects.get_by_left(&id).copied();
+        let type_registry_ref = self.assets().type_registry.clone();
+        let type_registry = type_registry_ref.read();
+        for (component_id, value) in baseline
+            .components
+            .values_mut()
+            .flat_map(|components| components.iter_mut())
+        {
+            remap_ids(&type_registry, *component_id, value, &map);
+        }
+
+        let component_registry_ref = self.assets().component_registry.clone();
+        let component_registry = component_registry_ref.read();
+        let mut created = HashMap::from([(source_root_id, root)]);
+        for (prefab_id, components) in &baseline.components {
+            let object_overrides = overrides.get(prefab_id);
+            let instance_id = game_objects
+                .get_by_left(prefab_id)
+                .copied()
+                .unwrap_or_default();
+            if *prefab_id != source_root_id {
+                let parent = baseline
+                    .hierarchy
+                    .get(prefab_id)
+                    .and_then(|parent| created.get(parent));
+                let removed =
+                    is_removed(object_overrides.and_then(|o| o.get(&ComponentID::type_uuid())));
+                let (Some(parent), false) = (parent.copied(), removed) else {
+                    continue;
+                };
+                let game_object = self.new_game_object(Some(parent));
+                created.insert(*prefab_id, game_object);
+                self.uuid_map.insert(instance_id, game_object);
+            }
+            let game_object = created[prefab_id];
+
+            let component_ids = components
+                .keys()
+                .chain(object_overrides.into_iter().flat_map(|o| o.keys()))
+                .copied()
+                .collect::<BTreeSet<_>>();
+            for component_id in component_ids {
+                if game_object == root && is_root_component(component_id) {
+                    continue;
+                }
+                let fields = object_overrides.and_then(|o| o.get(&component_id));
+                if is_removed(fields) {
+                    continue;
+                }
+                let mut value = components.get(&component_id).cloned().unwrap_or_default();
+                for (path, field) in fields.into_iter().flatten() {
+                    set_field(&mut value, path, field.0.clone());
+                }
+                let instance = component_registry
+                    .component(component_id)
+                    .and_then(|component| {
+                        Some((component, component.deserialize(value.clone()).ok()?))
+                    });
+                match instance {
+                    Some((component, instance)) => {
+                        let Some(mut entry) = self.entry_mut(game_object) else {
+                            continue;
+                        };
+                        if component.bind_instance(&mut entry, instance) {
+                            self.components_to_start.insert((game_object, component_id));
+                        }
+                    }
+                    None => {
+                        let version = baseline.component_version(*prefab_id, component_id);
+                        self.opaque_components
+                            .entry(instance_id)
+                            .or_default()
+                            .insert(
+                                component_id,
+                                OpaqueComponent {
+                                    version,
+                                    data: value,
+                                },
+                            );
+                    }
+                }
+            }
+
+            // Instances nested in the prefab keep what they were built from
+            if game_object != root {
+                if let Some(expansion) = source
+                    .get_game_object_by_uuid(*prefab_id)
+                    .and_then(|go| source.prefab_expansion(go))
+                {
+                    let expansion = Arc::new(expansion.remapped(&type_registry, &map));
+                    self.write_component(game_object, |c: &mut ComponentPrefabInstance| {
+                        c.expansion = Some(expansion)
+                    });
+                }
+            }
+        }
+
+        let expansion = Arc::new(PrefabExpansion {
+            prefab: prefab.id(),
+            versions,
+            baseline: baseline.components,
+            game_objects,
+        });
+        self.write_component(root, |c: &mut ComponentPrefabInstance| {
+            c.expansion = Some(expansion)
+        });
+        self.refresh_active_in_hierarchy(root);
+    }
+
+    fn object_overrides(
+        &self,
+        expansion: &PrefabExpansion,
+        root: GameObject,
+        prefab_id: Uuid,
+    ) -> BTreeMap<Uuid, BTreeMap<String, OverrideValue>> {
+        let Some(baseline) = expansion.baseline.get(&prefab_id) else {
+            return Default::default();
+        };
+        let Some(game_object) = expansion
+            .game_objects
+            .get_by_left(&prefab_id)
+            .and_then(|id| self.get_game_object_by_uuid(*id))
+        else {
+            let removed = [(String::new(), OverrideValue(Value::Null))].into();
+            return [(ComponentID::type_uuid(), removed)].into();
+        };
+        let current = self.component_values(game_object);
+        let type_registry = self.assets().type_registry.read();
+        let mut overrides = BTreeMap::new();
+        for component_id in baseline
+            .keys()
+            .chain(current.keys())
+            .collect::<BTreeSet<_>>()
+        {
+            if game_object == root && is_root_component(*component_id) {
+                continue;
+            }
+            let mut fields = BTreeMap::new();
+            match (baseline.get(component_id), current.get(component_id)) {
+                (Some(baseline), Some(current)) => diff_fields(
+                    &type_registry,
+                    Some(*component_id),
+                    "",
+                    baseline,
+                    current,
+                    &mut fields,
+                ),
+                (Some(_), None) => {
+                    fields.insert(String::new(), OverrideValue(Value::Null));
+                }
+                (None, Some(current)) => {
+                    fields.insert(String::new(), OverrideValue(current.clone()));
+                }
+                (None, None) => {}
+            }
+            if !fields.is_empty() {
+                overrides.insert(*component_id, fields);
+            }
+        }
+        overrides
+    }
+
+    /// Serialized components of a game object, the ones that couldn't be loaded included.
+    fn component_values(&self, game_object: GameObject) -> BTreeMap<Uuid, Value> {
+        let mut data = SceneData::<Value>::default();
+        self.serialize_game_object(game_object, &mut data, true);
+        data.components.into_values().next().unwrap_or_default()
+    }
+
+    /// Replaces a component with the one deserialized from `value`, or removes it without one.
+    fn set_component_value(
+        &mut self,
+        game_object: GameObject,
+        component_id: Uuid,
+        value: Option<Value>,
+    ) {
+        let Some(value) = value else {
+            self.remove_component_dyn(game_object, component_id);
+            return;
+        };
+        let component_registry_ref = self.assets().component_registry.clone();
+        let component_registry = component_registry_ref.read();
+        let Some(component) = component_registry.component(component_id) else {
+            return;
+        };
+        let Ok(instance) = component.deserialize(value) else {
+            return;
+        };
+        let bound = self
+            .entry_mut(game_object)
+            .is_some_and(|mut entry| component.bind_instance(&mut entry, instance));
+        if bound {
+            self.components_to_start.insert((game_object, component_id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate as engine;
+    use crate::component::{Component, ComponentPointLight, ReflectComponent};
+    use crate::reflect::{Reflect, ReflectDefault};
+    use crate::scene::{GameObject, GameObjectRef, Scene, SceneData, SceneError};
+    use crate::test_utils;
+    use crate::utils::{ReflectTypeUuidDynamic, TypeUuid};
+
+    #[derive(Default, TypeUuid, Serialize, Deserialize, Component, Reflect)]
+    #[uuid = "b4e81f2d-5c3a-4d96-8e07-a1f9c2d6b358"]
+    #[reflect(Default, TypeUuidDynamic, Component)]
+    struct Follower {
+        target: GameObjectRef,
+        label: String,
+    }
+
+    impl Component for Follower {}
+
+    fn light_of(scene: &Scene, root: GameObject) -> GameObject {
+        scene
+            .get_descendants_with_component::<ComponentPointLight>(root)
+            .next()
+            .unwrap()
+    }
+
+    #[test]
+    fn nested_prefab_overrides() {
+        let mut scene = test_utils::scene();
+        let assets = scene.assets().clone();
+        let lamp = scene.create_game_object(None, None);
+        let light = scene.create_game_object(None, Some(lamp));
+        scene.add_component(light, ComponentPointLight::default());
+        let lamp_prefab = assets
+            .asset_registry
+            .read()
+            .create(
+                "tests/prefab_lamp".into(),
+                scene.create_prefab(lamp, None).unwrap(),
+            )
+            .unwrap();
+        let room = scene.create_game_object(None, None);
+        scene.instantiate_prefab(&lamp_prefab, Some(room)).unwrap();
+        let room_prefab = assets
+            .asset_registry
+            .read()
+            .create(
+                "tests/prefab_room".into(),
+                scene.create_prefab(room, None).unwrap(),
+            )
+            .unwrap();
+
+        let mut scene = assets.scene();
+        let instance = scene.instantiate_prefab(&room_prefab, None).unwrap();
+        // The instance, the room instance in it and the lamp instance in that one
+        assert_eq!(scene.get_descendants(instance).count(), 3);
+        let light = light_of(&scene, instance);
+        let light_id = scene.get_game_object_uuid(light);
+
+        // Only the root of the instance is saved, along with the overridden fields
+        scene.write_component(light, |c: &mut ComponentPointLight| c.radius = 4.0);
+        let overrides = scene.game_object_overrides(light);
+        assert_eq!(
+            overrides[&ComponentPointLight::type_uuid()]
+                .keys()
+                .collect::<Vec<_>>(),
+            ["radius"]
+        );
+        assert_eq!(<SceneData>::from(&scene).components.len(), 1);
+        let loaded = scene.clone();
+        let loaded_light = loaded.get_game_object_by_uuid(light_id).unwrap();
+        assert_eq!(
+            loaded.read_component(loaded_light, |c: &ComponentPointLight| c.radius),
+            Some(4.0)
+        );
+
+        // Changes to the nested prefab reach the instance, without undoing its overrides
+        let mut lamp_scene = assets.scene();
+        let lamp = lamp_scene.instantiate_prefab(&lamp_prefab, None).unwrap();
+        let lamp_light = light_of(&lamp_scene, lamp);
+        lamp_scene.write_component(lamp_light, |c: &mut ComponentPointLight| {
+            c.radius = 6.0;
+            c.layer_mask = 1;
+        });
+        lamp_scene
+            .apply_prefab_override(
+                lamp_light,
+                ComponentPointLight::type_uuid(),
+                Some("layer_mask"),
+            )
+            .unwrap();
+        scene.prepare();
+        let light = scene.get_game_object_by_uuid(light_id).unwrap();
+        assert_eq!(
+            scene.read_component(light, |c: &ComponentPointLight| (c.radius, c.layer_mask)),
+            Some((4.0, 1))
+        );
+
+        // Overrides applied to the prefab aren't overrides anymore
+        scene
+            .apply_prefab_override(light, ComponentPointLight::type_uuid(), None)
+            .unwrap();
+        scene.prepare();
+        let light = scene.get_game_object_by_uuid(light_id).unwrap();
+        assert!(scene.game_object_overrides(light).is_empty());
+        scene.write_component(light, |c: &mut ComponentPointLight| c.radius = 1.0);
+        scene.revert_prefab_override(light, ComponentPointLight::type_uuid(), Some("radius"));
+        assert_eq!(
+            scene.read_component(light, |c: &ComponentPointLight| c.radius),
+            Some(4.0)
+        );
+    }
+
+    #[test]
+    fn prefab_references_and_cycles() {
+        let mut scene = test_utils::scene();
+        let assets = scene.assets().clone();
+        let lamp = scene.create_game_object(None, None);
+        let light = scene.create_game_object(None, Some(lamp));
+        let light_id = scene.get_game_object_uuid(light);
+        scene.add_component(
+            lamp,
+            Follower {
+                target: GameObjectRef::new(light_id),
+                label: light_id.to_string(),
+            },
+        );
+        let lamp_prefab = assets
+            .asset_registry
+            .read()
+            .create(
+                "tests/prefab_cycle_lamp".into(),
+                scene.create_prefab(lamp, None).unwrap(),
+            )
+            .unwrap();
+
+        // Only game object references point to the game objects of the instance
+        let instance = scene.instantiate_prefab(&lamp_prefab, None).unwrap();
+        let instance_light = scene.get_children(instance).next().unwrap();
+        let (target, label) = scene
+            .read_component(instance, |f: &Follower| {
+                (f.target.game_object(&scene), f.label.clone())
+            })
+            .unwrap();
+        assert_eq!(target, Some(instance_light));
+        assert_eq!(label, light_id.to_string());
+
+        // A prefab can't be saved over one of the prefabs it holds instances of
+        let room = scene.create_game_object(None, None);
+        scene.set_parent(instance, Some(room));
+        let room_prefab = assets
+            .asset_registry
+            .read()
+            .create(
+                "tests/prefab_cycle_room".into(),
+                scene.create_prefab(room, None).unwrap(),
+            )
+            .unwrap();
+        let house = scene.create_game_object(None, None);
+        scene.instantiate_prefab(&room_prefab, Some(house)).unwrap();
+        assert!(matches!(
+            scene.create_prefab(house, Some(lamp_prefab.id())),
+            Err(SceneError::PrefabCycle)
+        ));
+
+        // Instances of a prefab within itself are left unexpanded
+        let cyclic = scene.create_prefab(house, None).unwrap();
+        lamp_prefab.write().data = cyclic.data;
+        let instance = scene.instantiate_prefab(&lamp_prefab, None).unwrap();
+        assert_eq!(scene.get_descendants(instance).count(), 3);
+    }
+}
@@ -0,0 +1,277 @@
+use std::collections::HashSet;
+use std::ops::{BitAnd, Deref, DerefMut};
+
+use legion::query::{
+    component, ComponentFilter, DefaultFilter, EntityFilter, EntityFilterTuple, IntoQuery,
+    Not as NotFilter, Passthrough, Query, View,
+};
+use legion::storage::Component as LegionComponent;
+use legion::{Entity, World};
+
+use crate::component::Component;
+use crate::scene::{GameObject, Scene};
+
+type DefaultSceneFilter<V> = <(Entity, V) as DefaultQueryFilter>::Filter;
+type WithFilter<T> = EntityFilterTuple<ComponentFilter<T>, Passthrough>;
+type WithoutFilter<T> = EntityFilterTuple<NotFilter<ComponentFilter<T>>, Passthrough>;
+
+/// The filter legion gives a query of a view.
+pub trait DefaultQueryFilter {
+    type Filter: EntityFilter;
+}
+
+impl<Q: IntoQuery> DefaultQueryFilter for Q {
+    type Filter = <Q::View as DefaultFilter>::Filter;
+}
+
+/// Goes through the game objects of a scene having the components of a legion view, such as
+/// `(&ComponentTransform, Option<&mut ComponentRigidBody>)`, one archetype after the other.
+/// Built with [`Scene::query`], or [`Scene::query_mut`] for views writing components:
+/// ```ignore
+/// for (game_object, (c_transform, c_light)) in scene
+///     .query::<(&ComponentTransform, &ComponentPointLight)>()
+///     .without::<ComponentCamera>()
+///     .descendants_of(parent)
+///     .iter()
+/// {
+///     ...
+/// }
+/// ```
+pub struct SceneQuery<S, V: IntoQuery, F: EntityFilter = DefaultSceneFilter<V>> {
+    scene: S,
+    query: Query<(Entity, V), F>,
+    within: Option<HashSet<Entity>>,
+}
+
+impl Scene {
+    /// Queries the game objects having components with a view only reading them.
+    pub fn query<V: IntoQuery>(&self) -> SceneQuery<&Self, V> {
+        SceneQuery {
+            scene: self,
+            query: <(Entity, V)>::query(),
+            within: None,
+        }
+    }
+
+    /// Queries the game objects having components with a view that may write them.
+    pub fn query_mut<V: IntoQuery>(&mut self) -> SceneQuery<&mut Self, V> {
+        SceneQuery {
+            scene: self,
+            query: <(Entity, V)>::query(),
+            within: None,
+        }
+    }
+}
+
+/// Views only reading components, which [`SceneQuery::iter`] can go through from a shared scene:
+/// `&T`, `Option<&T>`, and tuples of up to four of those.
+pub trait ReadOnlyView: IntoQuery {
+    fn iter<'q, F: EntityFilter>(
+        query: &'q mut Query<(Entity, Self), F>,
+        world: &'q World,
+    ) -> impl Iterator<Item = (&'q Entity, <Self::View as View<'q>>::Element)> + 'q;
+}
+
+// Legion only lets concrete read-only views be iterated from a shared world
+macro_rules! impl_read_only_view {
+    () => {
+        fn iter<'q, F: EntityFilter>(
+            query: &'q mut Query<(Entity, Self), F>,
+            world: &'q World,
+        ) -> impl Iterator<Item = (&'q Entity, <Self::View as View<'q>>::Element)> + 'q {
+            query.iter(world)
+        }
+    };
+}
+
+macro_rules! impl_read_only_tuple {
+    ([$(($t:ident $($view:tt)*))*]) => {
+        impl<'v, $($t: LegionComponent),*> ReadOnlyView for ($($($view)*,)*) {
+            impl_read_only_view!();
+        }
+    };
+    ([$($done:tt)*] $t:ident $($rest:ident)*) => {
+        impl_read_only_tuple!([$($done)* ($t &'v $t)] $($rest)*);
+        impl_read_only_tuple!([$($done)* ($t Option<&'v $t>)] $($rest)*);
+    };
+}
+
+impl<T: LegionComponent> ReadOnlyView for &T {
+    impl_read_only_view!();
+}
+
+impl<T: LegionComponent> ReadOnlyView for Option<&T> {
+    impl_read_only_view!();
+}
+
+impl_read_only_tuple!([] A);
+impl_read_only_tuple!([] A B);
+impl_read_only_tuple!([] A B C);
+impl_read_only_tuple!([] A B C D);
+
+impl<S: Deref<Target = Scene>, V: IntoQuery, F: EntityFilter> SceneQuery<S, V, F> {
+    /// Only keeps the game objects passing a legion filter.
+    pub fn filter<T: EntityFilter>(self, filter: T) -> SceneQuery<S, V, <F as BitAnd<T>>::Output>
+    where
+        F: BitAnd<T>,
+        <F as BitAnd<T>>::Output: EntityFilter,
+    {
+        SceneQuery {
+            scene: self.scene,
+            query: self.query.filter(filter),
+            within: self.within,
+        }
+    }
+
+    /// Only keeps the game objects having a component, without reading it.
+    pub fn with<T: Component>(self) -> SceneQuery<S, V, <F as BitAnd<WithFilter<T>>>::Output>
+    where
+        F: BitAnd<WithFilter<T>>,
+        <F as BitAnd<WithFilter<T>>>::Output: EntityFilter,
+    {
+        self.filter(component::<T>())
+    }
+
+    /// Only keeps the game objects not having a component.
+    pub fn without<T: Component>(self) -> SceneQuery<S, V, <F as BitAnd<WithoutFilter<T>>>::Output>
+    where
+        F: BitAnd<WithoutFilter<T>>,
+        <F as BitAnd<WithoutFilter<T>>>::Output: EntityFilter,
+    {
+        self.filter(!component::<T>())
+    }
+
+    /// Only keeps the descendants of a game object, the game object itself excluded.
+    pub fn descendants_of(mut self, game_object: GameObject) -> Self {
+        let descendants = self
+            .scene
+            .get_descendants(game_object)
+            .filter(|go| *go != game_object)
+            .map(|go| go.entity);
+        self.within = Some(match self.within {
+            Some(within) => descendants.filter(|e| within.contains(e)).collect(),
+            None => descendants.collect(),
+        });
+        self
+    }
+
+    /// Goes through the matching game objects, for views only reading components.
+    pub fn iter<'q>(
+        &'q mut self,
+    ) -> impl Iterator<Item = (GameObject, <V::View as View<'q>>::Element)> + 'q
+    where
+        V: ReadOnlyView,
+    {
+        let scene = &*self.scene;
+        let within = &self.within;
+        let detached = detached_entities::<V>(scene);
+        V::iter(&mut self.query, &scene.world).filter_map(move |(entity, element)| {
+            if within.as_ref().is_some_and(|w| !w.contains(entity)) || detached.contains(entity) {
+                return None;
+            }
+            scene
+                .get_game_object_from_entity(*entity)
+                .map(|go| (go, element))
+        })
+    }
+}
+
+impl<S: DerefMut<Target = Scene>, V: IntoQuery, F: EntityFilter> SceneQuery<S, V, F> {
+    /// Goes through the matching game objects, for views writing components.
+    pub fn iter_mut<'q>(
+        &'q mut self,
+    ) -> impl Iterator<Item = (GameObject, <V::View as View<'q>>::Element)> + 'q {
+        let detached = detached_entities::<V>(&self.scene);
+        let Scene {
+            world, entity_map, ..
+        } = &mut *self.scene;
+        let within = &self.within;
+        self.query
+            .iter_mut(world)
+            .filter_map(move |(entity, element)| {
+                if within.as_ref().is_some_and(|w| !w.contains(entity)) || detached.contains(entity)
+                {
+                    return None;
+                }
+                entity_map.get(entity).map(|node| {
+                    (
+                        GameObject {
+                            node: *node,
+                            entity: *entity,
+                        },
+                        element,
+                    )
+                })
+            })
+    }
+}
+
+/// Entities whose component read or written by the view is detached by
+/// [`Scene::with_component_mut`], as they hold a spare instance meanwhile.
+fn detached_entities<V: IntoQuery>(scene: &Scene) -> HashSet<Entity> {
+    let reads = <V::View as View>::reads_types();
+    let writes = <V::View as View>::writes_types();
+    let types = reads.as_ref().iter().chain(writes.as_ref()).copied();
+    scene.detached_entities(&types.collect::<Vec<_>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::component::{ComponentCamera, ComponentPointLight};
+    use crate::test_utils;
+
+    #[test]
+    fn scene_query() {
+        let mut scene = test_utils::scene();
+        let root = scene.create_game_object(None, None);
+        let child = scene.create_game_object(None, Some(root));
+        let grandchild = scene.create_game_object(None, Some(child));
+        let other = scene.create_game_object(None, None);
+        for go in [root, child, grandchild, other] {
+            scene.add_component(go, ComponentPointLight::default());
+        }
+        scene.add_component(grandchild, ComponentCamera::default());
+
+        let lights = scene
+            .query::<&ComponentPointLight>()
+            .iter()
+            .map(|(go, _)| go)
+            .collect::<HashSet<_>>();
+        assert_eq!(lights, HashSet::from([root, child, grandchild, other]));
+
+        let lights = scene
+            .query::<&ComponentPointLight>()
+            .without::<ComponentCamera>()
+            .descendants_of(root)
+            .iter()
+            .map(|(go, _)| go)
+            .collect::<Vec<_>>();
+        assert_eq!(lights, vec![child]);
+
+        let cameras = scene
+            .query::<(&ComponentPointLight, Option<&ComponentCamera>)>()
+            .descendants_of(child)
+            .iter()
+            .map(|(go, (_, camera))| (go, camera.is_some()))
+            .collect::<Vec<_>>();
+        assert_eq!(cameras, vec![(grandchild, true)]);
+
+        for (_, c_light) in scene
+            .query_mut::<&mut ComponentPointLight>()
+            .with::<ComponentCamera>()
+            .iter_mut()
+        {
+            c_light.radius = 42.0;
+        }
+        assert_eq!(
+            scene.read_component(grandchild, |c: &ComponentPointLight| c.radius),
+            Some(42.0)
+        );
+        assert_ne!(
+            scene.read_component(child, |c: &ComponentPointLight| c.radius),
+            Some(42.0)
+        );
+    }
+}
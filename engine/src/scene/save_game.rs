@@ -0,0 +1,255 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::io::BufWriter;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::BoxedError;
+use crate::physics::{PhysicsContext, PhysicsState};
+use crate::scene::{GameObject, Scene, SceneData};
+
+/// Runtime state of a scene its [`SceneData`] leaves out, see [`Scene::save_state`].
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SceneState {
+    /// State of the components keyed by game object and component type, see
+    /// [`ComponentInstance::save_state`](crate::component::ComponentInstance::save_state).
+    pub components: BTreeMap<Uuid, BTreeMap<Uuid, serde_json::Value>>,
+    /// Components that didn't get `start` called yet.
+    pub unstarted: BTreeSet<(Uuid, Uuid)>,
+    pub physics: PhysicsState,
+}
+
+/// A scene of a save game, with its runtime state.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedScene {
+    /// Scene asset the scene was loaded from, if any.
+    pub asset: Option<Uuid>,
+    pub data: SceneData,
+    pub state: SceneState,
+}
+
+/// The scenes of a running simulation along with the state of the resources, to pick the
+/// simulation back up where it was, see
+/// [`SceneManager::save_game`](crate::scene::SceneManager::save_game).
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    /// The loaded scenes, the persistent one first.
+    pub scenes: Vec<SavedScene>,
+    /// Index of the active scene in `scenes`.
+    pub active: usize,
+    /// State of the resources, see [`ResourceMap::save_state`](crate::resource::ResourceMap::save_state).
+    pub resources: BTreeMap<String, serde_json::Value>,
+}
+
+impl SaveGame {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BoxedError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), BoxedError> {
+        let writer = BufWriter::new(fs::File::create(path)?);
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+}
+
+impl Scene {
+    /// Saves the runtime state of the scene: the fields of its components marked
+    /// `#[save_state]`, the components left to start and the state of the simulation.
+    pub fn save_state(&self) -> SceneState {
+        self.save_game_objects_state(self.get_descendants_ordered(self.root()))
+    }
+
+    /// Saves the runtime state of some game objects of the scene, along with the time the
+    /// simulation has left to run, see [`Scene::save_state`].
+    pub(crate) fn save_game_objects_state(
+        &self,
+        game_objects: impl Iterator<Item = GameObject>,
+    ) -> SceneState {
+        let mut state = SceneState {
+            physics: PhysicsContext::save_state(self),
+            ..Default::default()
+        };
+        let component_registry = self.assets().component_registry.read();
+        let mut saved = HashSet::new();
+        for game_object in game_objects {
+            saved.insert(game_object);
+            let Some(entry) = self.entry(game_object) else {
+                continue;
+            };
+            let game_object_id = self.get_game_object_uuid(game_object);
+            for (component_id, component) in component_registry.components() {
+                let Some(value) = component
+                    .get_instance(&entry)
+                    .and_then(|instance| instance.save_state())
+                else {
+                    continue;
+                };
+                state
+                    .components
+                    .entry(game_object_id)
+                    .or_default()
+                    .insert(*component_id, value);
+            }
+        }
+        state.unstarted = self
+            .components_to_start
+            .iter()
+            .filter(|(game_object, _)| saved.contains(game_object))
+            .map(|(game_object, component_id)| {
+                (self.get_game_object_uuid(*game_object), *component_id)
+            })
+            .collect();
+        let saved_ids = saved
+            .iter()
+            .map(|game_object| self.get_game_object_uuid(*game_object))
+            .collect::<HashSet<_>>();
+        state
+            .physics
+            .bodies
+            .retain(|game_object_id, _| saved_ids.contains(game_object_id));
+        state
+    }
+
+    /// Restores the state saved by [`Scene::save_state`] to the scene it was saved with.
+    pub fn load_state(&mut self, state: &SceneState) {
+        self.prepare();
+        PhysicsContext::load_state(self, &state.physics);
+        let component_registry_ref = self.assets().component_registry.clone();
+        let component_registry = component_registry_ref.read();
+        for (game_object_id, components) in &state.components {
+            let Some(game_object) = self.get_game_object_by_uuid(*game_object_id) else {
+                continue;
+            };
+            for (component_id, value) in components {
+                let Some(component) = component_registry.component(*component_id) else {
+                    continue;
+                };
+                let Some(mut entry) = self.entry_mut(game_object) else {
+                    continue;
+                };
+                if let Some(instance) = component.get_instance_mut(&mut entry) {
+                    instance.load_state(value);
+                }
+            }
+        }
+        self.components_to_start = state
+            .unstarted
+            .iter()
+            .filter_map(|(game_object_id, component_id)| {
+                Some((
+                    self.get_game_object_by_uuid(*game_object_id)?,
+                    *component_id,
+                ))
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use crate as engine;
+    use crate::component::{
+        Component, ComponentCollider, ComponentInstance, ComponentRigidBody, ReflectComponent,
+    };
+    use crate::reflect::{Reflect, ReflectDefault};
+    use crate::resource::ResourceMap;
+    use crate::scene::{SaveGame, SceneData, SceneManager};
+    use crate::test_utils::{self, FrameCounter};
+    use crate::utils::{ReflectTypeUuidDynamic, TypeUuid};
+
+    #[derive(Default, TypeUuid, Serialize, Deserialize, Component, Reflect)]
+    #[uuid = "5a9c3e17-2b64-4f8d-9e1a-c7d0b3f62a48"]
+    #[reflect(Default, TypeUuidDynamic, Component)]
+    struct Explorer {
+        #[serde(skip)]
+        #[reflect_skip]
+        #[save_state]
+        visits: u32,
+        /// JSON keys have to be strings, so this one can't be saved.
+        #[serde(skip)]
+        #[reflect_skip]
+        #[save_state]
+        visited: HashMap<(i32, i32), u32>,
+    }
+
+    impl Component for Explorer {}
+
+    fn run(scenes: &mut SceneManager, resources: &mut ResourceMap, frames: usize) {
+        let input = test_utils::input();
+        for _ in 0..frames {
+            resources.time_mut().delta_time = 1.0 / 60.0;
+            resources.time_mut().time += 1.0 / 60.0;
+            scenes.prepare();
+            scenes.update(resources, &input);
+        }
+    }
+
+    #[test]
+    fn save_and_load_game() {
+        let assets = test_utils::assets();
+        let mut scenes = SceneManager::new(assets.asset_registry.readonly());
+        let mut resources = ResourceMap::new();
+        let scene = scenes.current_scene_mut();
+        let ball = scene.create_game_object(None, None);
+        scene.add_component(ball, ComponentRigidBody::default());
+        scene.add_component(ball, ComponentCollider::default());
+        scene.add_component(ball, FrameCounter::default());
+        let ball_id = scene.get_game_object_uuid(ball);
+        scenes.start_simulation();
+        run(&mut scenes, &mut resources, 10);
+
+        let save = scenes.save_game(&resources);
+        let save = SaveGame::from_bytes(&serde_json::to_vec(&save).unwrap()).unwrap();
+        assert_eq!(save.resources.keys().collect::<Vec<_>>(), ["time"]);
+        let mut loaded = SceneManager::new(assets.asset_registry.readonly());
+        let mut loaded_resources = ResourceMap::new();
+        loaded.load_game(&save, &mut loaded_resources);
+        loaded.start_simulation();
+        assert_eq!(loaded_resources.time().time(), resources.time().time());
+
+        run(&mut scenes, &mut resources, 5);
+        run(&mut loaded, &mut loaded_resources, 5);
+        let (scene, loaded_scene) = (scenes.simulation_scene(), loaded.simulation_scene());
+        assert_eq!(
+            serde_json::to_value(<SceneData>::from(scene)).unwrap(),
+            serde_json::to_value(<SceneData>::from(loaded_scene)).unwrap()
+        );
+        let ball = scene.get_game_object_by_uuid(ball_id).unwrap();
+        let loaded_ball = loaded_scene.get_game_object_by_uuid(ball_id).unwrap();
+        assert_eq!(
+            loaded_scene.read_component(loaded_ball, |c: &FrameCounter| c.frames),
+            Some(15)
+        );
+        let velocity = |scene: &crate::scene::Scene, ball| {
+            let handle = scene.physics.rigid_body_handle(ball).unwrap();
+            *scene.physics.bodies[handle].linvel()
+        };
+        assert!(velocity(scene, ball.entity).y < 0.0);
+        assert_eq!(
+            velocity(scene, ball.entity),
+            velocity(loaded_scene, loaded_ball.entity)
+        );
+    }
+
+    #[test]
+    fn state_fields_saved_separately() {
+        let explorer = Explorer {
+            visits: 3,
+            visited: HashMap::from([((0, 1), 2)]),
+        };
+        let state = explorer.save_state().unwrap();
+        assert_eq!(state, json!({ "visits": 3 }));
+
+        let mut loaded = Explorer::default();
+        loaded.load_state(&state);
+        assert_eq!(loaded.visits, 3);
+    }
+}
@@ -1,6 +1,8 @@
-use bimap::BiHashMap;
+use indexmap::IndexMap;
+use legion::storage::ComponentTypeId;
+use legion::systems::Resources;
 use legion::world::{Entry, EntryRef};
-use legion::{Entity, EntityStore, IntoQuery, World};
+use legion::{Entity, EntityStore, IntoQuery, Schedule, World};
 use nalgebra_glm::Mat4;
 use petgraph::prelude::{EdgeRef, StableGraph};
 use petgraph::stable_graph::{DefaultIx, NodeIndex, WalkNeighbors};
@@ -8,23 +10,31 @@ use petgraph::visit::{Bfs, Dfs, Reversed, Walker};
 use petgraph::Direction;
 use serde::de::DeserializeSeed;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::collections::{HashMap, HashSet};
+use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::Path;
 use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use uuid::Uuid;
 
 use crate as engine;
 use crate::assets::error::AssetError;
-use crate::assets::{Asset, LoadedAsset};
-use crate::component::{Component, ComponentEventContext, ComponentTransform};
-use crate::component::{ComponentCamera, ComponentID};
+use crate::assets::{reflect_dependencies, Asset, LoadedAsset};
+use crate::component::{
+    Component, ComponentEventContext, ComponentPrefabInstance, ComponentTransform, ReflectComponent,
+};
+use crate::component::{ComponentCamera, ComponentCollider, ComponentID, ComponentRigidBody};
 use crate::context::ReadOnlyAssetContext;
 use crate::input::Input;
 use crate::math::Transform;
-use crate::physics::{PhysicsConfiguration, PhysicsContext};
-use crate::reflect::{ReflectDefault, TypeInfo};
+use crate::physics::{PhysicsConfiguration, PhysicsContext, PhysicsEvent};
+use crate::reflect::{Reflect, ReflectDefault};
 use crate::resource::ResourceMap;
-use crate::scene::{GameObjectRef, Prefab};
+use crate::scene::prefab_instance::contains_prefab;
+use crate::scene::{
+    FrameTime, OpaqueComponent, PendingGameObject, Prefab, SceneCommands, SceneError,
+    SceneHierarchy, SceneLoadError, SceneLoadErrorKind, SceneLoadReport, SerializedComponent,
+    SystemSchedule, SystemStage, WorldTransforms, DEFAULT_LAYERS, SCENE_FORMAT_VERSION,
+};
 use crate::utils::{ContextSeed, TypeUuid};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -33,10 +43,41 @@ pub struct GameObject {
     pub entity: Entity,
 }
 
-#[derive(Default, Clone, Serialize, Deserialize)]
-pub struct SceneData {
-    pub components: HashMap<Uuid, HashMap<Uuid, serde_json::Value>>,
-    pub hierarchy: HashMap<Uuid, Uuid>,
+/// Serialized form of a scene, or of a part of it for prefabs.
+///
+/// Game objects are kept in hierarchy order, parents first and children in sibling order, and
+/// their components sorted by type uuid, so that saving an unchanged scene gives the same file.
+///
+/// Components are JSON values by default, and binary data in [`BinarySceneData`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SceneData<C = serde_json::Value> {
+    /// Format version, see [`SCENE_FORMAT_VERSION`]. Missing from scenes saved before versioning.
+    #[serde(default)]
+    pub version: u32,
+    pub components: IndexMap<Uuid, BTreeMap<Uuid, C>>,
+    /// Version of the data of each component type, for types with a version other than 0.
+    #[serde(default)]
+    pub component_versions: BTreeMap<Uuid, u32>,
+    /// Version of the data of single components, by game object, for components whose data
+    /// isn't at the version of their type, such as components that failed to migrate.
+    #[serde(default)]
+    pub instance_versions: BTreeMap<Uuid, BTreeMap<Uuid, u32>>,
+    pub hierarchy: IndexMap<Uuid, Uuid>,
+    #[serde(default)]
+    pub physics: PhysicsConfiguration,
+}
+
+impl<C> Default for SceneData<C> {
+    fn default() -> Self {
+        Self {
+            version: SCENE_FORMAT_VERSION,
+            components: Default::default(),
+            component_versions: Default::default(),
+            instance_versions: Default::default(),
+            hierarchy: Default::default(),
+            physics: Default::default(),
+        }
+    }
 }
 
 #[derive(TypeUuid)]
@@ -44,16 +85,38 @@ pub struct SceneData {
 pub struct Scene {
     pub world: World,
     pub physics: PhysicsContext,
-    uuid_map: HashMap<Uuid, GameObject>,
-    entity_map: HashMap<Entity, NodeIndex>,
+    pub(crate) uuid_map: HashMap<Uuid, GameObject>,
+    pub(crate) entity_map: HashMap<Entity, NodeIndex>,
     entity_arena: StableGraph<Entity, i32>,
     root: GameObject,
     transform_cache: RwLock<HashMap<NodeIndex, Transform>>,
     camera: Option<GameObject>,
-    objects_to_delete: HashSet<GameObject>,
-    components_to_start: HashSet<(GameObject, Uuid)>,
+    pub(crate) objects_to_delete: HashSet<GameObject>,
+    pub(crate) components_to_start: HashSet<(GameObject, Uuid)>,
+    pub(crate) commands: SceneCommands,
+    pub(crate) spawned_game_objects: HashMap<PendingGameObject, GameObject>,
     new_index: usize,
+    pub(crate) opaque_components: HashMap<Uuid, HashMap<Uuid, OpaqueComponent>>,
+    load_report: SceneLoadReport,
     assets: ReadOnlyAssetContext,
+    /// Instances swapped into entities while their component is detached, reused between hooks.
+    spare_components: HashMap<Uuid, Vec<Box<dyn Component>>>,
+    detached_components: Vec<DetachedComponent>,
+    pub(crate) system_schedules: HashMap<SystemStage, SystemSchedule>,
+}
+
+/// Component instance moved out of its entity by [`Scene::with_component_mut`].
+struct DetachedComponent {
+    game_object: GameObject,
+    component_id: Uuid,
+    type_id: TypeId,
+    change: Option<DetachedChange>,
+}
+
+/// Change made to a detached component through the scene, applied once it's attached back.
+enum DetachedChange {
+    Removed,
+    Replaced(Box<dyn Reflect>),
 }
 
 pub struct WalkChildren {
@@ -85,6 +148,11 @@ impl Scene {
             id,
             name: String::from("Root"),
             visible: true,
+            active: true,
+            active_in_hierarchy: true,
+            layers: DEFAULT_LAYERS,
+            tags: Default::default(),
+            disabled_components: Default::default(),
         });
         Self {
             world,
@@ -97,8 +165,15 @@ impl Scene {
             camera: Default::default(),
             objects_to_delete: Default::default(),
             components_to_start: Default::default(),
+            commands: Default::default(),
+            spawned_game_objects: Default::default(),
             new_index: 0,
+            opaque_components: Default::default(),
+            load_report: Default::default(),
             assets,
+            spare_components: Default::default(),
+            detached_components: Default::default(),
+            system_schedules: Default::default(),
         }
     }
 }
@@ -125,7 +200,35 @@ impl Asset for Scene {
     where
         Self: Sized,
     {
-        LoadedAsset::<Self>::from_json_file_ctx(assets, path)
+        let bytes = std::fs::read(path).map_err(|_| AssetError::LoadError)?;
+        Scene::from_bytes(assets, &bytes)
+            .map(LoadedAsset::new)
+            .map_err(|_| AssetError::LoadError)
+    }
+
+    fn has_dependencies() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    /// Assets referenced by the `AssetRef` fields of the components, see [`reflect_dependencies`].
+    fn dependencies(&self) -> BTreeSet<Uuid> {
+        let mut dependencies = BTreeSet::new();
+        let type_registry = self.assets.type_registry.read();
+        let component_registry = self.assets.component_registry.read();
+        for game_object in self.get_descendants_ordered(self.root) {
+            let Some(entry) = self.entry(game_object) else {
+                continue;
+            };
+            for (_, component) in component_registry.components() {
+                if let Some(instance) = component.get_instance(&entry) {
+                    reflect_dependencies(&type_registry, instance.as_reflect(), &mut dependencies);
+                }
+            }
+        }
+        dependencies
     }
 }
 
@@ -148,7 +251,7 @@ impl<'de> DeserializeSeed<'de> for ContextSeed<'de, ReadOnlyAssetContext, Scene>
     {
         Ok(Scene::from((
             self.context,
-            SceneData::deserialize(deserializer)?,
+            SceneData::<serde_json::Value>::deserialize(deserializer)?,
         )))
     }
 }
@@ -160,75 +263,36 @@ impl Clone for Scene {
     }
 }
 
-impl From<(&ReadOnlyAssetContext, SceneData)> for Scene {
-    fn from((assets, value): (&ReadOnlyAssetContext, SceneData)) -> Self {
+impl<C: SerializedComponent> From<(&ReadOnlyAssetContext, SceneData<C>)> for Scene {
+    fn from((assets, mut value): (&ReadOnlyAssetContext, SceneData<C>)) -> Self {
         let mut scene = assets.scene();
-        for (_, components) in value.components {
-            let game_object = scene.new_game_object(None);
-            for (component_id, data) in components {
-                let registry = assets.component_registry.read();
-                let Some(component) = registry.component(component_id) else {
-                    continue;
-                };
-                let Some(instance) = component.deserialize(data) else {
-                    continue;
-                };
-                let Some(mut entry) = scene.entry_mut(game_object) else {
-                    continue;
-                };
-                let _ = component.bind_instance(&mut entry, instance);
-            }
-            let mut id = None;
-            if let Some(entry) = scene.entry(game_object) {
-                if let Ok(c_id) = entry.get_component::<ComponentID>() {
-                    id = Some(c_id.id);
-                }
-            }
-            if let Some(id) = id {
-                scene.uuid_map.insert(id, game_object);
-            }
-        }
-        for (id, parent) in value.hierarchy {
-            if let Some(game_object) = scene.get_game_object_by_uuid(id) {
-                if let Some(parent) = scene.get_game_object_by_uuid(parent) {
-                    scene.set_parent(game_object, Some(parent));
-                }
-            }
-        }
+        scene.physics.config = std::mem::take(&mut value.physics);
+        scene.load_report = scene.add_game_objects(value);
         scene
     }
 }
 
-impl From<&Scene> for SceneData {
+impl<C: SerializedComponent> From<&Scene> for SceneData<C> {
     fn from(scene: &Scene) -> Self {
-        let world = &scene.world;
-        let mut query = <(Entity, &ComponentID)>::query();
-        let mut data = Default::default();
-        for (entity, id) in query.iter(world) {
-            let Some(game_object) = scene.get_game_object_from_entity(*entity) else {
-                continue;
-            };
-            scene.serialize_game_object(game_object, id.id, &mut data);
+        let mut data = SceneData {
+            physics: scene.physics.config.clone(),
+            ..Default::default()
+        };
+        for game_object in scene.without_prefab_members(scene.get_descendants_ordered(scene.root)) {
+            scene.serialize_game_object(game_object, &mut data, false);
         }
         data
     }
 }
 
-impl From<(&Scene, GameObject)> for SceneData {
+impl<C: SerializedComponent> From<(&Scene, GameObject)> for SceneData<C> {
     fn from((scene, game_object): (&Scene, GameObject)) -> Self {
         let mut data = Default::default();
-        std::iter::once(game_object.node)
-            .chain(Bfs::new(&scene.entity_arena, game_object.node).iter(&scene.entity_arena))
-            .filter_map(|c| scene.get_game_object_from_node(c))
-            .for_each(|game_object| {
-                let Some(entry) = scene.entry(game_object) else {
-                    return;
-                };
-                let Ok(id) = entry.get_component::<ComponentID>() else {
-                    return;
-                };
-                scene.serialize_game_object(game_object, id.id, &mut data);
-            });
+        for game_object in scene.without_prefab_members(
+            std::iter::once(game_object).chain(scene.get_descendants_ordered(game_object)),
+        ) {
+            scene.serialize_game_object(game_object, &mut data, false);
+        }
         data
     }
 }
@@ -247,14 +311,125 @@ impl Scene {
         })
     }
 
-    pub(crate) fn serialize_game_object(
+    /// Adds the game objects of scene data to the scene, keeping their ids. Game objects whose
+    /// parent isn't part of the data are added under that parent if the scene has it, and at
+    /// the root otherwise.
+    pub(crate) fn add_game_objects<C: SerializedComponent>(
+        &mut self,
+        mut value: SceneData<C>,
+    ) -> SceneLoadReport {
+        let component_registry_ref = self.assets.component_registry.clone();
+        let registry = component_registry_ref.read();
+        let mut report = value.migrate(&registry);
+        let game_object_ids = value.components.keys().copied().collect::<Vec<_>>();
+        for (game_object_id, components) in std::mem::take(&mut value.components) {
+            let game_object = self.new_game_object(None);
+            for (component_id, data) in components {
+                let version = value.component_version(game_object_id, component_id);
+                let current_version = registry.component_version(component_id);
+                let error = match registry.component(component_id) {
+                    None => SceneLoadErrorKind::UnknownComponent,
+                    Some(_) if version > current_version => {
+                        SceneLoadErrorKind::NewerVersion(version)
+                    }
+                    Some(_) if version < current_version => {
+                        SceneLoadErrorKind::OutdatedVersion(version)
+                    }
+                    Some(component) => match data.deserialize(component) {
+                        Ok(instance) => {
+                            let Some(mut entry) = self.entry_mut(game_object) else {
+                                continue;
+                            };
+                            if component.bind_instance(&mut entry, instance) {
+                                self.components_to_start.insert((game_object, component_id));
+                            }
+                            continue;
+                        }
+                        Err(err) => SceneLoadErrorKind::InvalidData(err),
+                    },
+                };
+                report.errors.push(SceneLoadError {
+                    game_object: game_object_id,
+                    component: component_id,
+                    kind: error,
+                });
+                if let Some(data) = data.into_opaque() {
+                    self.opaque_components
+                        .entry(game_object_id)
+                        .or_default()
+                        .insert(component_id, OpaqueComponent { version, data });
+                }
+            }
+            let mut id = None;
+            if let Some(entry) = self.entry(game_object) {
+                if let Ok(c_id) = entry.get_component::<ComponentID>() {
+                    id = Some(c_id.id);
+                }
+            }
+            if let Some(id) = id {
+                self.uuid_map.insert(id, game_object);
+            }
+        }
+        drop(registry);
+        // Prefab instances are built before parents are set, as game objects added to an
+        // instance are children of its game objects
+        let prefab_instances = game_object_ids
+            .iter()
+            .filter_map(|id| self.get_game_object_by_uuid(*id))
+            .filter(|go| {
+                self.read_component(*go, |_: &ComponentPrefabInstance| ())
+                    .is_some()
+            })
+            .collect::<Vec<_>>();
+        for root in prefab_instances {
+            self.expand_prefab_instance(root);
+        }
+        // Parents are set in hierarchy order to keep the order of siblings
+        for id in &game_object_ids {
+            let Some(parent) = value.hierarchy.get(id) else {
+                continue;
+            };
+            if let Some(game_object) = self.get_game_object_by_uuid(*id) {
+                if let Some(parent) = self.get_game_object_by_uuid(*parent) {
+                    self.set_parent(game_object, Some(parent));
+                }
+            }
+        }
+        // Game objects are added active in the hierarchy until their parents are known
+        let loaded = game_object_ids.iter().collect::<HashSet<_>>();
+        for id in &game_object_ids {
+            if value
+                .hierarchy
+                .get(id)
+                .is_some_and(|parent| loaded.contains(parent))
+            {
+                continue;
+            }
+            if let Some(game_object) = self.get_game_object_by_uuid(*id) {
+                self.refresh_active_in_hierarchy(game_object);
+            }
+        }
+        report
+    }
+
+    /// Writes a game object to scene data. Unless `expand_instances` is set, the roots of
+    /// prefab instances are written with their [`ComponentID`] and a [`ComponentPrefabInstance`]
+    /// holding their overrides only, their other components coming from the prefab.
+    pub(crate) fn serialize_game_object<C: SerializedComponent>(
         &self,
         game_object: GameObject,
-        game_object_id: Uuid,
-        data: &mut SceneData,
+        data: &mut SceneData<C>,
+        expand_instances: bool,
     ) {
+        let Some(game_object_id) = self.read_component(game_object, |c_id: &ComponentID| c_id.id)
+        else {
+            return;
+        };
         'insert_hierarchy: {
-            let Some(parent) = self.get_parent_game_object(game_object) else {
+            let Some(parent) = self
+                .get_parent_game_object(game_object)
+                .filter(|parent| *parent != self.root)
+            else {
                 break 'insert_hierarchy;
             };
             let Some(entry) = self.entry(parent) else {
@@ -266,21 +441,74 @@ impl Scene {
             data.hierarchy.insert(game_object_id, parent_id.id);
         }
 
-        for (component_id, component) in self.assets.component_registry.read().components() {
+        let prefab_instance = if expand_instances || self.prefab_expansion(game_object).is_none() {
+            None
+        } else {
+            Some(ComponentPrefabInstance {
+                prefab: self
+                    .read_component(game_object, |c: &ComponentPrefabInstance| c.prefab.clone())
+                    .unwrap_or_default(),
+                overrides: self.prefab_overrides(game_object),
+                expansion: None,
+            })
+        };
+
+        let component_registry = self.assets.component_registry.read();
+        for (component_id, component) in component_registry.components() {
             let Some(entry) = self.entry(game_object) else {
                 continue;
             };
-            let Some(instance) = component.get_instance(&entry) else {
-                continue;
+            let instance = match &prefab_instance {
+                Some(prefab_instance) if *component_id == ComponentPrefabInstance::type_uuid() => {
+                    prefab_instance
+                }
+                Some(_) if *component_id != ComponentID::type_uuid() => continue,
+                // Left out rather than written with the values of the spare taking its place
+                _ if self.is_component_detached(game_object, *component_id) => continue,
+                _ => {
+                    let Some(instance) = component.get_instance(&entry) else {
+                        continue;
+                    };
+                    instance
+                }
             };
-            let Some(value) = instance.serialize() else {
+            let Some(value) = C::serialize(instance) else {
                 continue;
             };
+            let version = component_registry.component_version(*component_id);
+            if version != 0 {
+                data.component_versions.insert(*component_id, version);
+            }
             data.components
                 .entry(game_object_id)
                 .or_default()
                 .insert(*component_id, value);
         }
+
+        if prefab_instance.is_some() {
+            return;
+        }
+        for (component_id, component) in self
+            .opaque_components
+            .get(&game_object_id)
+            .into_iter()
+            .flatten()
+        {
+            let current_version = component_registry.component_version(*component_id);
+            if component.version != current_version {
+                data.instance_versions
+                    .entry(game_object_id)
+                    .or_default()
+                    .insert(*component_id, component.version);
+            } else if current_version != 0 {
+                data.component_versions
+                    .insert(*component_id, current_version);
+            }
+            data.components
+                .entry(game_object_id)
+                .or_default()
+                .insert(*component_id, C::from_opaque(&component.data));
+        }
     }
 }
 
@@ -318,6 +546,7 @@ impl Scene {
         self.uuid_map.insert(id.id, game_object);
         self.bind_component(game_object, id);
         self.bind_component(game_object, ComponentTransform::default());
+        self.refresh_active_in_hierarchy(game_object);
         game_object
     }
 
@@ -325,90 +554,23 @@ impl Scene {
         self.objects_to_delete.insert(game_object);
     }
 
-    pub fn create_prefab(&self, game_object: GameObject) -> Prefab {
+    /// Makes a prefab out of a game object and its descendants. `replaced` is the id of the
+    /// prefab it's going to be saved over if any, which the game object can't hold an instance
+    /// of.
+    pub fn create_prefab(
+        &self,
+        game_object: GameObject,
+        replaced: Option<Uuid>,
+    ) -> Result<Prefab, SceneError> {
         let data: SceneData = (self, game_object).into();
+        if replaced.is_some_and(|id| contains_prefab(&self.assets, &data, id)) {
+            return Err(SceneError::PrefabCycle);
+        }
 
-        Prefab {
+        Ok(Prefab {
             data: data.clone(),
             scene: (&self.assets, data).into(),
-        }
-    }
-
-    pub fn instantiate_prefab(&mut self, prefab: &Prefab, parent: Option<GameObject>) {
-        let root_node = prefab.scene.game_objects().next().unwrap();
-
-        let mut id_mapping: BiHashMap<Uuid, Uuid> = Default::default();
-        for (game_object_id, _) in &prefab.data.components {
-            let new_game_object_id = Uuid::new_v4();
-            id_mapping.insert(*game_object_id, new_game_object_id);
-        }
-
-        for (game_object_id, components) in prefab.data.components.iter() {
-            let game_object = self.new_game_object(None);
-            let new_game_object_id = *id_mapping.get_by_left(game_object_id).unwrap();
-            for (component_id, data) in components {
-                let asset_registry_ref = self.assets.asset_registry.clone();
-                let asset_registry = asset_registry_ref.read();
-                let component_registry_ref = self.assets.component_registry.clone();
-                let component_registry = component_registry_ref.read();
-                let type_registry_ref = self.assets.type_registry.clone();
-                let type_registry = type_registry_ref.read();
-                let Some(TypeInfo::Struct(struct_info)) =
-                    type_registry.type_info_by_id(*component_id)
-                else {
-                    continue;
-                };
-                let Some(component) = component_registry.component(*component_id) else {
-                    continue;
-                };
-                let Some(mut instance) = component.deserialize(data.clone()) else {
-                    continue;
-                };
-                let Some(mut entry) = self.entry_mut(game_object) else {
-                    continue;
-                };
-                for (name, field) in &struct_info.fields {
-                    let Some(id) = field.get::<GameObjectRef>(&*instance).map(|r| r.id()) else {
-                        continue;
-                    };
-                    let Some(target_id) = id_mapping.get_by_left(&id) else {
-                        continue;
-                    };
-                    field.set(&mut *instance, GameObjectRef::new(*target_id));
-                }
-                let _ = component.bind_instance(&mut entry, instance);
-            }
-            if let Some(mut entry) = self.entry_mut(game_object) {
-                if let Ok(c_id) = entry.get_component_mut::<ComponentID>() {
-                    c_id.id = new_game_object_id;
-                }
-            }
-            self.uuid_map.insert(new_game_object_id, game_object);
-        }
-
-        for (id, parent_id) in prefab.data.hierarchy.iter() {
-            let Some(new_id) = id_mapping.get_by_left(id) else {
-                continue;
-            };
-            let Some(new_parent_id) = id_mapping.get_by_left(parent_id) else {
-                continue;
-            };
-            let Some(game_object) = self.get_game_object_by_uuid(*new_id) else {
-                continue;
-            };
-            let Some(parent) = self.get_game_object_by_uuid(*new_parent_id) else {
-                continue;
-            };
-            self.set_parent(game_object, Some(parent));
-        }
-
-        if let Some(parent) = parent {
-            if let Some(game_object) =
-                self.get_game_object_by_uuid(prefab.scene.get_game_object_uuid(root_node))
-            {
-                self.set_parent(game_object, Some(parent));
-            }
-        }
+        })
     }
 
     pub fn set_parent(&mut self, game_object: GameObject, parent: Option<GameObject>) {
@@ -452,6 +614,9 @@ impl Scene {
         let insert_index = insert_index.unwrap_or_else(|| self.next_edge_index(Some(parent)));
         self.entity_arena
             .add_edge(parent.node, game_object.node, insert_index);
+        PhysicsContext::mark_dirty(self, game_object);
+        let changed = self.refresh_active_in_hierarchy(game_object);
+        self.notify_active_changed(changed);
     }
 
     pub fn get_index_in_parent(
@@ -474,6 +639,23 @@ impl Scene {
         })
     }
 
+    /// Moves a game object to the given position among its siblings.
+    pub fn set_sibling_index(&mut self, game_object: GameObject, index: i32) {
+        let parent = self
+            .get_parent_game_object(game_object)
+            .unwrap_or(self.root);
+        let Some(edge) = self.entity_arena.find_edge(parent.node, game_object.node) else {
+            return;
+        };
+        let current = self.entity_arena[edge];
+        self.entity_arena.remove_edge(edge);
+        self.shift_edge_weights(parent, current, -1);
+        let index = index.clamp(0, self.next_edge_index(Some(parent)));
+        self.shift_edge_weights(parent, index, 1);
+        self.entity_arena
+            .add_edge(parent.node, game_object.node, index);
+    }
+
     fn shift_edge_weights(&mut self, parent: GameObject, start: i32, offset: i32) {
         let mut walker = self.entity_arena.neighbors(parent.node).detach();
         while let Some((edge, _)) = walker.next(&self.entity_arena) {
@@ -516,7 +698,7 @@ impl Scene {
                 if let Some(camera) = &self.camera {
                     go == camera
                 } else {
-                    c.enabled
+                    c.enabled && self.is_component_enabled::<ComponentCamera>(*go)
                 }
             })
             .map(|(go, c)| (go, c))
@@ -558,11 +740,33 @@ impl Scene {
             .insert((game_object, component_uuid));
     }
 
+    pub fn remove_component<T: Component + TypeUuid>(&mut self, game_object: GameObject) {
+        self.remove_component_dyn(game_object, T::type_uuid());
+    }
+
+    pub fn remove_component_dyn(&mut self, game_object: GameObject, type_uuid: Uuid) {
+        let component_registry_ref = self.assets.component_registry.clone();
+        let component_registry = component_registry_ref.read();
+        let Some(component) = component_registry.component(type_uuid) else {
+            return;
+        };
+        if let Some(detached) = self.detached_component_mut(game_object, type_uuid) {
+            detached.change = Some(DetachedChange::Removed);
+        } else if let Some(mut entry) = self.entry_mut(game_object) {
+            component.remove_instance(&mut entry);
+        }
+        self.components_to_start.remove(&(game_object, type_uuid));
+    }
+
     pub(crate) fn bind_component<T: Component + Send + Sync + 'static>(
         &mut self,
         game_object: GameObject,
         component: T,
     ) {
+        if let Some(detached) = self.detached_component_mut(game_object, component.uuid()) {
+            detached.change = Some(DetachedChange::Replaced(Box::new(component)));
+            return;
+        }
         self.entry_mut(game_object)
             .map(|mut e| e.add_component(component));
     }
@@ -578,33 +782,19 @@ impl Scene {
         let Some(component) = component_registry.component(type_uuid) else {
             return;
         };
-        let self_ptr = unsafe { self.as_ptr_mut() };
-        let assets = self.assets.clone();
-        self.entry_mut(game_object).map(|mut e| {
-            let result = component.bind_instance(&mut e, meta.default());
-            if result {
-                if let Some(instance) = component.get_instance_mut(&mut e) {
-                    instance.reset(ComponentEventContext {
-                        assets: &assets,
-                        scene: unsafe { &mut *self_ptr },
-                        game_object,
-                    });
-                }
-            }
-            result
-        });
-    }
-
-    pub unsafe fn get_component_ptr(
-        &mut self,
-        game_object: GameObject,
-        component: &dyn Component,
-    ) -> Option<*mut dyn Component> {
-        self.entry_mut(game_object).and_then(|mut entry| {
-            component
-                .get_instance_mut(&mut entry)
-                .map(|instance| instance as *mut dyn Component)
-        })
+        // The component is being used by a hook, it's replaced afterwards without a reset.
+        if let Some(detached) = self.detached_component_mut(game_object, type_uuid) {
+            detached.change = Some(DetachedChange::Replaced(meta.default()));
+            self.components_to_start.insert((game_object, type_uuid));
+            return;
+        }
+        let bound = self
+            .entry_mut(game_object)
+            .is_some_and(|mut e| component.bind_instance(&mut e, meta.default()));
+        if bound {
+            self.components_to_start.insert((game_object, type_uuid));
+            self.call_hook(game_object, component, |instance, ctx| instance.reset(ctx));
+        }
     }
 
     pub fn entry(&self, game_object: GameObject) -> Option<EntryRef> {
@@ -624,6 +814,7 @@ impl Scene {
         entry
             .as_ref()
             .and_then(|entry| entry.get_component::<T>().ok())
+            .filter(|instance| !self.is_component_detached(game_object, instance.uuid()))
             .map(reader)
     }
 
@@ -632,55 +823,498 @@ impl Scene {
         game_object: GameObject,
         writer: F,
     ) -> Option<()> {
-        let mut entry = self.entry_mut(game_object);
+        let mut entry = self.world.entry(game_object.entity);
         entry
             .as_mut()
             .and_then(|entry| entry.get_component_mut::<T>().ok())
+            .filter(|instance| {
+                !self
+                    .detached_components
+                    .iter()
+                    .any(|d| d.game_object == game_object && d.component_id == instance.uuid())
+            })
             .map(writer)
     }
 
+    /// What went wrong when loading the scene. Components that failed to load are kept as they
+    /// were saved, and written back when the scene is serialized.
+    pub fn load_report(&self) -> &SceneLoadReport {
+        &self.load_report
+    }
+
+    pub fn assets(&self) -> &ReadOnlyAssetContext {
+        &self.assets
+    }
+
     pub fn prepare(&mut self) {
         self.delete_game_objects();
+        self.refresh_prefab_instances();
         self.clear_transform_cache();
         PhysicsContext::prepare(self);
     }
 
     pub fn update(&mut self, resources: &mut ResourceMap, input: &Input) {
-        PhysicsContext::update(self, resources.time(), &PhysicsConfiguration::default());
+        self.start_components();
         let component_registry_ref = self.assets.component_registry.clone();
         let component_registry = component_registry_ref.read();
-        // No way around this, we want component's update method to take &mut self
-        // but there's no way to do that and provide a &mut Scene
-        // At worst, this is a race condition because we can guarantee that
-        // this reference only lives until the end of this function
-        let scene = unsafe { &mut *(self as *mut Self) };
-        let scene2 = unsafe { &mut *(self as *mut Self) };
-        let assets = self.assets.clone();
-        for (_, component) in component_registry.components_update() {
-            for game_object in <Entity>::query()
-                .iter(&self.world)
-                .filter_map(|e| self.get_game_object_from_entity(*e))
+        let fixed_update = component_registry
+            .components_fixed_update()
+            .map(|(id, component)| (id, component.as_ref()))
+            .collect::<Vec<_>>();
+        let mut system_resources = Resources::default();
+        let fixed_time = FrameTime {
+            time: resources.time().time(),
+            delta_time: self.physics.config.fixed_time_step,
+        };
+        for _ in 0..self.physics.pending_steps(resources.time()) {
+            self.for_each_enabled_component(&fixed_update, |component, ctx| {
+                component.fixed_update(ctx, resources, input)
+            });
+            self.run_systems(SystemStage::FixedUpdate, fixed_time, &mut system_resources);
+            PhysicsContext::step_scene(self);
+        }
+        let frame_time = FrameTime {
+            time: resources.time().time(),
+            delta_time: resources.time().delta_time(),
+        };
+        system_resources.insert(PhysicsContext::rigid_body_poses(self));
+        self.run_systems(SystemStage::Update, frame_time, &mut system_resources);
+        let update = component_registry
+            .components_update()
+            .map(|(id, component)| (id, component.as_ref()))
+            .collect::<Vec<_>>();
+        self.for_each_enabled_component(&update, |component, ctx| {
+            component.update(ctx, resources, input)
+        });
+        let late_update = component_registry
+            .components_late_update()
+            .map(|(id, component)| (id, component.as_ref()))
+            .collect::<Vec<_>>();
+        self.for_each_enabled_component(&late_update, |component, ctx| {
+            component.late_update(ctx, resources, input)
+        });
+        self.apply_commands();
+        system_resources.insert(self.hierarchy());
+        system_resources.insert(WorldTransforms::default());
+        self.run_systems(SystemStage::PostUpdate, frame_time, &mut system_resources);
+        if let Some(WorldTransforms(world_transforms)) = system_resources.remove() {
+            self.transform_cache_mut().extend(world_transforms);
+        }
+    }
+
+    /// Runs the systems of a stage on legion's scheduler. The schedule is built once and kept
+    /// until the component registry changes, see [`SystemSchedule`].
+    fn run_systems(&mut self, stage: SystemStage, time: FrameTime, resources: &mut Resources) {
+        let generation = self.assets.component_registry.read().generation();
+        let mut schedule = match self.system_schedules.remove(&stage) {
+            Some(schedule) if schedule.generation() == generation => {
+                Schedule::from(schedule.into_steps())
+            }
+            _ => {
+                let component_registry_ref = self.assets.component_registry.clone();
+                let component_registry = component_registry_ref.read();
+                let mut schedule = Schedule::builder();
+                for system in component_registry.systems(stage) {
+                    system.add_to_schedule(&mut schedule);
+                }
+                schedule.build()
+            }
+        };
+        resources.insert(time);
+        resources.insert(self.assets.clone());
+        schedule.execute(&mut self.world, resources);
+        if let Some(schedule) = SystemSchedule::new(generation, schedule.into_vec()) {
+            self.system_schedules.insert(stage, schedule);
+        }
+    }
+
+    /// Lists the game objects with their parent, parents first.
+    fn hierarchy(&self) -> SceneHierarchy {
+        SceneHierarchy(
+            Bfs::new(&self.entity_arena, self.root.node)
+                .iter(&self.entity_arena)
+                .filter_map(|node| self.get_game_object_from_node(node))
+                .map(|go| {
+                    let parent = self.get_parent_game_object(go).map(|parent| parent.node);
+                    (go, parent)
+                })
+                .collect(),
+        )
+    }
+
+    /// Calls a hook on every enabled instance of the given component types, one type after
+    /// the other in the given order. The hooks need the scene, so each instance is swapped out
+    /// of its entity on its own while its hook runs, see [`Scene::with_component_mut`].
+    fn for_each_enabled_component(
+        &mut self,
+        components: &[(Uuid, &dyn Component)],
+        mut hook: impl FnMut(&mut dyn Component, ComponentEventContext),
+    ) {
+        for (component_id, component) in components {
+            for game_object in component
+                .query_entities(&self.world)
+                .into_iter()
+                .filter_map(|e| self.get_game_object_from_entity(e))
+                .collect::<Vec<_>>()
             {
-                let Some(mut entry) = scene2.entry_mut(game_object) else {
-                    continue;
-                };
-                let Some(instance) = component.get_instance_mut(&mut entry) else {
+                if self.is_component_enabled_dyn(game_object, *component_id)
+                    && self.is_active_in_hierarchy(game_object)
+                {
+                    self.call_hook(game_object, *component, &mut hook);
+                }
+            }
+        }
+    }
+
+    /// Calls a hook on a component instance, with the scene it belongs to.
+    fn call_hook(
+        &mut self,
+        game_object: GameObject,
+        component: &dyn Component,
+        hook: impl FnOnce(&mut dyn Component, ComponentEventContext),
+    ) {
+        let assets = self.assets.clone();
+        self.with_component_mut(game_object, component, |instance, scene| {
+            hook(
+                instance,
+                ComponentEventContext {
+                    assets: &assets,
+                    scene,
+                    game_object,
+                },
+            )
+        });
+    }
+
+    /// Runs `f` on a component instance of a game object, along with the scene it belongs to.
+    /// Meanwhile the instance is swapped out of its entity for a spare one: the scene reads
+    /// and writes of that component give `None`, queries and serialized scene data leave it
+    /// out, and removing or adding it again takes effect once `f` returns. Returns `None` if
+    /// the game object doesn't have the component, or if it's already detached by an outer call.
+    pub fn with_component_mut<R>(
+        &mut self,
+        game_object: GameObject,
+        component: &dyn Component,
+        f: impl FnOnce(&mut dyn Component, &mut Scene) -> R,
+    ) -> Option<R> {
+        let mut instance = self.detach_component(game_object, component)?;
+        let result = f(instance.as_mut(), self);
+        self.attach_component(game_object, component, instance);
+        Some(result)
+    }
+
+    fn detach_component(
+        &mut self,
+        game_object: GameObject,
+        component: &dyn Component,
+    ) -> Option<Box<dyn Component>> {
+        let component_id = component.uuid();
+        if self.is_component_detached(game_object, component_id) {
+            return None;
+        }
+        let mut spare = match self
+            .spare_components
+            .get_mut(&component_id)
+            .and_then(Vec::pop)
+        {
+            Some(spare) => spare,
+            None => self.new_component_instance(component_id)?,
+        };
+        let swapped = self
+            .world
+            .entry(game_object.entity)
+            .is_some_and(|mut entry| component.swap_instance(&mut entry, spare.as_mut()));
+        if !swapped {
+            self.spare_components
+                .entry(component_id)
+                .or_default()
+                .push(spare);
+            return None;
+        }
+        self.detached_components.push(DetachedComponent {
+            game_object,
+            component_id,
+            type_id: Any::type_id(spare.as_any()),
+            change: None,
+        });
+        Some(spare)
+    }
+
+    fn attach_component(
+        &mut self,
+        game_object: GameObject,
+        component: &dyn Component,
+        mut instance: Box<dyn Component>,
+    ) {
+        let component_id = component.uuid();
+        let Some(index) = self
+            .detached_components
+            .iter()
+            .rposition(|d| d.game_object == game_object && d.component_id == component_id)
+        else {
+            return;
+        };
+        let detached = self.detached_components.remove(index);
+        let Some(mut entry) = self.world.entry(game_object.entity) else {
+            return;
+        };
+        match detached.change {
+            None => {
+                if component.swap_instance(&mut entry, instance.as_mut()) {
+                    self.spare_components
+                        .entry(component_id)
+                        .or_default()
+                        .push(instance);
+                }
+            }
+            Some(DetachedChange::Removed) => component.remove_instance(&mut entry),
+            Some(DetachedChange::Replaced(replacement)) => {
+                component.bind_instance(&mut entry, replacement);
+            }
+        }
+    }
+
+    /// Entities holding a spare instance of one of the given component types while their own
+    /// is detached, which queries leave out.
+    pub(crate) fn detached_entities(&self, types: &[ComponentTypeId]) -> HashSet<Entity> {
+        self.detached_components
+            .iter()
+            .filter(|d| types.iter().any(|ty| ty.type_id() == d.type_id))
+            .map(|d| d.game_object.entity)
+            .collect()
+    }
+
+    fn is_component_detached(&self, game_object: GameObject, component_id: Uuid) -> bool {
+        self.detached_components
+            .iter()
+            .any(|d| d.game_object == game_object && d.component_id == component_id)
+    }
+
+    fn detached_component_mut(
+        &mut self,
+        game_object: GameObject,
+        component_id: Uuid,
+    ) -> Option<&mut DetachedComponent> {
+        self.detached_components
+            .iter_mut()
+            .rev()
+            .find(|d| d.game_object == game_object && d.component_id == component_id)
+    }
+
+    fn new_component_instance(&self, component_id: Uuid) -> Option<Box<dyn Component>> {
+        let type_registry = self.assets.type_registry.read();
+        let instance = type_registry
+            .trait_meta::<ReflectDefault>(component_id)?
+            .default();
+        type_registry
+            .trait_meta::<ReflectComponent>(component_id)?
+            .get_boxed(instance)
+            .ok()
+    }
+
+    /// Calls `on_enable` and `start` on the enabled components added since the last update.
+    /// Disabled components, and components of inactive game objects, are started once they
+    /// get enabled.
+    fn start_components(&mut self) {
+        let to_start = self
+            .components_to_start
+            .iter()
+            .filter(|(game_object, component_id)| {
+                self.is_component_enabled_dyn(*game_object, *component_id)
+                    && self.is_active_in_hierarchy(*game_object)
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        for (game_object, component_id) in to_start {
+            self.components_to_start
+                .remove(&(game_object, component_id));
+            self.call_component_hook(game_object, component_id, |component, ctx| {
+                component.on_enable(ComponentEventContext {
+                    assets: ctx.assets,
+                    scene: &mut *ctx.scene,
+                    game_object,
+                });
+                component.start(ctx);
+            });
+        }
+    }
+
+    fn call_component_hook(
+        &mut self,
+        game_object: GameObject,
+        component_id: Uuid,
+        hook: impl FnOnce(&mut dyn Component, ComponentEventContext),
+    ) {
+        let component_registry_ref = self.assets.component_registry.clone();
+        let component_registry = component_registry_ref.read();
+        if let Some(component) = component_registry.component(component_id) {
+            self.call_hook(game_object, component, hook);
+        }
+    }
+
+    pub fn is_component_enabled<T: Component + TypeUuid>(&self, game_object: GameObject) -> bool {
+        self.is_component_enabled_dyn(game_object, T::type_uuid())
+    }
+
+    pub fn is_component_enabled_dyn(&self, game_object: GameObject, component_id: Uuid) -> bool {
+        self.read_component(game_object, |c_id: &ComponentID| {
+            c_id.is_component_enabled(component_id)
+        })
+        .unwrap_or(true)
+    }
+
+    pub fn set_component_enabled<T: Component + TypeUuid>(
+        &mut self,
+        game_object: GameObject,
+        enabled: bool,
+    ) {
+        self.set_component_enabled_dyn(game_object, T::type_uuid(), enabled);
+    }
+
+    /// Enables or disables a component. Disabled components don't receive updates, nor
+    /// simulate or render for the engine's own components, and started components get
+    /// `on_enable` or `on_disable` called when their state changes.
+    pub fn set_component_enabled_dyn(
+        &mut self,
+        game_object: GameObject,
+        component_id: Uuid,
+        enabled: bool,
+    ) {
+        let Some(mut entry) = self.entry_mut(game_object) else {
+            return;
+        };
+        let Ok(c_id) = entry.get_component_mut::<ComponentID>() else {
+            return;
+        };
+        let changed = if enabled {
+            c_id.disabled_components.remove(&component_id)
+        } else {
+            c_id.disabled_components.insert(component_id)
+        };
+        if changed {
+            PhysicsContext::mark_dirty(self, game_object);
+        }
+        if !changed
+            || self
+                .components_to_start
+                .contains(&(game_object, component_id))
+            || !self.is_active_in_hierarchy(game_object)
+        {
+            return;
+        }
+        self.call_component_hook(game_object, component_id, |component, ctx| {
+            if enabled {
+                component.on_enable(ctx);
+            } else {
+                component.on_disable(ctx);
+            }
+        });
+    }
+
+    /// Whether a game object is active itself, regardless of its ancestors.
+    pub fn is_active(&self, game_object: GameObject) -> bool {
+        self.read_component(game_object, |c_id: &ComponentID| c_id.active)
+            .unwrap_or(false)
+    }
+
+    /// Whether a game object and all of its ancestors are active.
+    pub fn is_active_in_hierarchy(&self, game_object: GameObject) -> bool {
+        self.read_component(game_object, |c_id: &ComponentID| c_id.active_in_hierarchy)
+            .unwrap_or(false)
+    }
+
+    /// Updates the cached [`ComponentID::is_active_in_hierarchy`] of a game object and its
+    /// descendants, and returns the game objects it changed for.
+    pub(crate) fn refresh_active_in_hierarchy(
+        &mut self,
+        game_object: GameObject,
+    ) -> Vec<GameObject> {
+        let mut changed = Vec::new();
+        let mut stack = vec![game_object];
+        while let Some(go) = stack.pop() {
+            let parent_active = self
+                .get_parent_game_object(go)
+                .is_none_or(|parent| self.is_active_in_hierarchy(parent));
+            let Some(mut entry) = self.entry_mut(go) else {
+                continue;
+            };
+            let Ok(c_id) = entry.get_component_mut::<ComponentID>() else {
+                continue;
+            };
+            let active = parent_active && c_id.active;
+            if c_id.active_in_hierarchy != active {
+                c_id.active_in_hierarchy = active;
+                changed.push(go);
+            }
+            stack.extend(self.get_children(go));
+        }
+        changed
+    }
+
+    /// Activates or deactivates a game object, and with it its descendants. Components of
+    /// inactive game objects don't update, their rigid bodies and colliders are disabled and
+    /// their meshes aren't rendered.
+    /// Started components of the game objects that become active or inactive in the hierarchy
+    /// get `on_enable` or `on_disable` called.
+    pub fn set_active(&mut self, game_object: GameObject, active: bool) {
+        if self.entry(game_object).is_none() || self.is_active(game_object) == active {
+            return;
+        }
+        self.write_component(game_object, |c_id: &mut ComponentID| c_id.active = active);
+        let changed = self.refresh_active_in_hierarchy(game_object);
+        self.notify_active_changed(changed);
+    }
+
+    /// Updates the physics of game objects that became active or inactive in the hierarchy,
+    /// and calls `on_enable` or `on_disable` on their started components.
+    fn notify_active_changed(&mut self, changed: Vec<GameObject>) {
+        let component_registry_ref = self.assets.component_registry.clone();
+        let component_registry = component_registry_ref.read();
+        for go in changed {
+            let active = self.is_active_in_hierarchy(go);
+            self.write_component(go, |c_rb: &mut ComponentRigidBody| c_rb.dirty = true);
+            self.write_component(go, |c_c: &mut ComponentCollider| c_c.dirty = true);
+            for (component_id, component) in component_registry.components_ordered() {
+                let has_component = self
+                    .entry(go)
+                    .is_some_and(|entry| component.get_instance(&entry).is_some());
+                if !has_component
+                    || !self.is_component_enabled_dyn(go, component_id)
+                    || self.components_to_start.contains(&(go, component_id))
+                {
                     continue;
-                };
-                instance.update(
-                    ComponentEventContext {
-                        assets: &assets,
-                        scene,
-                        game_object,
-                    },
-                    resources,
-                    input,
-                );
+                }
+                self.call_hook(go, component.as_ref(), |instance, ctx| {
+                    if active {
+                        instance.on_enable(ctx);
+                    } else {
+                        instance.on_disable(ctx);
+                    }
+                });
             }
         }
     }
 
-    fn first_update(&mut self) {}
+    /// Sends physics events to the enabled components of active game objects, in update order.
+    pub(crate) fn dispatch_physics_events(&mut self, events: Vec<(GameObject, PhysicsEvent)>) {
+        let component_registry_ref = self.assets.component_registry.clone();
+        let component_registry = component_registry_ref.read();
+        for (game_object, event) in events {
+            for (component_id, component) in component_registry.components_ordered() {
+                if self.entry(game_object).is_none() {
+                    break;
+                }
+                if !self.is_component_enabled_dyn(game_object, component_id)
+                    || !self.is_active_in_hierarchy(game_object)
+                {
+                    continue;
+                }
+                self.call_hook(game_object, component.as_ref(), |instance, ctx| {
+                    event.dispatch(instance, ctx)
+                });
+            }
+        }
+    }
 
     pub fn delete_game_objects(&mut self) {
         for game_object in self
@@ -689,13 +1323,16 @@ impl Scene {
             .collect::<Vec<_>>()
             .into_iter()
         {
+            // Already deleted along with an ancestor
+            if !self.entity_map.contains_key(&game_object.entity) {
+                continue;
+            }
             let parent = self
                 .get_parent_game_object(game_object)
                 .unwrap_or(self.root);
-            let index = self
-                .get_index_in_parent(parent, game_object, SiblingDir::Before)
-                .unwrap();
-            self.shift_edge_weights(parent, index, -1);
+            if let Some(index) = self.get_index_in_parent(parent, game_object, SiblingDir::Before) {
+                self.shift_edge_weights(parent, index, -1);
+            }
             for go in std::iter::once(game_object)
                 .chain(
                     Bfs::new(&self.entity_arena, game_object.node)
@@ -706,9 +1343,14 @@ impl Scene {
                 .into_iter()
                 .rev()
             {
+                let id = self.get_game_object_uuid(go);
+                self.physics.remove_entity(go.entity);
+                self.opaque_components.remove(&id);
+                self.components_to_start
+                    .retain(|(started, _)| *started != go);
                 self.world.remove(go.entity);
                 self.entity_map.remove(&go.entity);
-                self.uuid_map.remove(&self.get_game_object_uuid(go));
+                self.uuid_map.remove(&id);
                 self.entity_arena.remove_node(go.node);
             }
         }
@@ -741,6 +1383,46 @@ impl Scene {
         self.uuid_map.get(&id).copied()
     }
 
+    /// The game objects with the given name, parents first.
+    pub fn find_by_name<'a>(&'a self, name: &'a str) -> impl Iterator<Item = GameObject> + 'a {
+        self.game_objects().filter(move |go| {
+            self.read_component(*go, |c_id: &ComponentID| c_id.name == name)
+                .unwrap_or(false)
+        })
+    }
+
+    /// The game objects with the given tag, parents first.
+    pub fn find_by_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = GameObject> + 'a {
+        self.game_objects().filter(move |go| self.has_tag(*go, tag))
+    }
+
+    pub fn has_tag(&self, game_object: GameObject, tag: &str) -> bool {
+        self.read_component(game_object, |c_id: &ComponentID| c_id.tags.contains(tag))
+            .unwrap_or(false)
+    }
+
+    pub fn add_tag(&mut self, game_object: GameObject, tag: impl Into<String>) {
+        self.write_component(game_object, |c_id: &mut ComponentID| {
+            c_id.tags.insert(tag.into());
+        });
+    }
+
+    pub fn remove_tag(&mut self, game_object: GameObject, tag: &str) {
+        self.write_component(game_object, |c_id: &mut ComponentID| {
+            c_id.tags.remove(tag);
+        });
+    }
+
+    /// Mask of the layers a game object is on.
+    pub fn get_layers(&self, game_object: GameObject) -> u32 {
+        self.read_component(game_object, |c_id: &ComponentID| c_id.layers)
+            .unwrap_or(0)
+    }
+
+    pub fn set_layers(&mut self, game_object: GameObject, layers: u32) {
+        self.write_component(game_object, |c_id: &mut ComponentID| c_id.layers = layers);
+    }
+
     pub fn get_parent_game_object(&self, game_object: GameObject) -> Option<GameObject> {
         self.entity_arena
             .neighbors_directed(game_object.node, Direction::Incoming)
@@ -881,10 +1563,26 @@ impl Scene {
             .filter_map(|go| self.map_has_component::<T>(go))
     }
 
+    /// The descendants of a game object, depth first and in sibling order.
+    pub fn get_descendants_ordered(
+        &self,
+        game_object: GameObject,
+    ) -> impl Iterator<Item = GameObject> + '_ {
+        let mut stack = self.get_children_ordered(game_object).collect::<Vec<_>>();
+        stack.reverse();
+        std::iter::from_fn(move || {
+            let game_object = stack.pop()?;
+            let len = stack.len();
+            stack.extend(self.get_children_ordered(game_object));
+            stack[len..].reverse();
+            Some(game_object)
+        })
+    }
+
     pub fn get_ancestors(&self, game_object: GameObject) -> impl Iterator<Item = GameObject> + '_ {
         let reversed_arena = Reversed(&self.entity_arena);
-        Dfs::new(&reversed_arena, game_object.node)
-            .iter(&self.entity_arena)
+        Dfs::new(reversed_arena, game_object.node)
+            .iter(reversed_arena)
             .filter_map(|node| self.get_game_object_from_node(node))
     }
 
@@ -897,9 +1595,364 @@ impl Scene {
     }
 }
 
-impl Scene {
-    pub(crate) unsafe fn as_ptr_mut(&self) -> *mut Self {
-        let ptr = self as *const Self;
-        ptr as *mut Self
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate as engine;
+    use crate::component::ComponentID;
+    use crate::component::{Component, ComponentEventContext, ReflectComponent};
+    use crate::input::Input;
+    use crate::reflect::{Reflect, ReflectDefault};
+    use crate::resource::{Resource, ResourceMap};
+    use crate::scene::{GameObject, LayerNames, Scene, SceneData, SiblingDir, DEFAULT_LAYERS};
+    use crate::test_utils;
+    use crate::utils::{ReflectTypeUuidDynamic, TypeUuid};
+
+    #[derive(Default, TypeUuid, Serialize, Deserialize, Component, Reflect)]
+    #[uuid = "3b8f6d52-0e4a-4c1b-9d7e-2f5a6c8b1e03"]
+    #[reflect(Default, TypeUuidDynamic, Component)]
+    #[reflect_attr(update, fixed_update, late_update)]
+    struct LifecycleRecorder {
+        #[serde(skip)]
+        #[reflect_skip]
+        calls: Vec<&'static str>,
+    }
+
+    impl Component for LifecycleRecorder {
+        fn start(&mut self, _: ComponentEventContext) {
+            self.calls.push("start");
+        }
+
+        fn on_enable(&mut self, _: ComponentEventContext) {
+            self.calls.push("on_enable");
+        }
+
+        fn on_disable(&mut self, _: ComponentEventContext) {
+            self.calls.push("on_disable");
+        }
+
+        fn fixed_update(&mut self, _: ComponentEventContext, _: &mut ResourceMap, _: &Input) {
+            self.calls.push("fixed_update");
+        }
+
+        fn update(&mut self, _: ComponentEventContext, _: &mut ResourceMap, _: &Input) {
+            self.calls.push("update");
+        }
+
+        fn late_update(&mut self, _: ComponentEventContext, _: &mut ResourceMap, _: &Input) {
+            self.calls.push("late_update");
+        }
+    }
+
+    #[test]
+    fn component_lifecycle() {
+        let mut scene = test_utils::scene();
+        let game_object = scene.create_game_object(None, None);
+        scene.add_component(game_object, LifecycleRecorder::default());
+        let mut resources = ResourceMap::new();
+        let input = test_utils::input();
+        let mut update = |scene: &mut Scene, delta_time: f32| {
+            resources.time_mut().delta_time = delta_time;
+            scene.prepare();
+            scene.update(&mut resources, &input);
+            scene
+                .read_component(game_object, |r: &LifecycleRecorder| r.calls.clone())
+                .unwrap()
+        };
+        let step = scene.physics.config.fixed_time_step;
+
+        // Started once before the first update, fixed updates follow the physics steps
+        let calls = update(&mut scene, step * 2.5);
+        assert_eq!(
+            calls,
+            [
+                "on_enable",
+                "start",
+                "fixed_update",
+                "fixed_update",
+                "update",
+                "late_update"
+            ]
+        );
+        let calls = update(&mut scene, 0.0);
+        assert_eq!(calls[6..], ["update", "late_update"]);
+
+        // Disabled components don't update
+        scene.set_component_enabled::<LifecycleRecorder>(game_object, false);
+        let calls = update(&mut scene, step);
+        assert_eq!(calls[8..], ["on_disable"]);
+        scene.set_component_enabled::<LifecycleRecorder>(game_object, true);
+        assert!(scene.is_component_enabled::<LifecycleRecorder>(game_object));
+        let calls = update(&mut scene, 0.0);
+        assert_eq!(calls[9..], ["on_enable", "update", "late_update"]);
+    }
+
+    #[derive(Default, TypeUuid, Serialize, Deserialize, Component, Reflect)]
+    #[uuid = "6e2b9d40-1c7f-4a3e-b8d5-0f4a2c9e7b16"]
+    #[reflect(Default, TypeUuidDynamic, Component)]
+    #[reflect_attr(update)]
+    struct SelfReplacer {
+        generation: u32,
+        seen_from_scene: bool,
+    }
+
+    impl Component for SelfReplacer {
+        fn update(&mut self, ctx: ComponentEventContext, _: &mut ResourceMap, _: &Input) {
+            let data: SceneData = (&*ctx.scene).into();
+            self.seen_from_scene = ctx
+                .scene
+                .read_component(ctx.game_object, |_: &SelfReplacer| ())
+                .is_some()
+                || ctx.scene.query::<&SelfReplacer>().iter().next().is_some()
+                || ctx
+                    .scene
+                    .query_mut::<&mut SelfReplacer>()
+                    .iter_mut()
+                    .next()
+                    .is_some()
+                || data
+                    .components
+                    .values()
+                    .any(|components| components.contains_key(&SelfReplacer::type_uuid()));
+            if self.generation == 0 {
+                ctx.scene.remove_component::<SelfReplacer>(ctx.game_object);
+                let replacement = SelfReplacer {
+                    generation: 1,
+                    ..Default::default()
+                };
+                ctx.scene.add_component(ctx.game_object, replacement);
+            } else {
+                self.generation += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn component_replaced_in_hook() {
+        let mut scene = test_utils::scene();
+        let game_object = scene.create_game_object(None, None);
+        scene.add_component(game_object, SelfReplacer::default());
+        let mut resources = ResourceMap::new();
+        let generation = |scene: &Scene| {
+            scene
+                .read_component(game_object, |r: &SelfReplacer| {
+                    (r.generation, r.seen_from_scene)
+                })
+                .unwrap()
+        };
+
+        // The component re-added by its own hook replaces it once the hook returns
+        test_utils::update(&mut scene, &mut resources);
+        assert_eq!(generation(&scene), (1, false));
+
+        // The instance running a hook isn't reachable through the scene, nor is the spare
+        // taking its place meanwhile
+        test_utils::update(&mut scene, &mut resources);
+        assert_eq!(generation(&scene), (2, false));
+    }
+
+    #[derive(Default)]
+    struct UpdateLog(Vec<(&'static str, GameObject)>);
+
+    impl Resource for UpdateLog {}
+
+    #[derive(Default, TypeUuid, Serialize, Deserialize, Component, Reflect)]
+    #[uuid = "f1c0e7a4-6b2d-4e8f-a3c5-9d7b1e2f4a60"]
+    #[reflect(Default, TypeUuidDynamic, Component)]
+    #[reflect_attr(update, priority = -10)]
+    struct EarlyUpdater;
+
+    impl Component for EarlyUpdater {
+        fn update(&mut self, ctx: ComponentEventContext, resources: &mut ResourceMap, _: &Input) {
+            let log = resources.resource_mut::<UpdateLog>().unwrap();
+            log.0.push(("early", ctx.game_object));
+        }
+    }
+
+    #[derive(Default, TypeUuid, Serialize, Deserialize, Component, Reflect)]
+    #[uuid = "0a5d3c2b-8e1f-4b7a-9c6d-3e2f1a0b9c87"]
+    #[reflect(Default, TypeUuidDynamic, Component)]
+    #[reflect_attr(update)]
+    struct DefaultUpdater;
+
+    impl Component for DefaultUpdater {
+        fn update(&mut self, ctx: ComponentEventContext, resources: &mut ResourceMap, _: &Input) {
+            let log = resources.resource_mut::<UpdateLog>().unwrap();
+            log.0.push(("default", ctx.game_object));
+        }
+    }
+
+    #[test]
+    fn update_order() {
+        let mut scene = test_utils::scene();
+        let a = scene.create_game_object(None, None);
+        scene.add_component(a, DefaultUpdater);
+        scene.add_component(a, EarlyUpdater);
+        let b = scene.create_game_object(None, None);
+        scene.add_component(b, EarlyUpdater);
+        let c = scene.create_game_object(None, None);
+        scene.add_component(c, DefaultUpdater);
+        scene.add_component(c, EarlyUpdater);
+
+        let mut resources = ResourceMap::new();
+        resources.insert_default::<UpdateLog>();
+        let input = test_utils::input();
+        let mut update = |scene: &mut Scene| {
+            scene.prepare();
+            scene.update(&mut resources, &input);
+            std::mem::take(&mut resources.resource_mut::<UpdateLog>().unwrap().0)
+        };
+
+        // Lower priorities update first, whatever their type uuid, and each type
+        // goes through its entities archetype by archetype
+        let log = update(&mut scene);
+        assert_eq!(
+            log,
+            [
+                ("early", a),
+                ("early", c),
+                ("early", b),
+                ("default", a),
+                ("default", c),
+            ]
+        );
+        assert_eq!(update(&mut scene), log);
+    }
+
+    #[test]
+    fn active_hierarchy() {
+        let mut scene = test_utils::scene();
+        let parent = scene.create_game_object(None, None);
+        let child = scene.create_game_object(None, Some(parent));
+        scene.add_component(child, LifecycleRecorder::default());
+        let mut resources = ResourceMap::new();
+        let input = test_utils::input();
+        let mut update = |scene: &mut Scene| {
+            scene.prepare();
+            scene.update(&mut resources, &input);
+            scene
+                .read_component(child, |r: &LifecycleRecorder| r.calls.clone())
+                .unwrap()
+        };
+        let calls = update(&mut scene);
+        assert_eq!(calls, ["on_enable", "start", "update", "late_update"]);
+
+        // Deactivating a parent deactivates its descendants
+        scene.set_active(parent, false);
+        assert!(scene.is_active(child));
+        assert!(!scene.is_active_in_hierarchy(child));
+        let calls = update(&mut scene);
+        assert_eq!(calls[4..], ["on_disable"]);
+
+        // Children deactivated themselves stay inactive along with their parent
+        scene.set_active(child, false);
+        scene.set_active(parent, true);
+        assert!(!scene.is_active_in_hierarchy(child));
+        let calls = update(&mut scene);
+        assert_eq!(calls[4..], ["on_disable"]);
+        scene.set_active(child, true);
+        let calls = update(&mut scene);
+        assert_eq!(
+            calls[4..],
+            ["on_disable", "on_enable", "update", "late_update"]
+        );
+
+        // Moving a game object under an inactive parent deactivates it
+        let inactive = scene.create_game_object(None, None);
+        scene.set_active(inactive, false);
+        scene.set_parent(child, Some(inactive));
+        assert!(!scene.is_active_in_hierarchy(child));
+        let calls = update(&mut scene);
+        assert_eq!(calls[8..], ["on_disable"]);
+        let loaded = scene.clone();
+        let loaded_child = loaded
+            .get_game_object_by_uuid(scene.get_game_object_uuid(child))
+            .unwrap();
+        assert!(loaded.is_active(loaded_child));
+        assert!(!loaded.is_active_in_hierarchy(loaded_child));
+
+        scene.set_active(child, false);
+        let json = serde_json::to_string(&scene).unwrap();
+        assert!(json.contains("\"active\":false"));
+    }
+
+    #[test]
+    fn tags_and_layers() {
+        let mut scene = test_utils::scene();
+        let player = scene.create_game_object(None, None);
+        let weapon = scene.create_game_object(None, Some(player));
+        scene.write_component(weapon, |c_id: &mut ComponentID| c_id.name = "Sword".into());
+        scene.add_tag(player, "Player");
+        scene.add_tag(weapon, "Pickup");
+        scene.add_tag(weapon, "Player");
+
+        assert_eq!(
+            scene.find_by_tag("Player").collect::<Vec<_>>(),
+            [player, weapon]
+        );
+        assert_eq!(scene.find_by_name("Sword").collect::<Vec<_>>(), [weapon]);
+        scene.remove_tag(weapon, "Player");
+        assert!(!scene.has_tag(weapon, "Player"));
+        assert_eq!(scene.get_layers(player), DEFAULT_LAYERS);
+
+        let layer_names = LayerNames::new(["Default", "", "Ignore Raycast", "Water"]);
+        assert_eq!(layer_names.mask(&["Water", "Default", "Unknown"]), 0b1001);
+        assert_eq!(
+            layer_names
+                .iter()
+                .map(|(layer, _)| layer)
+                .collect::<Vec<_>>(),
+            [0, 2, 3]
+        );
+        scene.set_layers(weapon, layer_names.mask(&["Water"]));
+
+        let json = serde_json::to_string(&scene).unwrap();
+        assert!(json.contains("\"tags\":[\"Pickup\"]"));
+        assert!(json.contains("\"layers\":8"));
+    }
+
+    #[test]
+    fn deterministic_serialization() {
+        let mut scene = test_utils::scene();
+        let parent = scene.create_game_object(None, None);
+        let children = (0..8)
+            .map(|_| scene.create_game_object(None, Some(parent)))
+            .collect::<Vec<_>>();
+        scene.create_game_object(None, Some(children[3]));
+        scene.set_parent_with_sibling(
+            children[7],
+            Some(parent),
+            Some((children[0], SiblingDir::Before)),
+        );
+        scene.add_component(children[2], LifecycleRecorder::default());
+
+        let json = serde_json::to_string_pretty(&scene).unwrap();
+        let loaded = scene.clone();
+        assert_eq!(serde_json::to_string_pretty(&loaded).unwrap(), json);
+        assert_eq!(
+            loaded
+                .get_children_ordered(
+                    loaded
+                        .get_game_object_by_uuid(scene.get_game_object_uuid(parent))
+                        .unwrap()
+                )
+                .map(|go| loaded.get_game_object_uuid(go))
+                .collect::<Vec<_>>(),
+            scene
+                .get_children_ordered(parent)
+                .map(|go| scene.get_game_object_uuid(go))
+                .collect::<Vec<_>>()
+        );
+
+        // Game objects are written parents first, in sibling order
+        let data = <SceneData>::from(&scene);
+        let order = data.components.keys().copied().collect::<Vec<_>>();
+        let expected = std::iter::once(parent)
+            .chain(scene.get_descendants_ordered(parent))
+            .map(|go| scene.get_game_object_uuid(go))
+            .collect::<Vec<_>>();
+        assert_eq!(order, expected);
+        assert_eq!(order[1], scene.get_game_object_uuid(children[7]));
     }
 }
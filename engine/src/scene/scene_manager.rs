@@ -1,33 +1,230 @@
-use crate::assets::AssetRegistry;
-use crate::core::ReadOnlyRef;
+use std::sync::atomic::{AtomicIsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use uuid::Uuid;
+
+use crate::assets::error::AssetError;
+use crate::assets::{AssetAccess, AssetRef, AssetRegistry};
+use crate::background::Background;
+use crate::core::{ReadOnlyRef, Ref};
 use crate::input::Input;
 use crate::resource::ResourceMap;
-use crate::scene::Scene;
+use crate::scene::{GameObject, SaveGame, SavedScene, Scene, SceneRequest};
+
+/// Background task ids of scene loads, counting down to stay clear of the ids applications use.
+static NEXT_LOAD_TASK: AtomicIsize = AtomicIsize::new(-1);
+
+/// Id of a scene loaded in the [`SceneManager`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SceneId(u64);
+
+impl SceneId {
+    /// The scene that's kept when other scenes are unloaded, see
+    /// [`SceneManager::make_persistent`].
+    pub const PERSISTENT: SceneId = SceneId(0);
+}
+
+/// How a loaded scene is added to the ones already loaded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LoadSceneMode {
+    /// Replaces the loaded scenes, except the persistent one, and becomes the active scene.
+    #[default]
+    Single,
+    /// Is added next to the loaded scenes.
+    Additive,
+}
+
+/// How far a [`SceneLoad`] got.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SceneLoadStage {
+    /// Waiting for a background thread to pick the load up.
+    #[default]
+    Queued,
+    /// The scene asset was read, the copy added to the manager is being made.
+    AssetRead,
+    /// The scene is ready, the next [`SceneManager::prepare`] adds it.
+    Built,
+    /// The scene was added to the manager.
+    Added,
+}
+
+/// Handle to a scene loading in the background, see [`SceneManager::load_scene_async`].
+#[derive(Clone)]
+pub struct SceneLoad {
+    asset: Uuid,
+    mode: LoadSceneMode,
+    state: Arc<Mutex<SceneLoadState>>,
+}
+
+#[derive(Default)]
+struct SceneLoadState {
+    stage: SceneLoadStage,
+    loaded: Option<Result<Scene, AssetError>>,
+    scene: Option<SceneId>,
+    error: Option<AssetError>,
+    cancelled: bool,
+}
+
+impl SceneLoad {
+    pub(crate) fn new(asset: Uuid, mode: LoadSceneMode) -> Self {
+        Self {
+            asset,
+            mode,
+            state: Default::default(),
+        }
+    }
+
+    /// Id of the scene asset being loaded.
+    pub fn asset(&self) -> Uuid {
+        self.asset
+    }
+
+    pub fn mode(&self) -> LoadSceneMode {
+        self.mode
+    }
+
+    pub fn stage(&self) -> SceneLoadStage {
+        self.state.lock().unwrap().stage
+    }
+
+    /// Progress of the load from 0 to 1, by [`SceneLoadStage`].
+    pub fn progress(&self) -> f32 {
+        self.stage() as u8 as f32 / SceneLoadStage::Added as u8 as f32
+    }
 
+    /// Whether the scene was added to the manager, failed to load or was cancelled.
+    pub fn is_done(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.scene.is_some() || state.error.is_some() || state.cancelled
+    }
+
+    /// The loaded scene, once it's added to the manager.
+    pub fn scene(&self) -> Option<SceneId> {
+        self.state.lock().unwrap().scene
+    }
+
+    pub fn error(&self) -> Option<AssetError> {
+        self.state.lock().unwrap().error.clone()
+    }
+
+    fn cancel(&self) {
+        self.state.lock().unwrap().cancelled = true;
+    }
+}
+
+#[derive(Clone)]
+struct LoadedScene {
+    id: SceneId,
+    /// Scene asset the scene was loaded from.
+    asset: Option<Uuid>,
+    scene: Scene,
+}
+
+/// Scenes loaded together, always including the persistent scene.
+#[derive(Clone)]
+struct SceneSet {
+    scenes: Vec<LoadedScene>,
+    active: SceneId,
+}
+
+impl SceneSet {
+    fn get(&self, id: SceneId) -> Option<&LoadedScene> {
+        self.scenes.iter().find(|loaded| loaded.id == id)
+    }
+
+    fn get_mut(&mut self, id: SceneId) -> Option<&mut LoadedScene> {
+        self.scenes.iter_mut().find(|loaded| loaded.id == id)
+    }
+
+    fn active(&self) -> &Scene {
+        &self.get(self.active).unwrap().scene
+    }
+
+    fn active_mut(&mut self) -> &mut Scene {
+        let active = self.active;
+        &mut self.get_mut(active).unwrap().scene
+    }
+
+    /// Adds a scene, replacing the others but the persistent one when loaded in single mode.
+    fn add(&mut self, loaded: LoadedScene, mode: LoadSceneMode) {
+        if mode == LoadSceneMode::Single {
+            self.scenes
+                .retain(|loaded| loaded.id == SceneId::PERSISTENT);
+            self.active = loaded.id;
+        }
+        self.scenes.push(loaded);
+    }
+
+    fn remove(&mut self, id: SceneId) -> bool {
+        if id == SceneId::PERSISTENT {
+            return false;
+        }
+        let count = self.scenes.len();
+        self.scenes.retain(|loaded| loaded.id != id);
+        if self.active == id {
+            self.active = self
+                .scenes
+                .iter()
+                .map(|loaded| loaded.id)
+                .find(|id| *id != SceneId::PERSISTENT)
+                .unwrap_or(SceneId::PERSISTENT);
+        }
+        self.scenes.len() != count
+    }
+}
+
+/// Scenes the game runs, with a copy of them the simulation runs on in the editor.
+///
+/// Several scenes can be loaded at once, one of them being the active scene. The persistent
+/// scene is always loaded and holds the game objects that outlive the scenes they come from.
+/// Scenes can be loaded right away or in the background, and game code requests loads with
+/// [`SceneCommands::load_scene`](crate::scene::SceneCommands::load_scene). Loads and unloads
+/// affect the simulation scenes while there are some.
 pub struct SceneManager {
     simulation_running: bool,
-    current_scene: Scene,
-    simulation_scene: Option<Scene>,
+    scenes: SceneSet,
+    simulation_scenes: Option<SceneSet>,
+    loads: Vec<SceneLoad>,
+    next_id: u64,
     default_scene: ReadOnlyRef<Scene>,
     asset_registry: ReadOnlyRef<AssetRegistry>,
 }
 
 impl SceneManager {
     pub fn new(asset_registry_ref: ReadOnlyRef<AssetRegistry>) -> Self {
+        let persistent_scene;
         let current_scene;
         let default_scene;
         {
             let asset_registry = asset_registry_ref.read();
+            persistent_scene = asset_registry.new_empty_scene();
             current_scene = asset_registry.new_empty_scene();
             default_scene = asset_registry
                 .default_scene()
                 .expect("failed to load default scene")
                 .readonly();
         }
+        let current = SceneId(1);
         Self {
             simulation_running: false,
-            current_scene,
-            simulation_scene: None,
+            scenes: SceneSet {
+                scenes: vec![
+                    LoadedScene {
+                        id: SceneId::PERSISTENT,
+                        asset: None,
+                        scene: persistent_scene,
+                    },
+                    LoadedScene {
+                        id: current,
+                        asset: None,
+                        scene: current_scene,
+                    },
+                ],
+                active: current,
+            },
+            simulation_scenes: None,
+            loads: Vec::new(),
+            next_id: 2,
             default_scene,
             asset_registry: asset_registry_ref,
         }
@@ -35,26 +232,224 @@ impl SceneManager {
 
     pub fn load_empty_scene(&mut self) {
         self.stop_simulation();
-        self.current_scene = self.asset_registry.read().new_empty_scene();
+        let scene = self.asset_registry.read().new_empty_scene();
+        self.add_scene(scene, None, LoadSceneMode::Single);
     }
 
     pub fn load_default_scene(&mut self) {
         self.stop_simulation();
-        self.current_scene = self.default_scene.read().clone();
+        let scene = self.default_scene.read().clone();
+        self.add_scene(scene, None, LoadSceneMode::Single);
     }
 
     pub fn load_scene(&mut self, scene: ReadOnlyRef<Scene>) {
         self.stop_simulation();
-        self.current_scene = scene.read().clone();
+        self.load_scene_with_mode(scene, LoadSceneMode::Single);
+    }
+
+    /// Loads a scene right away, without stopping the simulation.
+    pub fn load_scene_with_mode(
+        &mut self,
+        scene: ReadOnlyRef<Scene>,
+        mode: LoadSceneMode,
+    ) -> SceneId {
+        let asset = Some(scene.id()).filter(|id| !id.is_nil());
+        let scene = scene.read().clone();
+        self.add_scene(scene, asset, mode)
+    }
+
+    /// Loads a scene on a background thread. It's added to the manager by the first
+    /// [`SceneManager::prepare`] once it's loaded.
+    pub fn load_scene_async(
+        &mut self,
+        scene: &AssetRef<Scene>,
+        mode: LoadSceneMode,
+        background: &Ref<Background>,
+    ) -> SceneLoad {
+        let load = SceneLoad::new(AssetAccess::id(scene), mode);
+        self.start_load(load.clone(), background);
+        load
+    }
+
+    fn start_load(&mut self, load: SceneLoad, background: &Ref<Background>) {
+        let asset_registry = self.asset_registry.clone();
+        let task_load = load.clone();
+        background
+            .write()
+            .execute(NEXT_LOAD_TASK.fetch_sub(1, Ordering::Relaxed), move || {
+                let result = asset_registry.read().load_by_id::<Scene>(task_load.asset);
+                if result.is_ok() {
+                    task_load.state.lock().unwrap().stage = SceneLoadStage::AssetRead;
+                }
+                let loaded = result.map(|scene| scene.read().clone());
+                let mut state = task_load.state.lock().unwrap();
+                if loaded.is_ok() {
+                    state.stage = SceneLoadStage::Built;
+                }
+                state.loaded = Some(loaded);
+            });
+        self.loads.push(load);
+    }
+
+    /// Scene loads that are still going on.
+    pub fn loads(&self) -> &[SceneLoad] {
+        &self.loads
+    }
+
+    /// Adds the scenes that finished loading in the background.
+    fn finish_loads(&mut self) {
+        for load in std::mem::take(&mut self.loads) {
+            let loaded = load.state.lock().unwrap().loaded.take();
+            match loaded {
+                Some(Ok(scene)) => {
+                    let id = self.add_scene(scene, Some(load.asset), load.mode);
+                    let mut state = load.state.lock().unwrap();
+                    state.scene = Some(id);
+                    state.stage = SceneLoadStage::Added;
+                }
+                Some(Err(error)) => load.state.lock().unwrap().error = Some(error),
+                None => self.loads.push(load),
+            }
+        }
+    }
+
+    fn add_scene(&mut self, scene: Scene, asset: Option<Uuid>, mode: LoadSceneMode) -> SceneId {
+        let id = self.next_scene_id();
+        self.live_scenes_mut()
+            .add(LoadedScene { id, asset, scene }, mode);
+        id
+    }
+
+    fn next_scene_id(&mut self) -> SceneId {
+        self.next_id += 1;
+        SceneId(self.next_id - 1)
+    }
+
+    /// Unloads a scene, other than the persistent one. Returns whether it was loaded.
+    pub fn unload_scene(&mut self, id: SceneId) -> bool {
+        self.live_scenes_mut().remove(id)
     }
 
     pub fn unload_current_scene(&mut self) {
-        self.simulation_scene = None;
+        self.simulation_scenes = None;
+    }
+
+    /// Moves a game object and its descendants to the persistent scene, so they're kept when
+    /// the scene they come from is unloaded. Game objects of prefab instances are moved with
+    /// their whole instance. Returns the game object in the persistent scene.
+    ///
+    /// The moved game objects keep their runtime state, as a save game would: the component
+    /// fields marked `#[save_state]`, the velocities of their rigid bodies and whether their
+    /// components were started.
+    pub fn make_persistent(
+        &mut self,
+        scene: SceneId,
+        game_object: GameObject,
+    ) -> Option<GameObject> {
+        if scene == SceneId::PERSISTENT {
+            return Some(game_object);
+        }
+        let scenes = self.live_scenes_mut();
+        let from = &mut scenes.get_mut(scene)?.scene;
+        from.entry(game_object)?;
+        let id = from.get_game_object_uuid(game_object);
+        let root = from.snapshot_root(game_object);
+        let snapshot = from.snapshot(game_object);
+        let mut state = from.save_game_objects_state(
+            std::iter::once(root).chain(from.get_descendants_ordered(root)),
+        );
+        from.delete_game_object(root);
+        from.delete_game_objects();
+
+        // The persistent scene keeps its own simulation time and components left to start
+        let persistent = &mut scenes.get_mut(SceneId::PERSISTENT)?.scene;
+        state.physics.accumulated_time = persistent.physics.accumulated_time();
+        state.unstarted.extend(
+            persistent
+                .components_to_start
+                .iter()
+                .map(|(go, component_id)| (persistent.get_game_object_uuid(*go), *component_id)),
+        );
+        persistent.restore_snapshot(&snapshot)?;
+        persistent.load_state(&state);
+        persistent.get_game_object_by_uuid(id)
+    }
+
+    /// Scene new game objects are created in, and that game cameras are looked for in first.
+    pub fn active_scene(&self) -> SceneId {
+        self.live_scenes().active
+    }
+
+    /// Makes a loaded scene the active scene. Returns whether it's loaded.
+    pub fn set_active_scene(&mut self, id: SceneId) -> bool {
+        let scenes = self.live_scenes_mut();
+        if scenes.get(id).is_none() {
+            return false;
+        }
+        scenes.active = id;
+        true
+    }
+
+    /// The loaded scenes, in the order they were loaded after the persistent scene.
+    pub fn scenes(&self) -> impl Iterator<Item = (SceneId, &Scene)> {
+        self.live_scenes()
+            .scenes
+            .iter()
+            .map(|loaded| (loaded.id, &loaded.scene))
+    }
+
+    /// The loaded scenes with the active scene first, in the order they're rendered.
+    pub fn scenes_active_first(&self) -> Vec<&Scene> {
+        let active = self.active_scene();
+        std::iter::once(self.simulation_scene())
+            .chain(
+                self.scenes()
+                    .filter(|(id, _)| *id != active)
+                    .map(|(_, scene)| scene),
+            )
+            .collect()
+    }
+
+    pub fn scenes_mut(&mut self) -> impl Iterator<Item = (SceneId, &mut Scene)> {
+        self.live_scenes_mut()
+            .scenes
+            .iter_mut()
+            .map(|loaded| (loaded.id, &mut loaded.scene))
+    }
+
+    pub fn scene(&self, id: SceneId) -> Option<&Scene> {
+        self.live_scenes().get(id).map(|loaded| &loaded.scene)
+    }
+
+    pub fn scene_mut(&mut self, id: SceneId) -> Option<&mut Scene> {
+        self.live_scenes_mut()
+            .get_mut(id)
+            .map(|loaded| &mut loaded.scene)
+    }
+
+    /// Id of the scene asset a loaded scene comes from, if any.
+    pub fn scene_asset(&self, id: SceneId) -> Option<Uuid> {
+        self.live_scenes().get(id)?.asset
+    }
+
+    /// Sets the scene asset a loaded scene comes from, like once it's saved to a new asset.
+    pub fn set_scene_asset(&mut self, id: SceneId, asset: Option<Uuid>) {
+        if let Some(loaded) = self.live_scenes_mut().get_mut(id) {
+            loaded.asset = asset;
+        }
+    }
+
+    pub fn persistent_scene(&self) -> &Scene {
+        self.scene(SceneId::PERSISTENT).unwrap()
+    }
+
+    pub fn persistent_scene_mut(&mut self) -> &mut Scene {
+        self.scene_mut(SceneId::PERSISTENT).unwrap()
     }
 
     pub fn start_simulation(&mut self) {
-        if self.simulation_scene.is_none() {
-            self.simulation_scene = Some(self.current_scene.clone());
+        if self.simulation_scenes.is_none() {
+            self.simulation_scenes = Some(self.scenes.clone());
         }
 
         self.simulation_running = true;
@@ -65,51 +460,270 @@ impl SceneManager {
     }
 
     pub fn stop_simulation(&mut self) {
-        self.simulation_scene = None;
+        if self.simulation_scenes.take().is_some() {
+            for load in self.loads.drain(..) {
+                load.cancel();
+            }
+        }
         self.simulation_running = false;
     }
 
+    /// Adds the scenes loaded in the background, and prepares the loaded scenes.
     pub fn prepare(&mut self) {
-        self.simulation_scene_mut().prepare();
+        self.finish_loads();
+        for (_, scene) in self.scenes_mut() {
+            scene.prepare();
+        }
     }
 
+    /// Updates the simulation scenes, then carries out the loads and moves to the persistent
+    /// scene their game objects requested.
     pub fn update(&mut self, resources: &mut ResourceMap, input: &Input) {
         if !self.simulation_running {
             return;
         }
 
-        if let Some(scene) = &mut self.simulation_scene {
-            scene.update(resources, input);
+        let Some(scenes) = &mut self.simulation_scenes else {
+            return;
+        };
+        let mut requests = Vec::new();
+        for loaded in &mut scenes.scenes {
+            loaded.scene.update(resources, input);
+            requests.extend(
+                loaded
+                    .scene
+                    .take_requests()
+                    .into_iter()
+                    .map(|request| (loaded.id, request)),
+            );
+        }
+        for (scene, request) in requests {
+            match request {
+                SceneRequest::LoadScene(load) => {
+                    let background = resources.background().clone();
+                    self.start_load(load, &background);
+                }
+                SceneRequest::MakePersistent(game_object) => {
+                    self.make_persistent(scene, game_object);
+                }
+            }
         }
     }
 
+    /// Saves the simulation scenes, or the loaded scenes when there's no simulation, with
+    /// their runtime state and the state of the resources.
+    pub fn save_game(&self, resources: &ResourceMap) -> SaveGame {
+        let scenes = self.live_scenes();
+        SaveGame {
+            scenes: scenes
+                .scenes
+                .iter()
+                .map(|loaded| SavedScene {
+                    asset: loaded.asset,
+                    data: (&loaded.scene).into(),
+                    state: loaded.scene.save_state(),
+                })
+                .collect(),
+            active: scenes
+                .scenes
+                .iter()
+                .position(|loaded| loaded.id == scenes.active)
+                .unwrap_or_default(),
+            resources: resources.save_state(),
+        }
+    }
+
+    /// Replaces the simulation scenes with the scenes of a save game, and restores the state
+    /// of the resources. The simulation stays paused if it isn't running.
+    pub fn load_game(&mut self, save: &SaveGame, resources: &mut ResourceMap) {
+        for load in self.loads.drain(..) {
+            load.cancel();
+        }
+        let assets = self.persistent_scene().assets().clone();
+        let mut scenes = Vec::new();
+        for (index, saved) in save.scenes.iter().enumerate() {
+            let id = if index == 0 {
+                SceneId::PERSISTENT
+            } else {
+                self.next_scene_id()
+            };
+            let mut scene = Scene::from((&assets, saved.data.clone()));
+            scene.load_state(&saved.state);
+            scenes.push(LoadedScene {
+                id,
+                asset: saved.asset,
+                scene,
+            });
+        }
+        if scenes.is_empty() {
+            scenes.push(LoadedScene {
+                id: SceneId::PERSISTENT,
+                asset: None,
+                scene: assets.scene(),
+            });
+        }
+        let active = scenes
+            .get(save.active)
+            .map_or(SceneId::PERSISTENT, |loaded| loaded.id);
+        self.simulation_scenes = Some(SceneSet { scenes, active });
+        resources.load_state(&save.resources);
+    }
+
     pub fn has_simulation_scene(&self) -> bool {
-        self.simulation_scene.is_some()
+        self.simulation_scenes.is_some()
     }
 
     pub fn is_simulating(&self) -> bool {
         self.simulation_running
     }
 
+    /// The active simulation scene, or the active scene when there's no simulation.
     pub fn simulation_scene(&self) -> &Scene {
-        if let Some(scene) = &self.simulation_scene {
-            return scene;
-        }
-        &self.current_scene
+        self.live_scenes().active()
     }
 
     pub fn simulation_scene_mut(&mut self) -> &mut Scene {
-        if let Some(scene) = &mut self.simulation_scene {
-            return scene;
-        }
-        &mut self.current_scene
+        self.live_scenes_mut().active_mut()
     }
 
+    /// The active scene, outside of the simulation.
     pub fn current_scene(&self) -> &Scene {
-        &self.current_scene
+        self.scenes.active()
     }
 
     pub fn current_scene_mut(&mut self) -> &mut Scene {
-        &mut self.current_scene
+        self.scenes.active_mut()
+    }
+
+    fn live_scenes(&self) -> &SceneSet {
+        self.simulation_scenes.as_ref().unwrap_or(&self.scenes)
+    }
+
+    fn live_scenes_mut(&mut self) -> &mut SceneSet {
+        self.simulation_scenes.as_mut().unwrap_or(&mut self.scenes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::assets::AssetRef;
+    use crate::background::Background;
+    use crate::component::{ComponentCollider, ComponentRigidBody};
+    use crate::resource::ResourceMap;
+    use crate::scene::{LoadSceneMode, Scene, SceneId, SceneLoad, SceneLoadStage, SceneManager};
+    use crate::test_utils::{self, FrameCounter};
+
+    fn wait(scenes: &mut SceneManager, load: &SceneLoad) {
+        let start = Instant::now();
+        while !load.is_done() {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "scene load timed out"
+            );
+            std::thread::sleep(Duration::from_millis(5));
+            scenes.prepare();
+        }
+    }
+
+    #[test]
+    fn additive_and_background_loads() {
+        let assets = test_utils::assets();
+        let mut scenes = SceneManager::new(assets.asset_registry.readonly());
+        let mut level = assets.scene();
+        level.create_game_object(None, None);
+        let level = assets
+            .asset_registry
+            .read()
+            .create("tests/scene_manager_level".into(), level)
+            .unwrap();
+        let level: AssetRef<Scene> = Some(level).into();
+
+        let first = scenes.active_scene();
+        let player = scenes.simulation_scene_mut().create_game_object(None, None);
+        let player_id = scenes.simulation_scene().get_game_object_uuid(player);
+        let player = scenes.make_persistent(first, player).unwrap();
+        assert_eq!(
+            scenes.persistent_scene().get_game_object_uuid(player),
+            player_id
+        );
+        assert!(scenes
+            .simulation_scene()
+            .get_game_object_by_uuid(player_id)
+            .is_none());
+
+        let background = Background::new();
+        let additive = scenes.load_scene_async(&level, LoadSceneMode::Additive, &background);
+        assert!(additive.stage() < SceneLoadStage::Added);
+        let start = Instant::now();
+        while additive.stage() < SceneLoadStage::Built {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "scene load timed out"
+            );
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(additive.progress(), 2.0 / 3.0);
+        assert!(additive.scene().is_none());
+        wait(&mut scenes, &additive);
+        assert_eq!(additive.stage(), SceneLoadStage::Added);
+        assert_eq!(additive.progress(), 1.0);
+        assert_eq!(scenes.active_scene(), first);
+        assert_eq!(scenes.scenes().count(), 3);
+        let added = scenes.scene(additive.scene().unwrap()).unwrap();
+        assert_eq!(added.root_objects().count(), 1);
+
+        let single = scenes.load_scene_async(&level, LoadSceneMode::Single, &background);
+        wait(&mut scenes, &single);
+        assert_eq!(scenes.active_scene(), single.scene().unwrap());
+        assert_eq!(
+            scenes.scenes().map(|(id, _)| id).collect::<Vec<_>>(),
+            [SceneId::PERSISTENT, scenes.active_scene()]
+        );
+        assert!(scenes
+            .persistent_scene()
+            .get_game_object_by_uuid(player_id)
+            .is_some());
+    }
+
+    #[test]
+    fn make_persistent_keeps_runtime_state() {
+        let assets = test_utils::assets();
+        let mut scenes = SceneManager::new(assets.asset_registry.readonly());
+        let mut resources = ResourceMap::new();
+        let scene = scenes.current_scene_mut();
+        let ball = scene.create_game_object(None, None);
+        scene.add_component(ball, ComponentRigidBody::default());
+        scene.add_component(ball, ComponentCollider::default());
+        scene.add_component(ball, FrameCounter::default());
+        let ball_id = scene.get_game_object_uuid(ball);
+        scenes.start_simulation();
+        let input = test_utils::input();
+        for _ in 0..10 {
+            resources.time_mut().delta_time = 1.0 / 60.0;
+            scenes.prepare();
+            scenes.update(&mut resources, &input);
+        }
+        let velocity = |scene: &Scene, ball: crate::scene::GameObject| {
+            let handle = scene.physics.rigid_body_handle(ball.entity).unwrap();
+            *scene.physics.bodies[handle].linvel()
+        };
+        let ball = scenes
+            .simulation_scene()
+            .get_game_object_by_uuid(ball_id)
+            .unwrap();
+        let falling = velocity(scenes.simulation_scene(), ball);
+        assert!(falling.y < 0.0);
+
+        let active = scenes.active_scene();
+        let ball = scenes.make_persistent(active, ball).unwrap();
+        let persistent = scenes.persistent_scene();
+        assert_eq!(
+            persistent.read_component(ball, |c: &FrameCounter| c.frames),
+            Some(10)
+        );
+        assert_eq!(velocity(persistent, ball), falling);
+        assert!(persistent.components_to_start.is_empty());
     }
 }
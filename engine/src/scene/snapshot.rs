@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::scene::prefab_instance::{instance_game_object_id, remap_ids};
+use crate::scene::{GameObject, Scene, SceneData, SiblingDir};
+
+/// Saved state of a game object and its descendants, see [`Scene::snapshot`].
+#[derive(Clone)]
+pub struct GameObjectSnapshot {
+    id: Uuid,
+    index: i32,
+    data: SceneData,
+}
+
+impl GameObjectSnapshot {
+    /// Id of the saved game object.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+}
+
+impl Scene {
+    /// The game object saved by [`Scene::snapshot`] for `game_object`: the root of the
+    /// outermost prefab instance it's part of, or the game object itself.
+    pub fn snapshot_root(&self, game_object: GameObject) -> GameObject {
+        self.prefab_instance_of(game_object)
+            .map(|(root, _)| root)
+            .unwrap_or(game_object)
+    }
+
+    /// Saves a game object with its descendants, along with its place in the hierarchy, to be
+    /// put back with [`Scene::restore_snapshot`]. Game objects of prefab instances are saved
+    /// with their whole instance, see [`Scene::snapshot_root`].
+    pub fn snapshot(&self, game_object: GameObject) -> GameObjectSnapshot {
+        let game_object = self.snapshot_root(game_object);
+        let parent = self
+            .get_parent_game_object(game_object)
+            .unwrap_or(self.root());
+        GameObjectSnapshot {
+            id: self.get_game_object_uuid(game_object),
+            index: self
+                .get_index_in_parent(parent, game_object, SiblingDir::Before)
+                .unwrap_or_default(),
+            data: (self, game_object).into(),
+        }
+    }
+
+    /// Puts a saved game object back as it was, replacing its current state if it's still in
+    /// the scene. Its descendants are replaced as well, including the ones added since the
+    /// snapshot was taken.
+    pub fn restore_snapshot(&mut self, snapshot: &GameObjectSnapshot) -> Option<GameObject> {
+        if let Some(game_object) = self.get_game_object_by_uuid(snapshot.id) {
+            self.delete_game_object(game_object);
+            self.delete_game_objects();
+        }
+        self.add_game_objects(snapshot.data.clone());
+        let game_object = self.get_game_object_by_uuid(snapshot.id)?;
+        self.set_sibling_index(game_object, snapshot.index);
+        Some(game_object)
+    }
+
+    /// Copies a game object and its descendants right after it, and returns the copy.
+    /// References between the copied game objects point to the copies.
+    pub fn duplicate_game_object(&mut self, game_object: GameObject) -> Option<GameObject> {
+        let snapshot = self.snapshot(game_object);
+        let game_object = self.get_game_object_by_uuid(snapshot.id)?;
+        // Game objects of prefab instances keep ids derived from the ids of their roots
+        let mut ids = HashMap::new();
+        for go in std::iter::once(game_object).chain(self.get_descendants_ordered(game_object)) {
+            let id = self.get_game_object_uuid(go);
+            let new_id = *ids.entry(id).or_insert_with(Uuid::new_v4);
+            if let Some(expansion) = self.prefab_expansion(go) {
+                for (prefab_id, member_id) in &expansion.game_objects {
+                    ids.entry(*member_id)
+                        .or_insert_with(|| instance_game_object_id(new_id, *prefab_id));
+                }
+            }
+        }
+        let map = |id| ids.get(&id).copied();
+
+        let mut data = SceneData::default();
+        let type_registry_ref = self.assets().type_registry.clone();
+        let type_registry = type_registry_ref.read();
+        for (id, mut components) in snapshot.data.components {
+            for (component_id, value) in components.iter_mut() {
+                remap_ids(&type_registry, *component_id, value, &map);
+            }
+            data.components.insert(map(id).unwrap_or(id), components);
+        }
+        data.hierarchy = snapshot
+            .data
+            .hierarchy
+            .into_iter()
+            .map(|(id, parent)| (map(id).unwrap_or(id), map(parent).unwrap_or(parent)))
+            .collect();
+        data.component_versions = snapshot.data.component_versions;
+        data.instance_versions = snapshot
+            .data
+            .instance_versions
+            .into_iter()
+            .map(|(id, versions)| (map(id).unwrap_or(id), versions))
+            .collect();
+        self.add_game_objects(data);
+
+        let copy = self.get_game_object_by_uuid(map(snapshot.id)?)?;
+        self.set_sibling_index(copy, snapshot.index + 1);
+        Some(copy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::component::{ComponentID, ComponentPointLight};
+    use crate::scene::SceneData;
+    use crate::test_utils;
+
+    #[test]
+    fn snapshot_restore_and_duplicate() {
+        let mut scene = test_utils::scene();
+        let first = scene.create_game_object(None, None);
+        let parent = scene.create_game_object(None, None);
+        let child = scene.create_game_object(None, Some(parent));
+        let last = scene.create_game_object(None, None);
+        scene.add_component(child, ComponentPointLight::default());
+        let saved = <SceneData>::from(&scene);
+        let snapshot = scene.snapshot(parent);
+        let child_id = scene.get_game_object_uuid(child);
+
+        scene.write_component(child, |c: &mut ComponentPointLight| c.radius = 4.0);
+        scene.set_parent(parent, Some(last));
+        scene.create_game_object(None, Some(child));
+        let parent = scene.restore_snapshot(&snapshot).unwrap();
+        assert_eq!(
+            serde_json::to_value(<SceneData>::from(&scene)).unwrap(),
+            serde_json::to_value(&saved).unwrap()
+        );
+
+        scene.delete_game_object(parent);
+        scene.delete_game_objects();
+        assert!(scene.get_game_object_by_uuid(child_id).is_none());
+        let parent = scene.restore_snapshot(&snapshot).unwrap();
+        let copy = scene.duplicate_game_object(parent).unwrap();
+        let roots = scene.root_objects().collect::<Vec<_>>();
+        assert_eq!(roots.len(), 4);
+        assert_eq!(roots[0], first);
+        assert_eq!(roots[2], copy);
+        let copy_child = scene.get_children(copy).next().unwrap();
+        assert_ne!(scene.get_game_object_uuid(copy_child), child_id);
+        assert_eq!(
+            scene.read_component(copy_child, |c: &ComponentID| c.name.clone()),
+            scene.read_component(
+                scene.get_game_object_by_uuid(child_id).unwrap(),
+                |c: &ComponentID| { c.name.clone() }
+            )
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+
+use legion::systems::{Builder, Executor, Step};
+use legion::world::EntityStore;
+use legion::SystemBuilder;
+use nalgebra_glm::Mat4;
+use petgraph::stable_graph::NodeIndex;
+
+use engine_derive::reflect_trait;
+
+use crate as engine;
+use crate::component::ComponentTransform;
+use crate::core::TimeType;
+use crate::math::Transform;
+use crate::reflect::{Reflect, ReflectDefault};
+use crate::scene::GameObject;
+use crate::utils::{ReflectTypeUuidDynamic, TypeUuid};
+
+/// The point of [`Scene::update`](crate::scene::Scene::update) at which a system runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SystemStage {
+    /// Before each physics step, after the components' `fixed_update`.
+    FixedUpdate,
+    /// Once per frame, after physics and before the components' `update`.
+    Update,
+    /// Once per frame, after the components' `late_update`.
+    PostUpdate,
+}
+
+/// Adds legion systems to the scenes' schedules. Systems declare the components and
+/// resources they read and write, and legion runs the ones that don't conflict in parallel.
+///
+/// Like components, systems are registered through reflection and sorted by their
+/// `priority` reflect attribute, lowest first, then by type uuid:
+/// ```ignore
+/// #[derive(Default, TypeUuid, Reflect)]
+/// #[uuid = "..."]
+/// #[reflect(Default, TypeUuidDynamic, System)]
+/// #[reflect_attr(priority = -10)]
+/// pub struct SpinSystem;
+/// ```
+/// Besides the ones added by the engine in some stages, systems can read the [`FrameTime`]
+/// and [`ReadOnlyAssetContext`](crate::context::ReadOnlyAssetContext) resources.
+/// Entities should be created and deleted through the scene rather than command buffers,
+/// so that they get a place in its hierarchy.
+#[reflect_trait]
+pub trait System: Reflect {
+    fn stage(&self) -> SystemStage;
+    fn add_to_schedule(&self, schedule: &mut Builder);
+}
+
+/// Time of the current frame, or of the current physics step in [`SystemStage::FixedUpdate`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameTime {
+    pub time: TimeType,
+    pub delta_time: TimeType,
+}
+
+/// Steps of a stage's legion schedule, kept by the scene between runs until the component
+/// registry changes. Legion's schedules can't be sent across threads, which scenes need to be,
+/// so only the steps that can are kept.
+pub(crate) struct SystemSchedule {
+    generation: u64,
+    steps: Vec<ScheduleStep>,
+}
+
+enum ScheduleStep {
+    Systems(Executor),
+    FlushCmdBuffers,
+}
+
+impl SystemSchedule {
+    /// Keeps the steps of a schedule built from the given registry generation, unless some
+    /// systems run on the thread they were added from.
+    pub(crate) fn new(generation: u64, steps: Vec<Step>) -> Option<Self> {
+        let steps = steps
+            .into_iter()
+            .map(|step| match step {
+                Step::Systems(executor) => Some(ScheduleStep::Systems(executor)),
+                Step::FlushCmdBuffers => Some(ScheduleStep::FlushCmdBuffers),
+                Step::ThreadLocalFn(_) | Step::ThreadLocalSystem(_) => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self { generation, steps })
+    }
+
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub(crate) fn into_steps(self) -> Vec<Step> {
+        self.steps
+            .into_iter()
+            .map(|step| match step {
+                ScheduleStep::Systems(executor) => Step::Systems(executor),
+                ScheduleStep::FlushCmdBuffers => Step::FlushCmdBuffers,
+            })
+            .collect()
+    }
+}
+
+/// Every game object of the scene along with its parent, parents first.
+pub(crate) struct SceneHierarchy(pub(crate) Vec<(GameObject, Option<NodeIndex>)>);
+
+#[derive(Default)]
+pub(crate) struct WorldTransforms(pub(crate) HashMap<NodeIndex, Transform>);
+
+/// Computes the world transform of every game object once the frame is over, for the
+/// renderer and anything else reading them until the next update.
+#[derive(Default, TypeUuid, Reflect)]
+#[uuid = "5d0c7e1a-92b4-4f3e-8a61-c7d2e9b4f018"]
+#[reflect(Default, TypeUuidDynamic, System)]
+#[reflect_attr(priority = 1000)]
+pub struct TransformSystem;
+
+impl System for TransformSystem {
+    fn stage(&self) -> SystemStage {
+        SystemStage::PostUpdate
+    }
+
+    fn add_to_schedule(&self, schedule: &mut Builder) {
+        schedule.add_system(
+            SystemBuilder::new("transform")
+                .read_resource::<SceneHierarchy>()
+                .write_resource::<WorldTransforms>()
+                .read_component::<ComponentTransform>()
+                .build(|_, world, (hierarchy, world_transforms), _| {
+                    let mut matrices: HashMap<NodeIndex, Mat4> =
+                        HashMap::with_capacity(hierarchy.0.len());
+                    for (game_object, parent) in &hierarchy.0 {
+                        let local = world
+                            .entry_ref(game_object.entity)
+                            .ok()
+                            .and_then(|entry| {
+                                entry
+                                    .get_component::<ComponentTransform>()
+                                    .ok()
+                                    .map(|c| c.transform.matrix)
+                            })
+                            .unwrap_or_else(Mat4::identity);
+                        let matrix = parent
+                            .and_then(|parent| matrices.get(&parent))
+                            .map_or(local, |parent| parent * local);
+                        matrices.insert(game_object.node, matrix);
+                    }
+                    world_transforms.0 = matrices
+                        .into_iter()
+                        .map(|(node, matrix)| (node, matrix.into()))
+                        .collect();
+                }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use legion::query::component;
+    use legion::systems::Builder;
+    use legion::{IntoQuery, SystemBuilder};
+    use nalgebra_glm::Vec3;
+
+    use crate as engine;
+    use crate::component::ComponentTransform;
+    use crate::math::Transform;
+    use crate::reflect::{Reflect, ReflectDefault};
+    use crate::resource::ResourceMap;
+    use crate::scene::{FrameTime, ReflectSystem, System, SystemStage};
+    use crate::test_utils::{self, Marker};
+    use crate::utils::{ReflectTypeUuidDynamic, TypeUuid};
+
+    #[derive(Default, TypeUuid, Reflect)]
+    #[uuid = "c3f81b07-5e2a-4d96-a0b4-7d16e8c9f2a1"]
+    #[reflect(Default, TypeUuidDynamic, System)]
+    struct SlideSystem;
+
+    impl System for SlideSystem {
+        fn stage(&self) -> SystemStage {
+            SystemStage::Update
+        }
+
+        fn add_to_schedule(&self, schedule: &mut Builder) {
+            schedule.add_system(
+                SystemBuilder::new("slide")
+                    .read_resource::<FrameTime>()
+                    .with_query(<&mut ComponentTransform>::query().filter(component::<Marker>()))
+                    .build(|_, world, time, query| {
+                        query.par_for_each_mut(world, |c_transform| {
+                            c_transform
+                                .transform
+                                .translate(&Vec3::new(time.delta_time, 0.0, 0.0));
+                        });
+                    }),
+            );
+        }
+    }
+
+    #[test]
+    fn systems() {
+        let mut scene = test_utils::scene();
+        let parent = scene.create_game_object(None, None);
+        scene.add_component(parent, Marker);
+        let child = scene.create_game_object(None, Some(parent));
+        scene.set_transform(child, Transform::from_xyz(0.0, 1.0, 0.0).matrix);
+        let mut resources = ResourceMap::new();
+        resources.time_mut().delta_time = 0.5;
+
+        test_utils::update(&mut scene, &mut resources);
+        // The second update runs the schedules kept from the first one
+        assert!(scene.system_schedules.contains_key(&SystemStage::Update));
+        test_utils::update(&mut scene, &mut resources);
+
+        // World transforms are computed once systems and components are done
+        assert_eq!(
+            scene.get_transform(parent).position,
+            Vec3::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            scene.get_world_transform(child).position,
+            Vec3::new(1.0, 1.0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,75 @@
+//! Shared setup for the engine unit tests.
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate as engine;
+use crate::component::{Component, ComponentEventContext, ReflectComponent};
+use crate::context::AssetContext;
+use crate::input::{Input, InputState};
+use crate::reflect::{Reflect, ReflectDefault};
+use crate::render::RenderContext;
+use crate::resource::ResourceMap;
+use crate::scene::Scene;
+use crate::utils::{ReflectTypeUuidDynamic, TypeUuid};
+
+/// GPU-less context shared by the tests, with the workspace assets as both project and
+/// engine assets.
+pub(crate) fn assets() -> AssetContext {
+    static CONTEXT: OnceLock<AssetContext> = OnceLock::new();
+    CONTEXT
+        .get_or_init(|| {
+            let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets");
+            AssetContext::with_render_context(RenderContext::without_gpu(), &assets, &assets)
+                .unwrap()
+        })
+        .clone()
+}
+
+pub(crate) fn scene() -> Scene {
+    assets().scene()
+}
+
+/// Input with no key or pointer down.
+pub(crate) fn input() -> Input<'static> {
+    static CONTEXT: OnceLock<egui::Context> = OnceLock::new();
+    Input::from_ctx(
+        CONTEXT.get_or_init(Default::default),
+        None,
+        InputState::default(),
+    )
+}
+
+/// Runs one frame of the scene, as the game loop would.
+pub(crate) fn update(scene: &mut Scene, resources: &mut ResourceMap) {
+    scene.prepare();
+    scene.update(resources, &input());
+}
+
+/// Component with no data nor behaviour, to tag game objects.
+#[derive(Default, TypeUuid, Serialize, Deserialize, Component, Reflect)]
+#[uuid = "d8a13e5c-47b2-4f9d-a6e0-2c5b9f71d084"]
+#[reflect(Default, TypeUuidDynamic, Component)]
+pub(crate) struct Marker;
+
+impl Component for Marker {}
+
+/// Counts its updates, in a runtime field only kept by save games.
+#[derive(Default, TypeUuid, Serialize, Deserialize, Component, Reflect)]
+#[uuid = "0c7e5a92-3f1d-4b68-a2e4-95d8b1c6f073"]
+#[reflect(Default, TypeUuidDynamic, Component)]
+#[reflect_attr(update)]
+pub(crate) struct FrameCounter {
+    #[serde(skip)]
+    #[reflect_skip]
+    #[save_state]
+    pub frames: u32,
+}
+
+impl Component for FrameCounter {
+    fn update(&mut self, _: ComponentEventContext, _: &mut ResourceMap, _: &Input) {
+        self.frames += 1;
+    }
+}
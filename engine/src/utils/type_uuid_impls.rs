@@ -1,5 +1,6 @@
 use crate as engine;
 use engine_derive::impl_extern_type_uuid;
+use std::collections::BTreeSet;
 use uuid::Uuid;
 
 impl_extern_type_uuid!(bool, "011e2cb3-4db1-4745-aea6-bc4d813b266c");
@@ -18,5 +19,6 @@ impl_extern_type_uuid!(isize, "cf4b886c-1ec9-4892-8c12-9eea9dcfca8b");
 impl_extern_type_uuid!(f32, "accb9256-833e-4b7e-9b93-4b5d90aafd6c");
 impl_extern_type_uuid!(f64, "b097ab57-bde8-4007-8181-268f8d90a4b0");
 impl_extern_type_uuid!(String, "e6fe8a72-0495-4877-88e4-6d9f8062b8f2");
+impl_extern_type_uuid!(BTreeSet<String>, "9c3e7f21-5a8d-4b06-b2e4-d1f06a9c83b7");
 
 impl_extern_type_uuid!(Uuid, "26e09929-d7bb-4928-bed4-49bf8e62d5af");
@@ -12,6 +12,9 @@ use tinytemplate::TinyTemplate;
 pub struct Project {
     name: String,
     creation_date: String,
+    /// Names of the layers game objects can be on, by bit index in layer masks.
+    #[serde(default = "Project::default_layers")]
+    layers: Vec<String>,
     #[serde(skip)]
     root_directory: PathBuf,
 }
@@ -41,6 +44,7 @@ impl Project {
         let project = Project {
             name,
             creation_date: Utc::now().to_rfc3339(),
+            layers: Self::default_layers(),
             root_directory: project_directory,
         };
 
@@ -100,6 +104,15 @@ impl Project {
         Ok(project)
     }
 
+    /// Writes the project file back, to keep changes made to the project's settings.
+    pub fn save(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let toml_string = toml::to_string(self)
+            .map_err(|e| format!("Failed to serialize project file 'project.toml': {}", e))?;
+        fs::write(self.root_directory.join("project.toml"), toml_string)
+            .map_err(|e| format!("Failed to write project file 'project.toml': {}", e))?;
+        Ok(())
+    }
+
     pub fn is_valid(&self) -> bool {
         self.root_directory.exists()
             && self.assets_directory().exists()
@@ -118,6 +131,18 @@ impl Project {
         &self.creation_date
     }
 
+    fn default_layers() -> Vec<String> {
+        vec![String::from("Default")]
+    }
+
+    pub fn layers(&self) -> &[String] {
+        &self.layers
+    }
+
+    pub fn set_layers(&mut self, layers: Vec<String>) {
+        self.layers = layers;
+    }
+
     pub fn root_directory(&self) -> &PathBuf {
         &self.root_directory
     }
@@ -6,7 +6,7 @@ use egui::{
     Align2, Color32, Context, Direction, FontId, Image, ImageSource, InnerResponse, Layout, Pos2,
     Rect, Sense,
 };
-use engine::context::{AssetContext, GameContext};
+use engine::context::{default_engine_assets_path, AssetContext, GameContext};
 use engine::core::Time;
 use engine::error::DynError;
 use engine::ext::egui::EguiContextExt;
@@ -35,8 +35,11 @@ struct GameApp {
 
 impl GameApp {
     fn new(cc: &eframe::CreationContext) -> Result<Self, Box<DynError>> {
-        let assets =
-            AssetContext::new(cc, PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets"))?;
+        let assets = AssetContext::new(
+            cc,
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets"),
+            default_engine_assets_path()?,
+        )?;
         {
             let mut type_registry = assets.type_registry.write();
             plugin_main(&mut type_registry);
@@ -48,6 +51,7 @@ impl GameApp {
         let mut game = GameContext::new(assets.clone());
         let scene = assets.asset_registry.read().load::<Scene>("scene").unwrap();
         game.scenes.load_scene(scene.readonly());
+        game.scenes.start_simulation();
         Ok(Self {
             game,
             renderer: SceneRenderer::new(
@@ -81,7 +85,7 @@ impl eframe::App for GameApp {
             } = &mut self.game;
             if let Some((network, time)) = resources.resource2_mut::<Network, Time>() {
                 // TODO: Pass &Time resource directly maybe?
-                network.update(scenes.current_scene_mut(), time.static_duration());
+                network.update(scenes.simulation_scene_mut(), time.static_duration());
             }
         }
 
@@ -117,9 +121,8 @@ impl eframe::App for GameApp {
                 let GameContext {
                     scenes, resources, ..
                 } = &mut self.game;
-                let scene = scenes.current_scene_mut();
-                scene.prepare();
-                scene.update(resources, &input);
+                scenes.prepare();
+                scenes.update(resources, &input);
                 ui.painter().text(
                     Pos2::new(0.0, ui.max_rect().height()),
                     Align2::LEFT_BOTTOM,
@@ -132,21 +135,25 @@ impl eframe::App for GameApp {
         {
             let GameApp { game, renderer, .. } = self;
             let render_state = frame.wgpu_render_state().unwrap();
-            let scene = game.scenes.current_scene();
+            let scenes = game.scenes.scenes_active_first();
             let (width, height) = Self::physical_size(ctx, &rect);
             if width != 0 && height != 0 {
                 renderer.resize_textures(width, height);
             }
-            if let Some((game_object, c_camera)) = scene.get_main_camera() {
+            let main_camera = scenes
+                .iter()
+                .find_map(|scene| Some((*scene, scene.get_main_camera()?)));
+            if let Some((scene, (game_object, c_camera))) = main_camera {
                 let transform = scene.get_world_transform(game_object);
 
-                let camera = Camera::new(
+                let mut camera = Camera::new(
                     rect.aspect_ratio(),
                     c_camera.fov,
                     c_camera.near_plane,
                     c_camera.far_plane,
                 );
-                renderer.render_scene(render_state, &camera, &transform, scene, None);
+                camera.culling_mask = c_camera.culling_mask;
+                renderer.render_scenes(render_state, &camera, &transform, &scenes, None);
             }
         }
 
@@ -0,0 +1,84 @@
+use egui::Key;
+use engine::component::{
+    Component, ComponentCharacterController, ComponentEventContext, ReflectComponent,
+};
+use engine::input::Input;
+use engine::reflect::{Reflect, ReflectDefault};
+use engine::resource::ResourceMap;
+use engine::utils::{ReflectTypeUuidDynamic, TypeUuid};
+use nalgebra_glm::Vec3;
+use serde::{Deserialize, Serialize};
+
+/// Walks a character controller around with WASD relative to the main camera,
+/// and jumps with space.
+#[derive(TypeUuid, Serialize, Deserialize, Component, Reflect)]
+#[uuid = "bd1237a6-f93e-46a8-a720-575d70d82c82"]
+#[reflect(Default, TypeUuidDynamic, Component)]
+#[reflect_attr(name = "Player", update)]
+#[serde(default)]
+#[repr(C)]
+pub struct ComponentPlayer {
+    #[reflect_attr(min = 0.0, speed = 0.1)]
+    pub speed: f32,
+    #[reflect_attr(min = 0.0, speed = 0.1)]
+    pub jump_speed: f32,
+    #[reflect_attr(min = 0.0, speed = 0.1)]
+    pub gravity: f32,
+    #[serde(skip)]
+    #[reflect_skip]
+    vertical_speed: f32,
+}
+
+impl Default for ComponentPlayer {
+    fn default() -> Self {
+        Self {
+            speed: 5.0,
+            jump_speed: 6.0,
+            gravity: 20.0,
+            vertical_speed: 0.0,
+        }
+    }
+}
+
+impl Component for ComponentPlayer {
+    fn update(
+        &mut self,
+        ComponentEventContext {
+            scene, game_object, ..
+        }: ComponentEventContext,
+        resources: &mut ResourceMap,
+        input: &Input,
+    ) {
+        let delta_time = resources.time().delta_time();
+        let (forward, right) = scene
+            .get_main_camera()
+            .map(|(camera, _)| {
+                let transform = scene.get_world_transform(camera);
+                (transform.forward(), transform.right())
+            })
+            .unwrap_or((Vec3::z(), Vec3::x()));
+        let flatten = |v: Vec3| Vec3::new(v.x, 0.0, v.z).try_normalize(f32::EPSILON);
+        let (forward, right) = (
+            flatten(forward).unwrap_or(Vec3::z()),
+            flatten(right).unwrap_or(Vec3::x()),
+        );
+
+        let key = |key: Key| input.input(|i| i.key_down(key)).unwrap_or(false) as i32 as f32;
+        let direction = forward * (key(Key::W) - key(Key::S)) + right * (key(Key::D) - key(Key::A));
+        let horizontal = direction.try_normalize(f32::EPSILON).unwrap_or_default() * self.speed;
+
+        let grounded = scene
+            .read_component(game_object, ComponentCharacterController::is_grounded)
+            .unwrap_or(false);
+        if grounded {
+            self.vertical_speed = 0.0;
+            if key(Key::Space) > 0.0 {
+                self.vertical_speed = self.jump_speed;
+            }
+        }
+        self.vertical_speed -= self.gravity * delta_time;
+
+        let desired_translation = (horizontal + Vec3::y() * self.vertical_speed) * delta_time;
+        scene.move_and_slide(game_object, desired_translation);
+    }
+}
@@ -0,0 +1,74 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use engine::error::BoxedError;
+
+pub const USAGE: &str = "\
+Usage: calyx-server <PROJECT_PATH> [OPTIONS]
+
+Options:
+  --scene <NAME>      Name of the scene asset to simulate [default: scene]
+  --bind <ADDR>       Socket address the server listens on [default: 0.0.0.0:54321]
+  --tick-rate <HZ>    Simulation and network ticks per second [default: 60]
+  --plugin <PATH>     Game plugin library exporting plugin_main [default: built project library]
+  -h, --help          Print this message";
+
+pub struct ServerArgs {
+    pub project_path: PathBuf,
+    pub scene: String,
+    pub bind_addr: SocketAddr,
+    pub tick_rate: f32,
+    pub plugin_path: Option<PathBuf>,
+}
+
+impl ServerArgs {
+    const DEFAULT_SCENE: &'static str = "scene";
+    const DEFAULT_BIND_ADDR: &'static str = "0.0.0.0:54321";
+    const DEFAULT_TICK_RATE: f32 = 60.0;
+
+    /// Parses the command line arguments, excluding the program name.
+    /// Returns `Ok(None)` when help was requested.
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, BoxedError> {
+        let mut project_path = None;
+        let mut scene = String::from(Self::DEFAULT_SCENE);
+        let mut bind_addr = Self::DEFAULT_BIND_ADDR.parse().unwrap();
+        let mut tick_rate = Self::DEFAULT_TICK_RATE;
+        let mut plugin_path = None;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--scene" => scene = Self::value(&mut args, &arg)?,
+                "--bind" => {
+                    bind_addr = Self::value(&mut args, &arg)?
+                        .parse()
+                        .map_err(|e| format!("Invalid bind address: {}", e))?
+                }
+                "--tick-rate" => {
+                    tick_rate = Self::value(&mut args, &arg)?
+                        .parse()
+                        .map_err(|e| format!("Invalid tick rate: {}", e))?;
+                    if !(tick_rate > 0.0 && tick_rate.is_finite()) {
+                        return Err("Tick rate must be a positive number".into());
+                    }
+                }
+                "--plugin" => plugin_path = Some(PathBuf::from(Self::value(&mut args, &arg)?)),
+                _ if arg.starts_with('-') => return Err(format!("Unknown option '{}'", arg).into()),
+                _ if project_path.is_none() => project_path = Some(PathBuf::from(arg)),
+                _ => return Err(format!("Unexpected argument '{}'", arg).into()),
+            }
+        }
+        let project_path = project_path.ok_or("Missing project path")?;
+        Ok(Some(Self {
+            project_path,
+            scene,
+            bind_addr,
+            tick_rate,
+            plugin_path,
+        }))
+    }
+
+    fn value(args: &mut impl Iterator<Item = String>, option: &str) -> Result<String, BoxedError> {
+        args.next()
+            .ok_or_else(|| format!("Missing value for option '{}'", option).into())
+    }
+}
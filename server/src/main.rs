@@ -0,0 +1,153 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use args::ServerArgs;
+use engine::context::{default_engine_assets_path, AssetContext, GameContext};
+use engine::core::Time;
+use engine::error::BoxedError;
+use engine::input::{Input, InputState};
+use engine::net::Network;
+use engine::scene::{LayerNames, Scene};
+use project::Project;
+use sharedlib::Lib;
+
+mod args;
+mod plugin;
+
+struct DedicatedServer {
+    game: GameContext,
+    egui_ctx: egui::Context,
+    tick_period: Duration,
+    // Dropped last, the scenes above hold components defined by the plugin
+    _plugin: Option<Lib>,
+}
+
+impl DedicatedServer {
+    fn new(args: &ServerArgs) -> Result<Self, BoxedError> {
+        let project_path = dunce::canonicalize(&args.project_path).map_err(Box::new)?;
+        let project = Project::load(project_path)?;
+        let assets =
+            AssetContext::headless(project.assets_directory(), default_engine_assets_path()?)?;
+        println!(
+            "SERVER - Loaded project '{}' (gpu: {})",
+            project.name(),
+            assets.render_context.has_gpu()
+        );
+
+        let plugin_path = match &args.plugin_path {
+            Some(path) => Some(path.clone()),
+            None => plugin::default_plugin_path(&project).ok(),
+        };
+        let plugin = Self::load_plugin(&assets, plugin_path);
+
+        let mut game = GameContext::new(assets.clone());
+        game.resources
+            .insert(LayerNames::new(project.layers().iter().cloned()));
+        let scene = assets
+            .asset_registry
+            .read()
+            .load::<Scene>(args.scene.as_str())
+            .map_err(|e| format!("Failed to load scene '{}': {:?}", args.scene, e))?;
+        game.scenes.load_scene(scene.readonly());
+        game.scenes.start_simulation();
+
+        game.resources.insert(Network::new(args.tick_rate));
+        game.resources.network_mut().host(args.bind_addr)?;
+        println!("SERVER - Listening on {}", args.bind_addr);
+
+        Ok(Self {
+            game,
+            egui_ctx: Default::default(),
+            tick_period: Duration::from_secs_f32(1.0 / args.tick_rate),
+            _plugin: plugin,
+        })
+    }
+
+    fn load_plugin(assets: &AssetContext, path: Option<PathBuf>) -> Option<Lib> {
+        let Some(path) = path else {
+            eprintln!("SERVER - No game plugin found, running with engine components only");
+            return None;
+        };
+        match plugin::load_plugin(assets, &path) {
+            Ok(lib) => {
+                println!("SERVER - Loaded game plugin {:?}", path);
+                Some(lib)
+            }
+            Err(err) => {
+                eprintln!("SERVER - Failed to load game plugin {:?}: {}", path, err);
+                None
+            }
+        }
+    }
+
+    fn run(&mut self, running: &AtomicBool) {
+        self.game.resources.time_mut().update_time();
+        let mut next_tick = Instant::now();
+        while running.load(Ordering::SeqCst) {
+            self.tick();
+            next_tick += self.tick_period;
+            let now = Instant::now();
+            if next_tick > now {
+                std::thread::sleep(next_tick - now);
+            } else {
+                // Running behind, skip the missed ticks instead of trying to catch up
+                next_tick = now;
+            }
+        }
+    }
+
+    fn tick(&mut self) {
+        let GameContext {
+            scenes, resources, ..
+        } = &mut self.game;
+        resources.time_mut().update_time();
+        if let Some((network, time)) = resources.resource2_mut::<Network, Time>() {
+            network.update(scenes.simulation_scene_mut(), time.static_duration());
+        }
+        let input = Input::from_ctx(&self.egui_ctx, None, InputState::default());
+        scenes.prepare();
+        scenes.update(resources, &input);
+    }
+
+    fn shutdown(&mut self) {
+        if let Some(server) = &mut self.game.resources.network_mut().server {
+            server.disconnect_all();
+        }
+        self.game.scenes.stop_simulation();
+        println!("SERVER - Shut down");
+    }
+}
+
+fn main() {
+    let args = match ServerArgs::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", args::USAGE);
+            return;
+        }
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, args::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(err) = ctrlc::set_handler(move || running.store(false, Ordering::SeqCst)) {
+            eprintln!("SERVER - Unable to install SIGINT handler: {}", err);
+        }
+    }
+
+    let mut server = match DedicatedServer::new(&args) {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("SERVER - {}", err);
+            std::process::exit(1);
+        }
+    };
+    server.run(&running);
+    server.shutdown();
+}
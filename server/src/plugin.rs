@@ -0,0 +1,43 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use engine::context::AssetContext;
+use engine::error::BoxedError;
+use engine::reflect::type_registry::TypeRegistry;
+use project::Project;
+use serde_json::Value;
+use sharedlib::{Lib, Symbol};
+
+/// Resolves the game library built for the project, the same way the editor does.
+pub fn default_plugin_path(project: &Project) -> Result<PathBuf, BoxedError> {
+    let output = Command::new("cargo")
+        .current_dir(project.root_directory())
+        .args(["metadata", "--format-version", "1", "--no-deps"])
+        .output()
+        .map_err(Box::new)?;
+    let json: Value = serde_json::from_slice(&output.stdout).map_err(Box::new)?;
+    let target_directory = json["target_directory"]
+        .as_str()
+        .ok_or("Unable to find the project target directory")?;
+    Ok(PathBuf::from(target_directory)
+        .join("debug")
+        .join(engine::utils::lib_file_name(project.name().as_str())))
+}
+
+/// Loads the game library, runs its `plugin_main` and refreshes the component registry.
+/// The returned library must outlive every scene that uses its components.
+pub fn load_plugin(assets: &AssetContext, path: &Path) -> Result<Lib, BoxedError> {
+    unsafe {
+        let lib = Lib::new(path).map_err(|e| format!("{}", e))?;
+        let load_fn = lib
+            .find_func::<extern "C" fn(&mut TypeRegistry), &str>("plugin_main")
+            .map_err(|e| format!("{}", e))?;
+        let mut type_registry = assets.type_registry.write();
+        load_fn.get()(&mut type_registry);
+        assets
+            .component_registry
+            .write()
+            .refresh_class_lists(&type_registry);
+        Ok(lib)
+    }
+}